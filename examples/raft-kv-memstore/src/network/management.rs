@@ -54,7 +54,7 @@ pub async fn init(app: Data<ExampleApp>) -> actix_web::Result<impl Responder> {
 /// Get the latest metrics of the cluster
 #[get("/metrics")]
 pub async fn metrics(app: Data<ExampleApp>) -> actix_web::Result<impl Responder> {
-    let metrics = app.raft.metrics().borrow().clone();
+    let metrics = app.raft.metrics_snapshot();
 
     let res: Result<RaftMetrics<ExampleNodeId, BasicNode>, Infallible> = Ok(metrics);
     Ok(Json(res))
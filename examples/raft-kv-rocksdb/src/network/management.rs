@@ -58,7 +58,7 @@ async fn init(req: Request<Arc<ExampleApp>>) -> tide::Result {
 
 /// Get the latest metrics of the cluster
 async fn metrics(req: Request<Arc<ExampleApp>>) -> tide::Result {
-    let metrics = req.state().raft.metrics().borrow().clone();
+    let metrics = req.state().raft.metrics_snapshot();
 
     let res: Result<RaftMetrics<ExampleNodeId, ExampleNode>, Infallible> = Ok(metrics);
     Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::Bound;
+use std::ops::Deref;
+use std::ops::RangeBounds;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use openraft::async_trait::async_trait;
+use openraft::storage::LogState;
+use openraft::storage::LogStats;
+use openraft::storage::RaftLogReader;
+use openraft::storage::Snapshot;
+use openraft::EffectiveMembership;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::RaftStorageDebug;
+use openraft::RaftTypeConfig;
+use openraft::SnapshotMeta;
+use openraft::StorageError;
+use openraft::Vote;
+
+/// The most recently appended log entries, with consecutive indexes.
+struct LogCache<C: RaftTypeConfig> {
+    entries: BTreeMap<u64, Entry<C>>,
+    capacity: usize,
+}
+
+impl<C: RaftTypeConfig> LogCache<C> {
+    /// Add entries that are written to the underlying store.
+    fn append(&mut self, entries: &[&Entry<C>]) {
+        for ent in entries {
+            let index = ent.log_id.index;
+
+            // Keep the indexes consecutive: an entry overrides all entries since its index, and an entry after a gap
+            // discards the cache.
+            self.entries.split_off(&index);
+            let last = self.entries.keys().next_back().copied();
+            if last.is_some() && last != Some(index - 1) {
+                self.entries.clear();
+            }
+
+            self.entries.insert(index, (*ent).clone());
+        }
+
+        if self.entries.len() > self.capacity {
+            let first = *self.entries.keys().nth(self.entries.len() - self.capacity).unwrap();
+            self.entries = self.entries.split_off(&first);
+        }
+    }
+
+    /// Remove entries since `index`, inclusive.
+    fn truncate(&mut self, index: u64) {
+        self.entries.split_off(&index);
+    }
+
+    /// Remove all entries.
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Remove entries upto `index`, inclusive.
+    fn purge(&mut self, index: u64) {
+        self.entries = self.entries.split_off(&(index + 1));
+    }
+
+    /// Returns the entries in `[start, end)` if the cache has all of the present ones.
+    ///
+    /// The cache holds the tail of the log, thus entries after the last cached one do not exist.
+    fn get(&self, start: u64, end: u64) -> Option<Vec<Entry<C>>> {
+        let first = *self.entries.keys().next()?;
+        if start < first {
+            return None;
+        }
+
+        Some(self.entries.range(start..end).map(|(_, ent)| ent.clone()).collect())
+    }
+}
+
+/// A store that wraps another store and keeps the most recently appended log entries in memory, to serve reads of
+/// the tail of the log without reading the underlying store.
+///
+/// Writes are passed through to the underlying store and then applied to the cache. Entries are removed from the
+/// cache before they are deleted from the underlying store, thus the cache never returns deleted entries. If
+/// deleting conflicting entries from the underlying store fails, the cache is cleared.
+///
+/// The cache is only used by the methods of this store. The log reader returned by `get_log_reader()` reads the
+/// underlying store directly.
+///
+/// All writes to the log have to go through this store. Writing the underlying store directly makes the cache stale.
+pub struct CachedStore<C: RaftTypeConfig, S: RaftStorage<C>> {
+    cache: Arc<Mutex<LogCache<C>>>,
+    hits: Arc<AtomicU64>,
+    inner: S,
+    c: PhantomData<C>,
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone> Clone for CachedStore<C, S> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            hits: self.hits.clone(),
+            inner: self.inner.clone(),
+            c: PhantomData,
+        }
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C>> Deref for CachedStore<C, S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C>> CachedStore<C, S> {
+    /// Create a `CachedStore` backed by another store, which caches at most `capacity` log entries.
+    pub fn new(inner: S, capacity: usize) -> Self {
+        Self {
+            cache: Arc::new(Mutex::new(LogCache {
+                entries: BTreeMap::new(),
+                capacity,
+            })),
+            hits: Arc::new(AtomicU64::new(0)),
+            inner,
+            c: PhantomData,
+        }
+    }
+
+    /// The number of reads served by the cache.
+    pub fn cache_hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The indexes of the cached log entries.
+    pub fn cached_indexes(&self) -> Vec<u64> {
+        self.cache.lock().unwrap().entries.keys().copied().collect()
+    }
+
+    fn get_cached<RB: RangeBounds<u64>>(&self, range: &RB) -> Option<Vec<Entry<C>>> {
+        let start = match range.start_bound() {
+            Bound::Included(i) => *i,
+            Bound::Excluded(i) => i.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(i) => i.saturating_add(1),
+            Bound::Excluded(i) => *i,
+            Bound::Unbounded => u64::MAX,
+        };
+
+        let entries = self.cache.lock().unwrap().get(start, end)?;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entries)
+    }
+}
+
+#[async_trait]
+impl<C, S, SM> RaftStorageDebug<SM> for CachedStore<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C> + RaftStorageDebug<SM>,
+{
+    async fn get_state_machine(&mut self) -> SM {
+        self.inner.get_state_machine().await
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftLogReader<C> for CachedStore<C, S> {
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        if let Some(entries) = self.get_cached(&range) {
+            return Ok(entries);
+        }
+
+        self.inner.try_get_log_entries(range).await
+    }
+
+    async fn get_log_term(&mut self, log_index: u64) -> Result<Option<u64>, StorageError<C::NodeId>> {
+        if let Some(entries) = self.get_cached(&(log_index..=log_index)) {
+            return Ok(entries.first().map(|ent| ent.log_id.leader_id.term));
+        }
+
+        self.inner.get_log_term(log_index).await
+    }
+
+    async fn get_log_entries_limited<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+        max_entries: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        let entries = match self.get_cached(&range) {
+            None => return self.inner.get_log_entries_limited(range, max_entries, max_bytes).await,
+            Some(x) => x,
+        };
+
+        // The same as the underlying store: at least one entry is returned.
+        let mut bytes = 0;
+        let mut res = vec![];
+        for ent in entries.into_iter().take(max_entries as usize) {
+            bytes += self.inner.serialized_size(&ent.payload).unwrap_or_default();
+            if !res.is_empty() && bytes > max_bytes {
+                break;
+            }
+            res.push(ent);
+        }
+
+        Ok(res)
+    }
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.inner.get_log_state().await
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftStorage<C> for CachedStore<C, S> {
+    type SnapshotData = S::SnapshotData;
+
+    type LogReader = S::LogReader;
+
+    type SnapshotBuilder = S::SnapshotBuilder;
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.save_vote(vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.inner.read_vote().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.inner.get_log_reader().await
+    }
+
+    fn serialized_size(&self, payload: &EntryPayload<C>) -> Option<u64> {
+        self.inner.serialized_size(payload)
+    }
+
+    async fn log_stats(&mut self) -> Result<LogStats, StorageError<C::NodeId>> {
+        self.inner.log_stats().await
+    }
+
+    async fn append_to_log(&mut self, entries: &[&Entry<C>]) -> Result<(), StorageError<C::NodeId>> {
+        let res = self.inner.append_to_log(entries).await;
+
+        let mut cache = self.cache.lock().unwrap();
+        match res {
+            Ok(()) => cache.append(entries),
+            // The underlying store may have written some of the entries.
+            Err(_) => cache.entries.clear(),
+        }
+
+        res
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.cache.lock().unwrap().truncate(log_id.index);
+
+        let res = self.inner.delete_conflict_logs_since(log_id).await;
+        if res.is_err() {
+            // It is unknown what is left in the underlying store, do not serve any read from the cache.
+            self.cache.lock().unwrap().clear();
+        }
+        res
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.cache.lock().unwrap().purge(log_id.index);
+        self.inner.purge_logs_upto(log_id).await
+    }
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        self.inner.last_applied_state().await
+    }
+
+    async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
+        self.inner.apply_to_state_machine(entries).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.inner.get_snapshot_builder().await
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<C::NodeId>> {
+        self.inner.begin_receiving_snapshot().await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<Self::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        // Installing a snapshot may remove logs from the underlying store.
+        self.cache.lock().unwrap().entries.clear();
+        self.inner.install_snapshot(meta, snapshot).await
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>> {
+        self.inner.get_current_snapshot().await
+    }
+
+    async fn get_snapshot_by_id(
+        &mut self,
+        snapshot_id: &str,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>> {
+        self.inner.get_snapshot_by_id(snapshot_id).await
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use openraft::async_trait::async_trait;
+use openraft::storage::LogState;
+use openraft::storage::LogStats;
+use openraft::storage::RaftLogReader;
+use openraft::storage::RaftSnapshotBuilder;
+use openraft::storage::Snapshot;
+use openraft::AnyError;
+use openraft::EffectiveMembership;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::ErrorSubject;
+use openraft::ErrorVerb;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::RaftStorageDebug;
+use openraft::RaftTypeConfig;
+use openraft::SnapshotMeta;
+use openraft::StorageError;
+use openraft::StorageIOError;
+use openraft::Vote;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+/// A storage method into which a [`FaultyStore`] can inject failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FaultPoint {
+    /// `RaftStorage::append_to_log()`.
+    AppendToLog,
+
+    /// `RaftStorage::apply_to_state_machine()`.
+    ApplyToStateMachine,
+
+    /// `RaftSnapshotBuilder::build_snapshot()`, i.e., log compaction.
+    BuildSnapshot,
+}
+
+/// A predicate on the payload of a log entry, see [`FaultPolicy::Payload`].
+pub type PayloadPredicate<C> = Arc<dyn Fn(&EntryPayload<C>) -> bool + Send + Sync + 'static>;
+
+/// When a [`FaultPoint`] of a [`FaultyStore`] fails.
+pub enum FaultPolicy<C: RaftTypeConfig> {
+    /// Fail only the `n`-th call, counting from 1. Other calls are passed to the underlying store.
+    NthCall(u64),
+
+    /// Fail every call with probability `p`, which must be in `[0, 1]`.
+    ///
+    /// The random numbers are generated from `seed`, thus the calls that fail are the same in every run.
+    Probability { p: f64, seed: u64 },
+
+    /// Fail every call with at least one entry whose payload matches the predicate, e.g.,
+    /// `FaultPolicy::Payload(Arc::new(|p| matches!(p, EntryPayload::Membership(_))))`.
+    ///
+    /// The whole call fails, thus none of the entries in it, not even those before the matching one, reaches the
+    /// underlying store. Calls without entries, such as [`FaultPoint::BuildSnapshot`], never fail.
+    Payload(PayloadPredicate<C>),
+}
+
+impl<C: RaftTypeConfig> Clone for FaultPolicy<C> {
+    fn clone(&self) -> Self {
+        match self {
+            FaultPolicy::NthCall(n) => FaultPolicy::NthCall(*n),
+            FaultPolicy::Probability { p, seed } => FaultPolicy::Probability { p: *p, seed: *seed },
+            FaultPolicy::Payload(pred) => FaultPolicy::Payload(pred.clone()),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> Debug for FaultPolicy<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaultPolicy::NthCall(n) => f.debug_tuple("NthCall").field(n).finish(),
+            FaultPolicy::Probability { p, seed } => {
+                f.debug_struct("Probability").field("p", p).field("seed", seed).finish()
+            }
+            FaultPolicy::Payload(_) => f.debug_tuple("Payload").finish(),
+        }
+    }
+}
+
+/// The state of a [`FaultPolicy`] installed on a [`FaultPoint`].
+struct Fault<C: RaftTypeConfig> {
+    policy: FaultPolicy<C>,
+
+    /// The number of calls made so far, including the failed ones.
+    calls: u64,
+
+    /// Whether the injected errors are marked as transient, see `StorageIOError::transient`.
+    transient: bool,
+
+    rng: StdRng,
+}
+
+impl<C: RaftTypeConfig> Fault<C> {
+    fn new(policy: FaultPolicy<C>, transient: bool) -> Self {
+        let seed = match policy {
+            FaultPolicy::NthCall(_) | FaultPolicy::Payload(_) => 0,
+            FaultPolicy::Probability { p, seed } => {
+                assert!((0.0..=1.0).contains(&p), "probability must be in [0, 1], got: {}", p);
+                seed
+            }
+        };
+
+        Self {
+            policy,
+            calls: 0,
+            transient,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Count a call with the given entries and decide if it should fail.
+    fn should_fail(&mut self, entries: &[&Entry<C>]) -> bool {
+        self.calls += 1;
+
+        match &self.policy {
+            FaultPolicy::NthCall(n) => self.calls == *n,
+            FaultPolicy::Probability { p, .. } => self.rng.gen_bool(*p),
+            FaultPolicy::Payload(pred) => entries.iter().any(|ent| pred(&ent.payload)),
+        }
+    }
+}
+
+/// Faults shared by a [`FaultyStore`], its clones and the snapshot builders it returns.
+struct Faults<C: RaftTypeConfig> {
+    inner: Arc<Mutex<BTreeMap<FaultPoint, Fault<C>>>>,
+}
+
+impl<C: RaftTypeConfig> Clone for Faults<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> Default for Faults<C> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+}
+
+impl<C: RaftTypeConfig> Faults<C> {
+    fn check(
+        &self,
+        point: FaultPoint,
+        entries: &[&Entry<C>],
+        subject: ErrorSubject<C::NodeId>,
+        verb: ErrorVerb,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        let mut faults = self.inner.lock().unwrap();
+
+        let fault = match faults.get_mut(&point) {
+            None => return Ok(()),
+            Some(f) => f,
+        };
+
+        if fault.should_fail(entries) {
+            tracing::info!(?point, calls = fault.calls, "inject storage failure");
+
+            let err = StorageIOError::new(
+                subject,
+                verb,
+                AnyError::error(format!("injected failure: {:?}, call: {}", point, fault.calls)),
+            );
+
+            let err = if fault.transient { err.transient() } else { err };
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// A store that wraps another store and returns `StorageError` from some of its methods, for testing how raft
+/// deals with storage failures.
+///
+/// A [`FaultPolicy`] is installed for each [`FaultPoint`] with [`FaultyStore::fail`]. Methods without a policy are
+/// passed to the underlying store. A failed call does not reach the underlying store.
+///
+/// E.g., to fail the 3rd `append_to_log()`:
+/// `FaultyStore::new(sto).fail(FaultPoint::AppendToLog, FaultPolicy::NthCall(3))`.
+pub struct FaultyStore<C: RaftTypeConfig, S: RaftStorage<C>> {
+    faults: Faults<C>,
+    inner: S,
+    c: PhantomData<C>,
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Clone> Clone for FaultyStore<C, S> {
+    fn clone(&self) -> Self {
+        Self {
+            faults: self.faults.clone(),
+            inner: self.inner.clone(),
+            c: PhantomData,
+        }
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C> + Default> Default for FaultyStore<C, S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C>> Deref for FaultyStore<C, S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<C: RaftTypeConfig, S: RaftStorage<C>> FaultyStore<C, S> {
+    /// Create a `FaultyStore` backed by another store, without any fault installed.
+    pub fn new(inner: S) -> Self {
+        Self {
+            faults: Faults::default(),
+            inner,
+            c: PhantomData,
+        }
+    }
+
+    /// Install a fault policy on a method, replacing the previous one and resetting its call count.
+    ///
+    /// It affects all clones of this store and the snapshot builders returned by it.
+    ///
+    /// It panics if the probability of a [`FaultPolicy::Probability`] is not in `[0, 1]`.
+    pub fn fail(self, point: FaultPoint, policy: FaultPolicy<C>) -> Self {
+        self.set_fault(point, policy);
+        self
+    }
+
+    /// The same as [`FaultyStore::fail`], but does not consume the store.
+    pub fn set_fault(&self, point: FaultPoint, policy: FaultPolicy<C>) {
+        self.faults.inner.lock().unwrap().insert(point, Fault::new(policy, false));
+    }
+
+    /// The same as [`FaultyStore::fail`], but the injected errors are transient, which raft retries before giving up.
+    ///
+    /// A failed call does not reach the underlying store, thus it is always safe to retry it.
+    pub fn fail_transient(self, point: FaultPoint, policy: FaultPolicy<C>) -> Self {
+        self.set_transient_fault(point, policy);
+        self
+    }
+
+    /// The same as [`FaultyStore::fail_transient`], but does not consume the store.
+    pub fn set_transient_fault(&self, point: FaultPoint, policy: FaultPolicy<C>) {
+        self.faults.inner.lock().unwrap().insert(point, Fault::new(policy, true));
+    }
+
+    /// Remove the fault policy on a method.
+    pub fn clear_fault(&self, point: FaultPoint) {
+        self.faults.inner.lock().unwrap().remove(&point);
+    }
+
+    /// The number of calls to a method since its fault policy is installed, including the failed ones.
+    ///
+    /// It returns 0 if there is no fault policy on it.
+    pub fn calls(&self, point: FaultPoint) -> u64 {
+        self.faults.inner.lock().unwrap().get(&point).map(|f| f.calls).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl<C, S, SM> RaftStorageDebug<SM> for FaultyStore<C, S>
+where
+    C: RaftTypeConfig,
+    S: RaftStorage<C> + RaftStorageDebug<SM>,
+{
+    async fn get_state_machine(&mut self) -> SM {
+        self.inner.get_state_machine().await
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftLogReader<C> for FaultyStore<C, S> {
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.inner.try_get_log_entries(range).await
+    }
+
+    async fn get_log_term(&mut self, log_index: u64) -> Result<Option<u64>, StorageError<C::NodeId>> {
+        self.inner.get_log_term(log_index).await
+    }
+
+    async fn get_log_entries_limited<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+        max_entries: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.inner.get_log_entries_limited(range, max_entries, max_bytes).await
+    }
+
+    async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
+        self.inner.get_log_state().await
+    }
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftStorage<C> for FaultyStore<C, S> {
+    type SnapshotData = S::SnapshotData;
+
+    type LogReader = S::LogReader;
+
+    type SnapshotBuilder = FaultySnapshotBuilder<C, S>;
+
+    async fn save_vote(&mut self, vote: &Vote<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.save_vote(vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
+        self.inner.read_vote().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.inner.get_log_reader().await
+    }
+
+    fn serialized_size(&self, payload: &EntryPayload<C>) -> Option<u64> {
+        self.inner.serialized_size(payload)
+    }
+
+    async fn log_stats(&mut self) -> Result<LogStats, StorageError<C::NodeId>> {
+        self.inner.log_stats().await
+    }
+
+    async fn append_to_log(&mut self, entries: &[&Entry<C>]) -> Result<(), StorageError<C::NodeId>> {
+        self.faults.check(FaultPoint::AppendToLog, entries, ErrorSubject::Logs, ErrorVerb::Write)?;
+        self.inner.append_to_log(entries).await
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.delete_conflict_logs_since(log_id).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<C::NodeId>) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.purge_logs_upto(log_id).await
+    }
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<C::NodeId>>, EffectiveMembership<C::NodeId, C::Node>), StorageError<C::NodeId>> {
+        self.inner.last_applied_state().await
+    }
+
+    async fn apply_to_state_machine(&mut self, entries: &[&Entry<C>]) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
+        let subject = match entries.first() {
+            Some(ent) => ErrorSubject::Apply(ent.log_id),
+            None => ErrorSubject::StateMachine,
+        };
+
+        self.faults.check(FaultPoint::ApplyToStateMachine, entries, subject, ErrorVerb::Write)?;
+        self.inner.apply_to_state_machine(entries).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        FaultySnapshotBuilder {
+            faults: self.faults.clone(),
+            inner: self.inner.get_snapshot_builder().await,
+        }
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<C::NodeId>> {
+        self.inner.begin_receiving_snapshot().await
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+        snapshot: Box<Self::SnapshotData>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        self.inner.install_snapshot(meta, snapshot).await
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>> {
+        self.inner.get_current_snapshot().await
+    }
+
+    async fn get_snapshot_by_id(
+        &mut self,
+        snapshot_id: &str,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>> {
+        self.inner.get_snapshot_by_id(snapshot_id).await
+    }
+}
+
+/// The snapshot builder of a [`FaultyStore`], which fails according to the policy on
+/// [`FaultPoint::BuildSnapshot`].
+pub struct FaultySnapshotBuilder<C: RaftTypeConfig, S: RaftStorage<C>> {
+    faults: Faults<C>,
+    inner: S::SnapshotBuilder,
+}
+
+#[async_trait]
+impl<C: RaftTypeConfig, S: RaftStorage<C>> RaftSnapshotBuilder<C, S::SnapshotData> for FaultySnapshotBuilder<C, S> {
+    async fn build_snapshot(
+        &mut self,
+    ) -> Result<Snapshot<C::NodeId, C::Node, S::SnapshotData>, StorageError<C::NodeId>> {
+        self.faults.check(
+            FaultPoint::BuildSnapshot,
+            &[],
+            ErrorSubject::StateMachine,
+            ErrorVerb::Read,
+        )?;
+        self.inner.build_snapshot().await
+    }
+}
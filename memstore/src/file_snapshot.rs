@@ -0,0 +1,652 @@
+use std::fmt::Debug;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::SeekFrom;
+use std::ops::Deref;
+use std::ops::RangeBounds;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use openraft::async_trait::async_trait;
+use openraft::storage::LogState;
+use openraft::storage::LogStats;
+use openraft::storage::RaftLogReader;
+use openraft::storage::RaftSnapshotBuilder;
+use openraft::storage::Snapshot;
+use openraft::AnyError;
+use openraft::AppData;
+use openraft::AppDataResponse;
+use openraft::EffectiveMembership;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::ErrorSubject;
+use openraft::ErrorVerb;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::RaftStorageDebug;
+use openraft::SnapshotMeta;
+use openraft::StorageError;
+use openraft::StorageIOError;
+use openraft::Vote;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::fs;
+use tokio::fs::File;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::ReadBuf;
+use tokio::sync::RwLock;
+
+use crate::ClientRequest;
+use crate::ClientResponse;
+use crate::ClientState;
+use crate::MemConfig;
+use crate::MemNodeId;
+use crate::MemStore;
+use crate::MemStoreStateMachine;
+use crate::StateMachineApply;
+
+/// Snapshot data in a file, which is read and written through the file handle, without loading it into memory.
+#[derive(Debug)]
+pub struct SnapshotFile {
+    file: File,
+    path: PathBuf,
+}
+
+impl SnapshotFile {
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsyncRead for SnapshotFile {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_read(cx, buf)
+    }
+}
+
+impl AsyncSeek for SnapshotFile {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().file).start_seek(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Pin::new(&mut self.get_mut().file).poll_complete(cx)
+    }
+}
+
+impl AsyncWrite for SnapshotFile {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().file).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_shutdown(cx)
+    }
+}
+
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+const TEMP_PREFIX: &str = "tmp-";
+
+/// The directory of snapshot files, shared by a [`FileSnapshotStore`], its clones and the snapshot builders it
+/// returns.
+struct SnapshotDir {
+    path: PathBuf,
+
+    /// For generating the names of the files being written.
+    seq: AtomicU64,
+
+    /// The meta of the current snapshot, whose file is the only one kept in the directory.
+    ///
+    /// It is locked while the file is opened or replaced, so that a file is never removed while being opened.
+    current: RwLock<Option<SnapshotMeta<MemNodeId, ()>>>,
+}
+
+impl SnapshotDir {
+    /// Returns the path of the file of a snapshot.
+    ///
+    /// The snapshot id is used as a file name, thus it may only contain ASCII letters, digits, `-`, `_` and `.`, and
+    /// must not start with `.`. Otherwise an `InvalidInput` error is returned.
+    fn snapshot_path(&self, snapshot_id: &str) -> io::Result<PathBuf> {
+        let valid = !snapshot_id.is_empty()
+            && !snapshot_id.starts_with('.')
+            && snapshot_id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b'.');
+
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("snapshot id can not be used as a file name: {:?}", snapshot_id),
+            ));
+        }
+
+        Ok(self.path.join(format!("{}{}", SNAPSHOT_PREFIX, snapshot_id)))
+    }
+
+    fn temp_path(&self) -> PathBuf {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        self.path.join(format!("{}{}", TEMP_PREFIX, seq))
+    }
+
+    /// Returns a snapshot that reads the file of the current snapshot, if its id is `snapshot_id`, or whatever its id
+    /// is if `snapshot_id` is `None`.
+    async fn open_current(
+        &self,
+        snapshot_id: Option<&str>,
+    ) -> Result<Option<Snapshot<MemNodeId, (), SnapshotFile>>, StorageError<MemNodeId>> {
+        let current = self.current.read().await;
+
+        let meta = match &*current {
+            Some(m) if snapshot_id.map(|id| id == m.snapshot_id).unwrap_or(true) => m.clone(),
+            _ => return Ok(None),
+        };
+
+        let io_err =
+            |e: io::Error| StorageError::from_io_error(ErrorSubject::Snapshot(meta.signature()), ErrorVerb::Read, e);
+
+        let path = self.snapshot_path(&meta.snapshot_id).map_err(io_err)?;
+        let file = File::open(&path).await.map_err(io_err)?;
+
+        Ok(Some(Snapshot {
+            meta,
+            snapshot: Box::new(SnapshotFile { file, path }),
+        }))
+    }
+
+    /// Make the complete snapshot file at `temp` the file of the current snapshot `meta`, and remove the file of
+    /// the superseded one.
+    ///
+    /// `apply` is called after the file is in place and before the current snapshot is updated, e.g., to replace
+    /// the state machine with the snapshot.
+    async fn replace_current<Fu>(&self, meta: &SnapshotMeta<MemNodeId, ()>, temp: &Path, apply: Fu) -> io::Result<()>
+    where Fu: std::future::Future<Output = ()> {
+        let path = self.snapshot_path(&meta.snapshot_id)?;
+
+        let mut current = self.current.write().await;
+
+        fs::rename(temp, &path).await?;
+        apply.await;
+
+        let prev = current.replace(meta.clone());
+
+        if let Some(prev) = prev {
+            if prev.snapshot_id != meta.snapshot_id {
+                // Safe unwrap(): the id of the current snapshot is checked when it is installed or built.
+                remove_file(&self.snapshot_path(&prev.snapshot_id).unwrap()).await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove a file, logging instead of returning an error, since a file left behind does no harm.
+///
+/// A snapshot that is being read, e.g., sent to a follower, is still readable after its file is removed.
+async fn remove_file(path: &Path) {
+    if let Err(e) = fs::remove_file(path).await {
+        tracing::warn!(
+            error = display(&e),
+            path = debug(path),
+            "failed to remove snapshot file"
+        );
+    }
+}
+
+/// Encode the state machine into the file at `path`.
+async fn write_state_machine<T>(sm: T, path: PathBuf) -> io::Result<()>
+where T: Serialize + Send + 'static {
+    tokio::task::spawn_blocking(move || {
+        let mut w = BufWriter::new(std::fs::File::create(&path)?);
+        serde_json::to_writer(&mut w, &sm)?;
+        let f = w.into_inner().map_err(|e| e.into_error())?;
+        f.sync_all()
+    })
+    .await
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+}
+
+/// Decode the state machine from the file at `path`.
+async fn read_state_machine<T>(path: PathBuf) -> io::Result<T>
+where T: DeserializeOwned + Send + 'static {
+    tokio::task::spawn_blocking(move || {
+        let r = BufReader::new(std::fs::File::open(&path)?);
+        let sm = serde_json::from_reader(r)?;
+        Ok(sm)
+    })
+    .await
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+}
+
+/// A store that wraps a [`MemStore`] and keeps its snapshot in a file, so that a snapshot is built, sent to other
+/// nodes and received from the leader by streaming a file, instead of holding the whole snapshot in memory.
+///
+/// The snapshot data of this store is a [`SnapshotFile`], with the JSON encoded state machine in it. The file is the
+/// only copy of the snapshot: the `MemStore` does not hold one.
+/// - A snapshot is built by encoding the state machine directly into a new file.
+/// - A snapshot being received is written to a new file. When it is installed, the state machine is decoded from the
+///   file.
+///
+/// Only the current snapshot is kept: the file of a superseded snapshot is removed. When the store is created, the
+/// newest snapshot left in the directory, e.g., by a previous process, becomes the current snapshot, and the state
+/// machine is recovered from it if it is newer. The other snapshot files and temp files are removed.
+///
+/// The snapshot directory must exist.
+pub struct FileSnapshotStore<D = ClientRequest, R = ClientResponse, A = ClientState>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    dir: Arc<SnapshotDir>,
+    inner: Arc<MemStore<D, R, A>>,
+}
+
+impl<D, R, A> Clone for FileSnapshotStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            dir: self.dir.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<D, R, A> Deref for FileSnapshotStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    type Target = Arc<MemStore<D, R, A>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<D, R, A> FileSnapshotStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    /// Create a `FileSnapshotStore` backed by a `MemStore`, which keeps snapshot files in the directory `dir`.
+    ///
+    /// The newest snapshot in `dir` that can be decoded becomes the current snapshot.
+    pub async fn new(inner: Arc<MemStore<D, R, A>>, dir: impl Into<PathBuf>) -> Self {
+        let dir = SnapshotDir {
+            path: dir.into(),
+            seq: AtomicU64::new(0),
+            current: RwLock::new(None),
+        };
+
+        if let Some((meta, sm)) = Self::recover_snapshot(&dir).await {
+            tracing::info!(snapshot = debug(&meta), "recovered snapshot from file");
+
+            if inner.sm.read().await.last_applied_log < meta.last_log_id {
+                inner.replace_state_machine(sm).await;
+            }
+            *dir.current.write().await = Some(meta);
+        }
+
+        Self {
+            dir: Arc::new(dir),
+            inner,
+        }
+    }
+
+    /// Find the newest snapshot in the directory that can be decoded, and remove the other snapshot files and the
+    /// temp files left, e.g., by a previous process.
+    async fn recover_snapshot(dir: &SnapshotDir) -> Option<(SnapshotMeta<MemNodeId, ()>, MemStoreStateMachine<A>)> {
+        let mut entries = match fs::read_dir(&dir.path).await {
+            Ok(x) => x,
+            Err(e) => {
+                tracing::warn!(
+                    error = display(&e),
+                    path = debug(&dir.path),
+                    "failed to read snapshot dir"
+                );
+                return None;
+            }
+        };
+
+        let mut newest: Option<(SnapshotMeta<MemNodeId, ()>, MemStoreStateMachine<A>, PathBuf)> = None;
+
+        loop {
+            let entry = match entries.next_entry().await {
+                Ok(Some(x)) => x,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!(
+                        error = display(&e),
+                        path = debug(&dir.path),
+                        "failed to read snapshot dir"
+                    );
+                    break;
+                }
+            };
+
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if name.starts_with(TEMP_PREFIX) {
+                remove_file(&path).await;
+                continue;
+            }
+
+            let snapshot_id = match name.strip_prefix(SNAPSHOT_PREFIX) {
+                Some(x) if dir.snapshot_path(x).is_ok() => x.to_string(),
+                Some(_) => {
+                    remove_file(&path).await;
+                    continue;
+                }
+                None => continue,
+            };
+
+            let sm = match read_state_machine::<MemStoreStateMachine<A>>(path.clone()).await {
+                Ok(x) => x,
+                Err(e) => {
+                    tracing::warn!(
+                        error = display(&e),
+                        path = debug(&path),
+                        "failed to decode snapshot file, remove it"
+                    );
+                    remove_file(&path).await;
+                    continue;
+                }
+            };
+
+            let meta = SnapshotMeta {
+                last_log_id: sm.last_applied_log,
+                last_membership: sm.last_membership.clone(),
+                snapshot_id,
+            };
+
+            // Keep the newer one of the two, and remove the other.
+            let stale = match newest.take() {
+                Some(prev) if prev.0.last_log_id >= meta.last_log_id => {
+                    newest = Some(prev);
+                    path
+                }
+                prev => {
+                    newest = Some((meta, sm, path));
+                    match prev {
+                        Some((_, _, prev_path)) => prev_path,
+                        None => continue,
+                    }
+                }
+            };
+            remove_file(&stale).await;
+        }
+
+        newest.map(|(meta, sm, _)| (meta, sm))
+    }
+
+    /// The path of the file of the snapshot `snapshot_id`.
+    ///
+    /// It returns an error if `snapshot_id` can not be used as a file name.
+    pub fn snapshot_path(&self, snapshot_id: &str) -> io::Result<PathBuf> {
+        self.dir.snapshot_path(snapshot_id)
+    }
+
+    /// Replace the state machine with the one decoded from the received snapshot file at `path`, and keep the file
+    /// as the file of the current snapshot.
+    async fn install_snapshot_file(
+        &self,
+        meta: &SnapshotMeta<MemNodeId, ()>,
+        file: &mut File,
+        path: &Path,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        let io_err = |verb: ErrorVerb, e: io::Error| {
+            StorageError::from_io_error(ErrorSubject::Snapshot(meta.signature()), verb, e)
+        };
+
+        // Check the id before decoding, since the file is renamed after it.
+        self.dir.snapshot_path(&meta.snapshot_id).map_err(|e| io_err(ErrorVerb::Write, e))?;
+
+        file.flush().await.map_err(|e| io_err(ErrorVerb::Write, e))?;
+        file.sync_all().await.map_err(|e| io_err(ErrorVerb::Write, e))?;
+
+        tracing::info!(snapshot = debug(meta), "decoding snapshot file for installation");
+
+        // A snapshot that can not be decoded is likely corrupted during transfer, and re-sending it may fix it.
+        let new_sm = read_state_machine::<MemStoreStateMachine<A>>(path.to_path_buf()).await.map_err(|e| {
+            StorageIOError::new(
+                ErrorSubject::Snapshot(meta.signature()),
+                ErrorVerb::Read,
+                AnyError::new(&e),
+            )
+            .transient()
+        })?;
+
+        self.dir
+            .replace_current(meta, path, self.inner.replace_state_machine(new_sm))
+            .await
+            .map_err(|e| io_err(ErrorVerb::Write, e))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<D, R, A> RaftStorageDebug<MemStoreStateMachine<A>> for FileSnapshotStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    async fn get_state_machine(&mut self) -> MemStoreStateMachine<A> {
+        self.inner.get_state_machine().await
+    }
+}
+
+#[async_trait]
+impl<D, R, A> RaftLogReader<MemConfig<D, R>> for FileSnapshotStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+    ) -> Result<Vec<Entry<MemConfig<D, R>>>, StorageError<MemNodeId>> {
+        self.inner.try_get_log_entries(range).await
+    }
+
+    async fn get_log_term(&mut self, log_index: u64) -> Result<Option<u64>, StorageError<MemNodeId>> {
+        self.inner.get_log_term(log_index).await
+    }
+
+    async fn get_log_entries_limited<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+        max_entries: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<Entry<MemConfig<D, R>>>, StorageError<MemNodeId>> {
+        self.inner.get_log_entries_limited(range, max_entries, max_bytes).await
+    }
+
+    async fn get_log_state(&mut self) -> Result<LogState<MemConfig<D, R>>, StorageError<MemNodeId>> {
+        self.inner.get_log_state().await
+    }
+}
+
+#[async_trait]
+impl<D, R, A> RaftStorage<MemConfig<D, R>> for FileSnapshotStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    type SnapshotData = SnapshotFile;
+
+    type LogReader = Arc<MemStore<D, R, A>>;
+
+    type SnapshotBuilder = FileSnapshotBuilder<D, R, A>;
+
+    async fn save_vote(&mut self, vote: &Vote<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.inner.save_vote(vote).await
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<MemNodeId>>, StorageError<MemNodeId>> {
+        self.inner.read_vote().await
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.inner.get_log_reader().await
+    }
+
+    fn serialized_size(&self, payload: &EntryPayload<MemConfig<D, R>>) -> Option<u64> {
+        self.inner.serialized_size(payload)
+    }
+
+    async fn log_stats(&mut self) -> Result<LogStats, StorageError<MemNodeId>> {
+        self.inner.log_stats().await
+    }
+
+    async fn append_to_log(&mut self, entries: &[&Entry<MemConfig<D, R>>]) -> Result<(), StorageError<MemNodeId>> {
+        self.inner.append_to_log(entries).await
+    }
+
+    async fn delete_conflict_logs_since(&mut self, log_id: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.inner.delete_conflict_logs_since(log_id).await
+    }
+
+    async fn purge_logs_upto(&mut self, log_id: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
+        self.inner.purge_logs_upto(log_id).await
+    }
+
+    async fn last_applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<MemNodeId>>, EffectiveMembership<MemNodeId, ()>), StorageError<MemNodeId>> {
+        self.inner.last_applied_state().await
+    }
+
+    async fn apply_to_state_machine(
+        &mut self,
+        entries: &[&Entry<MemConfig<D, R>>],
+    ) -> Result<Vec<R>, StorageError<MemNodeId>> {
+        self.inner.apply_to_state_machine(entries).await
+    }
+
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        FileSnapshotBuilder {
+            dir: self.dir.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<MemNodeId>> {
+        let path = self.dir.temp_path();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .await
+            .map_err(|e| StorageError::from_io_error(ErrorSubject::Store, ErrorVerb::Write, e))?;
+
+        Ok(Box::new(SnapshotFile { file, path }))
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<MemNodeId, ()>,
+        snapshot: Box<Self::SnapshotData>,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        let SnapshotFile { mut file, path } = *snapshot;
+
+        let res = self.install_snapshot_file(meta, &mut file, &path).await;
+        if res.is_err() {
+            remove_file(&path).await;
+        }
+        res
+    }
+
+    async fn get_current_snapshot(
+        &mut self,
+    ) -> Result<Option<Snapshot<MemNodeId, (), Self::SnapshotData>>, StorageError<MemNodeId>> {
+        self.dir.open_current(None).await
+    }
+
+    async fn get_snapshot_by_id(
+        &mut self,
+        snapshot_id: &str,
+    ) -> Result<Option<Snapshot<MemNodeId, (), Self::SnapshotData>>, StorageError<MemNodeId>> {
+        self.dir.open_current(Some(snapshot_id)).await
+    }
+}
+
+/// The snapshot builder of a [`FileSnapshotStore`], which encodes the state machine of the `MemStore` into a file.
+pub struct FileSnapshotBuilder<D = ClientRequest, R = ClientResponse, A = ClientState>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    dir: Arc<SnapshotDir>,
+    inner: Arc<MemStore<D, R, A>>,
+}
+
+#[async_trait]
+impl<D, R, A> RaftSnapshotBuilder<MemConfig<D, R>, SnapshotFile> for FileSnapshotBuilder<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    async fn build_snapshot(&mut self) -> Result<Snapshot<MemNodeId, (), SnapshotFile>, StorageError<MemNodeId>> {
+        let sm = self.inner.sm.read().await.clone();
+
+        let meta = SnapshotMeta {
+            last_log_id: sm.last_applied_log,
+            last_membership: sm.last_membership.clone(),
+            snapshot_id: self.inner.new_snapshot_id(sm.last_applied_log),
+        };
+
+        let io_err =
+            |e: io::Error| StorageError::from_io_error(ErrorSubject::Snapshot(meta.signature()), ErrorVerb::Write, e);
+
+        // Write to a temp file then rename it, so that a snapshot file is always complete.
+        let temp = self.dir.temp_path();
+
+        let res = async {
+            write_state_machine(sm, temp.clone()).await?;
+            self.dir.replace_current(&meta, &temp, async {}).await
+        }
+        .await;
+
+        if let Err(e) = res {
+            remove_file(&temp).await;
+            return Err(io_err(e));
+        }
+
+        let snapshot = self.dir.open_current(Some(&meta.snapshot_id)).await?;
+
+        // A snapshot built later may have replaced this one.
+        snapshot.ok_or_else(|| io_err(io::Error::new(io::ErrorKind::NotFound, "snapshot is superseded")))
+    }
+}
@@ -1,35 +1,67 @@
+mod cached_store;
+mod faulty_store;
+mod file_snapshot;
+mod snapshot_checksum;
+mod snapshot_codec;
+mod snapshot_cursor;
 #[cfg(test)] mod test;
 
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fmt::Debug;
-use std::io::Cursor;
+use std::fmt::Formatter;
+use std::marker::PhantomData;
 use std::ops::RangeBounds;
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use openraft::async_trait::async_trait;
 use openraft::storage::LogState;
+use openraft::storage::LogStats;
 use openraft::storage::RaftLogReader;
 use openraft::storage::RaftSnapshotBuilder;
 use openraft::storage::Snapshot;
 use openraft::AnyError;
+use openraft::AppData;
+use openraft::AppDataResponse;
+use openraft::DefensiveError;
 use openraft::EffectiveMembership;
 use openraft::Entry;
 use openraft::EntryPayload;
 use openraft::ErrorSubject;
 use openraft::ErrorVerb;
 use openraft::LogId;
+use openraft::LogIdOptionExt;
 use openraft::RaftStorage;
 use openraft::RaftStorageDebug;
+use openraft::RaftTypeConfig;
 use openraft::SnapshotMeta;
 use openraft::StorageError;
 use openraft::StorageIOError;
+use openraft::Violation;
 use openraft::Vote;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
 use tokio::sync::RwLock;
 
+pub use crate::cached_store::CachedStore;
+pub use crate::faulty_store::FaultPoint;
+pub use crate::faulty_store::FaultPolicy;
+pub use crate::faulty_store::FaultySnapshotBuilder;
+pub use crate::faulty_store::FaultyStore;
+pub use crate::faulty_store::PayloadPredicate;
+pub use crate::file_snapshot::FileSnapshotBuilder;
+pub use crate::file_snapshot::FileSnapshotStore;
+pub use crate::file_snapshot::SnapshotFile;
+use crate::snapshot_checksum::add_checksum;
+use crate::snapshot_checksum::verify_checksum;
+pub use crate::snapshot_codec::SnapshotCodec;
+pub use crate::snapshot_cursor::SnapshotCursor;
+
 /// The application data request type which the `MemStore` works with.
 ///
 /// Conceptually, for demo purposes, this represents an update to a client's status info,
@@ -69,42 +101,271 @@ pub struct ClientResponse(Option<String>);
 
 pub type MemNodeId = u64;
 
-openraft::declare_raft_types!(
-    /// Declare the type configuration for `MemStore`.
-    pub Config: D = ClientRequest, R = ClientResponse, NodeId = MemNodeId, Node = ()
-);
+/// The type configuration for a `MemStore` working with request type `D` and response type `R`.
+///
+/// The traits required by `RaftTypeConfig` are implemented by hand, because deriving them requires `D` and `R` to
+/// implement them too.
+pub struct MemConfig<D, R> {
+    _p: PhantomData<fn() -> (D, R)>,
+}
+
+impl<D, R> Debug for MemConfig<D, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MemConfig")
+    }
+}
+
+impl<D, R> Clone for MemConfig<D, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D, R> Copy for MemConfig<D, R> {}
+
+impl<D, R> Default for MemConfig<D, R> {
+    fn default() -> Self {
+        Self { _p: PhantomData }
+    }
+}
+
+impl<D, R> PartialEq for MemConfig<D, R> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<D, R> Eq for MemConfig<D, R> {}
+
+impl<D, R> PartialOrd for MemConfig<D, R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<D, R> Ord for MemConfig<D, R> {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl<D, R> RaftTypeConfig for MemConfig<D, R>
+where
+    D: AppData,
+    R: AppDataResponse,
+{
+    type D = D;
+    type R = R;
+    type NodeId = MemNodeId;
+    type Node = ();
+}
+
+/// The type configuration of the default `MemStore`.
+pub type Config = MemConfig<ClientRequest, ClientResponse>;
 
 /// The application snapshot type which the `MemStore` works with.
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MemStoreSnapshot {
     pub meta: SnapshotMeta<MemNodeId, ()>,
 
     /// The data of the state machine at the time of this snapshot.
-    pub data: Vec<u8>,
+    ///
+    /// It is shared with every reader returned by the store, instead of being copied.
+    pub data: Arc<Vec<u8>>,
+
+    /// The codec that encoded `data`.
+    #[serde(default)]
+    pub codec: SnapshotCodec,
 }
 
-/// The state machine of the `MemStore`.
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
-pub struct MemStoreStateMachine {
-    pub last_applied_log: Option<LogId<MemNodeId>>,
+/// The default number of most recent snapshots a `MemStore` retains, including the current one.
+///
+/// It can be changed with [`MemStore::with_retained_snapshots`].
+pub const RETAINED_SNAPSHOTS: usize = 1;
 
-    pub last_membership: EffectiveMembership<MemNodeId, ()>,
+/// Applies normal log entries of type `D` to the application data of a `MemStore` state machine.
+///
+/// Implement it to plug a custom command type into `MemStore`.
+pub trait StateMachineApply<D, R>:
+    Debug + Default + Clone + Serialize + DeserializeOwned + Send + Sync + 'static
+{
+    /// Apply the data of a normal log entry and return the response to the client.
+    fn apply(&mut self, data: &D) -> R;
+
+    /// The response for a blank or membership log entry.
+    fn empty_response(&self) -> R;
+}
 
+/// The max number of clients a [`ClientState`] keeps.
+///
+/// It is a constant rather than a per-node setting, so that every node prunes the same clients when applying the
+/// same logs, and the state machines of all nodes stay identical.
+pub const MAX_CLIENTS: usize = 10_000;
+
+/// The application data of the default `MemStore` state machine: the status of every client.
+///
+/// A request whose serial number is the same as the last applied one of the same client is not applied again,
+/// the last response is returned instead.
+///
+/// To bound the memory, at most [`MAX_CLIENTS`] clients are kept. When it is exceeded, the least recently applied
+/// client is forgotten: its status and last response are removed, and a retried request from it will be applied
+/// again.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ClientState {
     /// A mapping of client IDs to their state info.
     pub client_serial_responses: HashMap<String, (u64, Option<String>)>,
     /// The current status of a client by ID.
     pub client_status: HashMap<String, String>,
+
+    /// The number of requests applied so far, used to order clients by when they are applied.
+    #[serde(default)]
+    applied_seq: u64,
+
+    /// The `applied_seq` of the last request of every client in `client_status`.
+    #[serde(default)]
+    client_seq: HashMap<String, u64>,
+
+    /// The clients in `client_status` ordered by `applied_seq` of their last request.
+    #[serde(default)]
+    seq_client: BTreeMap<u64, String>,
+}
+
+impl ClientState {
+    /// Apply a request and forget the least recently applied clients that exceed `max_clients`.
+    pub(crate) fn apply_bounded(&mut self, data: &ClientRequest, max_clients: usize) -> ClientResponse {
+        if let Some((serial, r)) = self.client_serial_responses.get(&data.client) {
+            if serial == &data.serial {
+                return ClientResponse(r.clone());
+            }
+        }
+        let previous = self.client_status.insert(data.client.clone(), data.status.clone());
+        self.client_serial_responses.insert(data.client.clone(), (data.serial, previous.clone()));
+
+        self.touch(&data.client);
+        self.prune(max_clients);
+
+        ClientResponse(previous)
+    }
+
+    /// Mark `client` as the most recently applied one.
+    fn touch(&mut self, client: &str) {
+        self.applied_seq += 1;
+
+        if let Some(prev) = self.client_seq.insert(client.to_string(), self.applied_seq) {
+            self.seq_client.remove(&prev);
+        }
+        self.seq_client.insert(self.applied_seq, client.to_string());
+    }
+
+    /// Forget the least recently applied clients until there are at most `max_clients`.
+    fn prune(&mut self, max_clients: usize) {
+        while self.client_seq.len() > max_clients {
+            let (_seq, client) = match self.seq_client.pop_first() {
+                None => break,
+                Some(x) => x,
+            };
+
+            tracing::debug!(%client, "forget client");
+
+            self.client_seq.remove(&client);
+            self.client_serial_responses.remove(&client);
+            self.client_status.remove(&client);
+        }
+    }
+}
+
+impl StateMachineApply<ClientRequest, ClientResponse> for ClientState {
+    fn apply(&mut self, data: &ClientRequest) -> ClientResponse {
+        self.apply_bounded(data, MAX_CLIENTS)
+    }
+
+    fn empty_response(&self) -> ClientResponse {
+        ClientResponse(None)
+    }
+}
+
+/// The state machine of the `MemStore`.
+///
+/// A snapshot built before the application data is moved into `data`, which stores the fields of [`ClientState`]
+/// at the top level, is still able to be decoded.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(bound = "", from = "StateMachineRepr<A>")]
+pub struct MemStoreStateMachine<A = ClientState>
+where A: Debug + Default + Clone + Serialize + DeserializeOwned
+{
+    pub last_applied_log: Option<LogId<MemNodeId>>,
+
+    pub last_membership: EffectiveMembership<MemNodeId, ()>,
+
+    /// The application data.
+    pub data: A,
+}
+
+/// The serialized forms of [`MemStoreStateMachine`] that can be decoded.
+#[derive(Deserialize)]
+#[serde(bound = "", untagged)]
+enum StateMachineRepr<A>
+where A: DeserializeOwned
+{
+    Current {
+        last_applied_log: Option<LogId<MemNodeId>>,
+        last_membership: EffectiveMembership<MemNodeId, ()>,
+        data: A,
+    },
+
+    /// The application data is stored at the top level.
+    Legacy {
+        last_applied_log: Option<LogId<MemNodeId>>,
+        last_membership: EffectiveMembership<MemNodeId, ()>,
+        #[serde(flatten)]
+        data: A,
+    },
+}
+
+impl<A> From<StateMachineRepr<A>> for MemStoreStateMachine<A>
+where A: Debug + Default + Clone + Serialize + DeserializeOwned
+{
+    fn from(repr: StateMachineRepr<A>) -> Self {
+        match repr {
+            StateMachineRepr::Current {
+                last_applied_log,
+                last_membership,
+                data,
+            }
+            | StateMachineRepr::Legacy {
+                last_applied_log,
+                last_membership,
+                data,
+            } => Self {
+                last_applied_log,
+                last_membership,
+                data,
+            },
+        }
+    }
 }
 
 /// An in-memory storage system implementing the `RaftStorage` trait.
-pub struct MemStore {
+///
+/// It is generic over the request type `D`, the response type `R` and the application data `A` of the state
+/// machine, which applies `D` and returns `R`.
+/// By default it works with [`ClientRequest`], [`ClientResponse`] and [`ClientState`].
+pub struct MemStore<D = ClientRequest, R = ClientResponse, A = ClientState>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
     last_purged_log_id: RwLock<Option<LogId<MemNodeId>>>,
 
     /// The Raft log.
-    log: RwLock<BTreeMap<u64, Entry<Config>>>,
+    log: RwLock<BTreeMap<u64, Entry<MemConfig<D, R>>>>,
+
+    /// The number of entries in `log` and their size. It is updated while holding the write lock of `log`.
+    log_counter: RwLock<LogCounter>,
 
     /// The Raft state machine.
-    sm: RwLock<MemStoreStateMachine>,
+    sm: RwLock<MemStoreStateMachine<A>>,
 
     /// The current hard state.
     vote: RwLock<Option<Vote<MemNodeId>>>,
@@ -113,11 +374,103 @@ pub struct MemStore {
 
     /// The current snapshot.
     current_snapshot: RwLock<Option<MemStoreSnapshot>>,
+
+    /// The most recent snapshots built or installed, the last one is the current snapshot.
+    retained_snapshots: RwLock<VecDeque<MemStoreSnapshot>>,
+
+    /// The max number of snapshots in `retained_snapshots`.
+    max_retained_snapshots: usize,
+
+    /// The codec to encode snapshots built by this store.
+    snapshot_codec: SnapshotCodec,
+
+    /// Whether to add a checksum to snapshots built by this store.
+    snapshot_checksum: bool,
+}
+
+/// The persisted form of a `MemStore`, see [`MemStore::save_to_path`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct MemStoreData<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    last_purged_log_id: Option<LogId<MemNodeId>>,
+    log: BTreeMap<u64, Entry<MemConfig<D, R>>>,
+    sm: MemStoreStateMachine<A>,
+    vote: Option<Vote<MemNodeId>>,
+    snapshot_idx: u64,
+    current_snapshot: Option<MemStoreSnapshot>,
+
+    /// The retained snapshots, the last one is the current snapshot.
+    #[serde(default)]
+    retained_snapshots: VecDeque<MemStoreSnapshot>,
+
+    /// The max number of retained snapshots, see [`MemStore::with_retained_snapshots`].
+    #[serde(default = "default_retained_snapshots")]
+    max_retained_snapshots: usize,
+}
+
+fn default_retained_snapshots() -> usize {
+    RETAINED_SNAPSHOTS
+}
+
+/// Running counters of the log, so that `log_stats()` does not have to scan the entire log.
+#[derive(Debug, Default)]
+struct LogCounter {
+    entries: u64,
+    bytes: u64,
+}
+
+impl LogCounter {
+    fn count<D: AppData, R: AppDataResponse>(log: &BTreeMap<u64, Entry<MemConfig<D, R>>>) -> Self {
+        let mut c = Self::default();
+        for ent in log.values() {
+            c.add(ent);
+        }
+        c
+    }
+
+    fn add<D: AppData, R: AppDataResponse>(&mut self, ent: &Entry<MemConfig<D, R>>) {
+        self.entries += 1;
+        self.bytes += payload_size(&ent.payload);
+    }
+
+    fn sub<D: AppData, R: AppDataResponse>(&mut self, ent: &Entry<MemConfig<D, R>>) {
+        self.entries -= 1;
+        self.bytes -= payload_size(&ent.payload);
+    }
+}
+
+/// The serialized size of a log payload, as returned by `RaftStorage::serialized_size()`.
+fn payload_size<D: AppData, R: AppDataResponse>(payload: &EntryPayload<MemConfig<D, R>>) -> u64 {
+    serde_json::to_vec(payload).map(|x| x.len() as u64).unwrap_or_default()
 }
 
 impl MemStore {
     /// Create a new `MemStore` instance.
     pub fn new() -> Self {
+        Self::with_snapshot_idx(0)
+    }
+
+    pub async fn new_async() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+}
+
+impl<D, R, A> MemStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    /// Create a new `MemStore` instance whose snapshot index starts from `snapshot_idx`.
+    ///
+    /// The next snapshot built by this store will have an index of `snapshot_idx + 1`.
+    /// Seeding stores with distinct bases makes the `snapshot_id` of every node predictable in tests.
+    pub fn with_snapshot_idx(snapshot_idx: u64) -> Self {
         let log = RwLock::new(BTreeMap::new());
         let sm = RwLock::new(MemStoreStateMachine::default());
         let current_snapshot = RwLock::new(None);
@@ -125,38 +478,274 @@ impl MemStore {
         Self {
             last_purged_log_id: RwLock::new(None),
             log,
+            log_counter: RwLock::new(LogCounter::default()),
             sm,
             vote: RwLock::new(None),
-            snapshot_idx: Arc::new(Mutex::new(0)),
+            snapshot_idx: Arc::new(Mutex::new(snapshot_idx)),
             current_snapshot,
+            retained_snapshots: RwLock::new(VecDeque::new()),
+            max_retained_snapshots: RETAINED_SNAPSHOTS,
+            snapshot_codec: SnapshotCodec::default(),
+            snapshot_checksum: false,
         }
     }
 
-    pub async fn new_async() -> Arc<Self> {
-        Arc::new(Self::new())
+    /// Encode snapshots built by this store with `codec`. By default it is [`SnapshotCodec::Json`].
+    ///
+    /// Snapshots built or installed before are still readable: the codec of an installed snapshot is detected
+    /// from its bytes.
+    pub fn with_snapshot_codec(mut self, codec: SnapshotCodec) -> Self {
+        self.snapshot_codec = codec;
+        self
+    }
+
+    /// Add a checksum to snapshots built by this store. By default there is no checksum.
+    ///
+    /// A snapshot with a checksum is verified before it is decoded when it is installed, no matter whether the
+    /// installing store adds checksums itself. A corrupted snapshot fails the installation with a retryable error.
+    pub fn with_snapshot_checksum(mut self, enabled: bool) -> Self {
+        self.snapshot_checksum = enabled;
+        self
+    }
+
+    /// Retain the most recent `n` snapshots, including the current one, for
+    /// [`RaftStorage::get_snapshot_by_id`]. By default it is [`RETAINED_SNAPSHOTS`].
+    ///
+    /// At least the current snapshot is retained, even if `n` is 0.
+    pub fn with_retained_snapshots(mut self, n: usize) -> Self {
+        self.max_retained_snapshots = std::cmp::max(n, 1);
+        self
+    }
+
+    /// Find a retained snapshot by its `snapshot_id`.
+    async fn find_retained_snapshot(&self, snapshot_id: &str) -> Option<Snapshot<MemNodeId, (), SnapshotCursor>> {
+        let retained = self.retained_snapshots.read().await;
+        let snapshot = retained.iter().find(|x| x.meta.snapshot_id == snapshot_id)?;
+
+        Some(Snapshot {
+            meta: snapshot.meta.clone(),
+            snapshot: Box::new(SnapshotCursor::new(snapshot.data.clone())),
+        })
+    }
+
+    /// Save the log, the state machine, the vote and the retained snapshots to a JSON file at `path`.
+    ///
+    /// A store reloaded with [`MemStore::load_from_path`] returns the same initial state as this one, thus a test
+    /// can stop a node, save its store and restart the node with the reloaded store.
+    /// It is meant to be called while the node is stopped: it does not take a consistent view if the store is
+    /// being written concurrently.
+    pub async fn save_to_path(&self, path: impl AsRef<Path>) -> Result<(), StorageError<MemNodeId>> {
+        let data = MemStoreData::<D, R, A> {
+            last_purged_log_id: *self.last_purged_log_id.read().await,
+            log: self.log.read().await.clone(),
+            sm: self.sm.read().await.clone(),
+            vote: *self.vote.read().await,
+            snapshot_idx: *self.snapshot_idx.lock().unwrap(),
+            current_snapshot: self.current_snapshot.read().await.clone(),
+            retained_snapshots: self.retained_snapshots.read().await.clone(),
+            max_retained_snapshots: self.max_retained_snapshots,
+        };
+
+        let buf = serde_json::to_vec(&data)
+            .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Write, AnyError::new(&e)))?;
+
+        tokio::fs::write(path, buf)
+            .await
+            .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Write, AnyError::new(&e)))?;
+
+        Ok(())
+    }
+
+    /// Load a store saved by [`MemStore::save_to_path`], for restarting the node `id` with it.
+    ///
+    /// The number of retained snapshots set with [`MemStore::with_retained_snapshots`] is restored.
+    pub async fn load_from_path(id: MemNodeId, path: impl AsRef<Path>) -> Result<Self, StorageError<MemNodeId>> {
+        tracing::info!(id, path = display(path.as_ref().display()), "load MemStore");
+
+        let buf = tokio::fs::read(path)
+            .await
+            .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Read, AnyError::new(&e)))?;
+
+        let data: MemStoreData<D, R, A> = serde_json::from_slice(&buf)
+            .map_err(|e| StorageIOError::new(ErrorSubject::Store, ErrorVerb::Read, AnyError::new(&e)))?;
+
+        // A store saved without retained snapshots retains only the current one.
+        let retained = if data.retained_snapshots.is_empty() {
+            data.current_snapshot.iter().cloned().collect::<VecDeque<_>>()
+        } else {
+            data.retained_snapshots
+        };
+
+        Ok(Self {
+            last_purged_log_id: RwLock::new(data.last_purged_log_id),
+            log_counter: RwLock::new(LogCounter::count(&data.log)),
+            log: RwLock::new(data.log),
+            sm: RwLock::new(data.sm),
+            vote: RwLock::new(data.vote),
+            snapshot_idx: Arc::new(Mutex::new(data.snapshot_idx)),
+            current_snapshot: RwLock::new(data.current_snapshot),
+            retained_snapshots: RwLock::new(retained),
+            max_retained_snapshots: std::cmp::max(data.max_retained_snapshots, 1),
+            snapshot_codec: SnapshotCodec::default(),
+            snapshot_checksum: false,
+        })
+    }
+
+    /// Install a snapshot, e.g., one restored from a backup, into a store before a raft node starts with it.
+    ///
+    /// The state machine and the current snapshot are replaced with the snapshot, and the logs included in the
+    /// snapshot are purged. The node then starts at the last log id of the snapshot, as returned by
+    /// [`StorageHelper::get_initial_state`](`openraft::StorageHelper::get_initial_state`), thus a leader only
+    /// replicates the logs after it.
+    ///
+    /// It is meant to be called on a new store. It must not be called while the store is used by a raft node.
+    pub async fn install_initial_snapshot(
+        self: &Arc<Self>,
+        meta: &SnapshotMeta<MemNodeId, ()>,
+        data: Vec<u8>,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        let mut sto = self.clone();
+
+        sto.install_snapshot(meta, Box::new(SnapshotCursor::from(data))).await?;
+
+        if let Some(last) = meta.last_log_id {
+            if *self.last_purged_log_id.read().await < Some(last) {
+                sto.purge_logs_upto(last).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generate the id of a new snapshot of the state machine that has applied up to `last_applied`.
+    fn new_snapshot_id(&self, last_applied: Option<LogId<MemNodeId>>) -> String {
+        let snapshot_idx = {
+            let mut l = self.snapshot_idx.lock().unwrap();
+            *l += 1;
+            *l
+        };
+
+        if let Some(last) = last_applied {
+            format!("{}-{}-{}", last.leader_id, last.index, snapshot_idx)
+        } else {
+            format!("--{}", snapshot_idx)
+        }
+    }
+
+    /// Replace the current snapshot and retain it.
+    async fn set_current_snapshot(&self, snapshot: MemStoreSnapshot) {
+        {
+            let mut retained = self.retained_snapshots.write().await;
+            retained.push_back(snapshot.clone());
+            while retained.len() > self.max_retained_snapshots {
+                retained.pop_front();
+            }
+        }
+
+        let mut current_snapshot = self.current_snapshot.write().await;
+        *current_snapshot = Some(snapshot);
+    }
+
+    /// Export the last membership applied to the state machine.
+    ///
+    /// It is a lightweight checkpoint of the cluster config, without any client data.
+    pub async fn export_membership(&self) -> EffectiveMembership<MemNodeId, ()> {
+        self.sm.read().await.last_membership.clone()
+    }
+
+    /// Import a membership exported by [`MemStore::export_membership`].
+    ///
+    /// It appends the membership entry to the log and updates `last_membership` of the state machine.
+    /// Client data and `last_applied_log` are left untouched.
+    ///
+    /// It may only be called on a fresh store or a store that is not running in a raft node: raft does not know
+    /// about the changes made to its log and state machine.
+    ///
+    /// The membership entry must be the next one of the last log, otherwise a defensive error is returned, because
+    /// a hole in the log can not be read by raft.
+    pub async fn import_membership(
+        &self,
+        em: EffectiveMembership<MemNodeId, ()>,
+    ) -> Result<(), StorageError<MemNodeId>> {
+        if let Some(log_id) = em.log_id {
+            let mut log = self.log.write().await;
+
+            let last = log.iter().next_back().map(|(_, ent)| ent.log_id);
+            let last = last.or(*self.last_purged_log_id.read().await);
+
+            if log_id.index != last.next_index() {
+                let violation = Violation::LogsNonConsecutive {
+                    prev: last,
+                    next: log_id,
+                };
+                return Err(DefensiveError::new(ErrorSubject::Log(log_id), violation).into());
+            }
+
+            let entry = Entry {
+                log_id,
+                payload: EntryPayload::Membership(em.membership.clone()),
+            };
+            self.log_counter.write().await.add(&entry);
+            log.insert(log_id.index, entry);
+        }
+
+        self.sm.write().await.last_membership = em;
+        Ok(())
+    }
+
+    /// Atomically replace the entire state machine, without serializing it.
+    ///
+    /// The current snapshot no longer matches the state machine and is dropped. A new one will be built
+    /// when raft requests it. Retained snapshots are kept.
+    ///
+    /// **This bypasses all raft safety checks**: the log is not touched, and nothing ensures that
+    /// `sm.last_applied_log` or `sm.last_membership` is consistent with the log or with the other nodes.
+    /// It is meant for setting up a store in tests or for restoring a store that is not running in a
+    /// raft node.
+    pub async fn replace_state_machine(&self, sm: MemStoreStateMachine<A>) {
+        let mut current_sm = self.sm.write().await;
+        let mut current_snapshot = self.current_snapshot.write().await;
+
+        *current_sm = sm;
+        *current_snapshot = None;
     }
 }
 
-impl Default for MemStore {
+impl<D, R, A> Default for MemStore<D, R, A>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
     fn default() -> Self {
-        Self::new()
+        Self::with_snapshot_idx(0)
     }
 }
 
 #[async_trait]
-impl RaftStorageDebug<MemStoreStateMachine> for Arc<MemStore> {
+impl<D, R, A> RaftStorageDebug<MemStoreStateMachine<A>> for Arc<MemStore<D, R, A>>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
     /// Get a handle to the state machine for testing purposes.
-    async fn get_state_machine(&mut self) -> MemStoreStateMachine {
+    async fn get_state_machine(&mut self) -> MemStoreStateMachine<A> {
         self.sm.write().await.clone()
     }
 }
 
 #[async_trait]
-impl RaftLogReader<Config> for Arc<MemStore> {
+impl<D, R, A> RaftLogReader<MemConfig<D, R>> for Arc<MemStore<D, R, A>>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
     async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
         &mut self,
         range: RB,
-    ) -> Result<Vec<Entry<Config>>, StorageError<MemNodeId>> {
+    ) -> Result<Vec<Entry<MemConfig<D, R>>>, StorageError<MemNodeId>> {
         let res = {
             let log = self.log.read().await;
             log.range(range.clone()).map(|(_, val)| val.clone()).collect::<Vec<_>>()
@@ -165,7 +754,42 @@ impl RaftLogReader<Config> for Arc<MemStore> {
         Ok(res)
     }
 
-    async fn get_log_state(&mut self) -> Result<LogState<Config>, StorageError<MemNodeId>> {
+    /// Stop collecting entries once the sum of their serialized size reaches `max_bytes`.
+    async fn get_log_entries_limited<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+        max_entries: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<Entry<MemConfig<D, R>>>, StorageError<MemNodeId>> {
+        let log = self.log.read().await;
+
+        let mut res = vec![];
+        let mut bytes = 0;
+
+        for (_, ent) in log.range(range) {
+            if res.len() as u64 >= max_entries {
+                break;
+            }
+
+            let size = self.serialized_size(&ent.payload).unwrap_or_default();
+            if !res.is_empty() && bytes + size > max_bytes {
+                break;
+            }
+
+            bytes += size;
+            res.push(ent.clone());
+        }
+
+        Ok(res)
+    }
+
+    /// Read only the log id of the entry, without cloning the payload.
+    async fn get_log_term(&mut self, log_index: u64) -> Result<Option<u64>, StorageError<MemNodeId>> {
+        let log = self.log.read().await;
+        Ok(log.get(&log_index).map(|ent| ent.log_id.leader_id.term))
+    }
+
+    async fn get_log_state(&mut self) -> Result<LogState<MemConfig<D, R>>, StorageError<MemNodeId>> {
         let log = self.log.read().await;
         let last = log.iter().rev().next().map(|(_, ent)| ent.log_id);
 
@@ -184,9 +808,14 @@ impl RaftLogReader<Config> for Arc<MemStore> {
 }
 
 #[async_trait]
-impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<MemStore> {
+impl<D, R, A> RaftSnapshotBuilder<MemConfig<D, R>, SnapshotCursor> for Arc<MemStore<D, R, A>>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
     #[tracing::instrument(level = "trace", skip(self))]
-    async fn build_snapshot(&mut self) -> Result<Snapshot<MemNodeId, (), Cursor<Vec<u8>>>, StorageError<MemNodeId>> {
+    async fn build_snapshot(&mut self) -> Result<Snapshot<MemNodeId, (), SnapshotCursor>, StorageError<MemNodeId>> {
         let data;
         let last_applied_log;
         let last_membership;
@@ -194,55 +823,57 @@ impl RaftSnapshotBuilder<Config, Cursor<Vec<u8>>> for Arc<MemStore> {
         {
             // Serialize the data of the state machine.
             let sm = self.sm.read().await;
-            data = serde_json::to_vec(&*sm)
+            let json = serde_json::to_vec(&*sm)
+                .map_err(|e| StorageIOError::new(ErrorSubject::StateMachine, ErrorVerb::Read, AnyError::new(&e)))?;
+            let encoded = self
+                .snapshot_codec
+                .encode(json)
                 .map_err(|e| StorageIOError::new(ErrorSubject::StateMachine, ErrorVerb::Read, AnyError::new(&e)))?;
 
+            data = if self.snapshot_checksum {
+                add_checksum(encoded)
+            } else {
+                encoded
+            };
+
             last_applied_log = sm.last_applied_log;
             last_membership = sm.last_membership.clone();
         }
 
         let snapshot_size = data.len();
-
-        let snapshot_idx = {
-            let mut l = self.snapshot_idx.lock().unwrap();
-            *l += 1;
-            *l
-        };
-
-        let snapshot_id = if let Some(last) = last_applied_log {
-            format!("{}-{}-{}", last.leader_id, last.index, snapshot_idx)
-        } else {
-            format!("--{}", snapshot_idx)
-        };
+        let data = Arc::new(data);
 
         let meta = SnapshotMeta {
             last_log_id: last_applied_log,
             last_membership,
-            snapshot_id,
+            snapshot_id: self.new_snapshot_id(last_applied_log),
         };
 
         let snapshot = MemStoreSnapshot {
             meta: meta.clone(),
             data: data.clone(),
+            codec: self.snapshot_codec,
         };
 
-        {
-            let mut current_snapshot = self.current_snapshot.write().await;
-            *current_snapshot = Some(snapshot);
-        }
+        self.set_current_snapshot(snapshot).await;
 
-        tracing::info!(snapshot_size, "log compaction complete");
+        tracing::info!(snapshot_size, codec = ?self.snapshot_codec, "log compaction complete");
 
         Ok(Snapshot {
             meta,
-            snapshot: Box::new(Cursor::new(data)),
+            snapshot: Box::new(SnapshotCursor::new(data)),
         })
     }
 }
 
 #[async_trait]
-impl RaftStorage<Config> for Arc<MemStore> {
-    type SnapshotData = Cursor<Vec<u8>>;
+impl<D, R, A> RaftStorage<MemConfig<D, R>> for Arc<MemStore<D, R, A>>
+where
+    D: AppData,
+    R: AppDataResponse,
+    A: StateMachineApply<D, R>,
+{
+    type SnapshotData = SnapshotCursor;
 
     #[tracing::instrument(level = "trace", skip(self))]
     async fn save_vote(&mut self, vote: &Vote<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
@@ -270,10 +901,13 @@ impl RaftStorage<Config> for Arc<MemStore> {
 
         {
             let mut log = self.log.write().await;
+            let mut counter = self.log_counter.write().await;
 
             let keys = log.range(log_id.index..).map(|(k, _v)| *k).collect::<Vec<_>>();
             for key in keys {
-                log.remove(&key);
+                if let Some(ent) = log.remove(&key) {
+                    counter.sub(&ent);
+                }
             }
         }
 
@@ -282,7 +916,11 @@ impl RaftStorage<Config> for Arc<MemStore> {
 
     #[tracing::instrument(level = "debug", skip(self))]
     async fn purge_logs_upto(&mut self, log_id: LogId<MemNodeId>) -> Result<(), StorageError<MemNodeId>> {
-        tracing::debug!("delete_log: [{:?}, +oo)", log_id);
+        tracing::debug!("purge_log: (-oo, {:?}]", log_id);
+
+        // Hold the log lock while updating `last_purged_log_id`, so that `get_log_state()` and
+        // `append_to_log()` never see one updated without the other.
+        let mut log = self.log.write().await;
 
         {
             let mut ld = self.last_purged_log_id.write().await;
@@ -290,23 +928,41 @@ impl RaftStorage<Config> for Arc<MemStore> {
             *ld = Some(log_id);
         }
 
-        {
-            let mut log = self.log.write().await;
+        let kept = log.split_off(&(log_id.index + 1));
 
-            let keys = log.range(..=log_id.index).map(|(k, _v)| *k).collect::<Vec<_>>();
-            for key in keys {
-                log.remove(&key);
-            }
+        let mut counter = self.log_counter.write().await;
+        for ent in log.values() {
+            counter.sub(ent);
         }
 
+        *log = kept;
+
         Ok(())
     }
 
+    fn serialized_size(&self, payload: &EntryPayload<MemConfig<D, R>>) -> Option<u64> {
+        Some(payload_size(payload))
+    }
+
+    async fn log_stats(&mut self) -> Result<LogStats, StorageError<MemNodeId>> {
+        let c = self.log_counter.read().await;
+
+        Ok(LogStats {
+            entries: c.entries,
+            bytes: Some(c.bytes),
+        })
+    }
+
     #[tracing::instrument(level = "trace", skip(self, entries))]
-    async fn append_to_log(&mut self, entries: &[&Entry<Config>]) -> Result<(), StorageError<MemNodeId>> {
+    async fn append_to_log(&mut self, entries: &[&Entry<MemConfig<D, R>>]) -> Result<(), StorageError<MemNodeId>> {
         let mut log = self.log.write().await;
+        let mut counter = self.log_counter.write().await;
+
         for entry in entries {
-            log.insert(entry.log_id.index, (*entry).clone());
+            if let Some(prev) = log.insert(entry.log_id.index, (*entry).clone()) {
+                counter.sub(&prev);
+            }
+            counter.add(entry);
         }
         Ok(())
     }
@@ -314,8 +970,8 @@ impl RaftStorage<Config> for Arc<MemStore> {
     #[tracing::instrument(level = "trace", skip(self, entries))]
     async fn apply_to_state_machine(
         &mut self,
-        entries: &[&Entry<Config>],
-    ) -> Result<Vec<ClientResponse>, StorageError<MemNodeId>> {
+        entries: &[&Entry<MemConfig<D, R>>],
+    ) -> Result<Vec<R>, StorageError<MemNodeId>> {
         let mut res = Vec::with_capacity(entries.len());
 
         let mut sm = self.sm.write().await;
@@ -326,21 +982,11 @@ impl RaftStorage<Config> for Arc<MemStore> {
             sm.last_applied_log = Some(entry.log_id);
 
             match entry.payload {
-                EntryPayload::Blank => res.push(ClientResponse(None)),
-                EntryPayload::Normal(ref data) => {
-                    if let Some((serial, r)) = sm.client_serial_responses.get(&data.client) {
-                        if serial == &data.serial {
-                            res.push(ClientResponse(r.clone()));
-                            continue;
-                        }
-                    }
-                    let previous = sm.client_status.insert(data.client.clone(), data.status.clone());
-                    sm.client_serial_responses.insert(data.client.clone(), (data.serial, previous.clone()));
-                    res.push(ClientResponse(previous));
-                }
+                EntryPayload::Blank => res.push(sm.data.empty_response()),
+                EntryPayload::Normal(ref data) => res.push(sm.data.apply(data)),
                 EntryPayload::Membership(ref mem) => {
                     sm.last_membership = EffectiveMembership::new(Some(entry.log_id), mem.clone());
-                    res.push(ClientResponse(None))
+                    res.push(sm.data.empty_response())
                 }
             };
         }
@@ -349,7 +995,7 @@ impl RaftStorage<Config> for Arc<MemStore> {
 
     #[tracing::instrument(level = "trace", skip(self))]
     async fn begin_receiving_snapshot(&mut self) -> Result<Box<Self::SnapshotData>, StorageError<MemNodeId>> {
-        Ok(Box::new(Cursor::new(Vec::new())))
+        Ok(Box::default())
     }
 
     #[tracing::instrument(level = "trace", skip(self, snapshot))]
@@ -363,34 +1009,46 @@ impl RaftStorage<Config> for Arc<MemStore> {
             "decoding snapshot for installation"
         );
 
-        let new_snapshot = MemStoreSnapshot {
-            meta: meta.clone(),
-            data: snapshot.into_inner(),
+        let data = snapshot.into_shared();
+
+        // A snapshot that can not be decoded is likely corrupted during transfer, and re-sending it may fix it.
+        let snapshot_read_err = |e: std::io::Error| {
+            StorageIOError::new(
+                ErrorSubject::Snapshot(meta.signature()),
+                ErrorVerb::Read,
+                AnyError::new(&e),
+            )
+            .transient()
         };
 
-        {
-            let t = &new_snapshot.data;
-            let y = std::str::from_utf8(t).unwrap();
-            tracing::debug!("SNAP META:{:?}", meta);
-            tracing::debug!("JSON SNAP DATA:{}", y);
-        }
+        // A corrupted snapshot with a checksum is detected before decoding it.
+        let encoded = verify_checksum(&data).map_err(snapshot_read_err)?;
+        let codec = SnapshotCodec::detect(encoded);
 
         // Update the state machine.
         {
-            let new_sm: MemStoreStateMachine = serde_json::from_slice(&new_snapshot.data).map_err(|e| {
-                StorageIOError::new(
-                    ErrorSubject::Snapshot(new_snapshot.meta.signature()),
-                    ErrorVerb::Read,
-                    AnyError::new(&e),
-                )
-            })?;
+            let json = codec.decode(encoded).map_err(snapshot_read_err)?;
+
+            {
+                let y = String::from_utf8_lossy(&json);
+                tracing::debug!("SNAP META:{:?}, codec: {:?}", meta, codec);
+                tracing::debug!("JSON SNAP DATA:{}", y);
+            }
+
+            let new_sm: MemStoreStateMachine<A> =
+                serde_json::from_slice(&json).map_err(|e| snapshot_read_err(e.into()))?;
             let mut sm = self.sm.write().await;
             *sm = new_sm;
         }
 
+        let new_snapshot = MemStoreSnapshot {
+            meta: meta.clone(),
+            codec,
+            data,
+        };
+
         // Update current snapshot.
-        let mut current_snapshot = self.current_snapshot.write().await;
-        *current_snapshot = Some(new_snapshot);
+        self.set_current_snapshot(new_snapshot).await;
         Ok(())
     }
 
@@ -403,13 +1061,21 @@ impl RaftStorage<Config> for Arc<MemStore> {
                 let data = snapshot.data.clone();
                 Ok(Some(Snapshot {
                     meta: snapshot.meta.clone(),
-                    snapshot: Box::new(Cursor::new(data)),
+                    snapshot: Box::new(SnapshotCursor::new(data)),
                 }))
             }
             None => Ok(None),
         }
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn get_snapshot_by_id(
+        &mut self,
+        snapshot_id: &str,
+    ) -> Result<Option<Snapshot<MemNodeId, (), Self::SnapshotData>>, StorageError<MemNodeId>> {
+        Ok(self.find_retained_snapshot(snapshot_id).await)
+    }
+
     type LogReader = Self;
     type SnapshotBuilder = Self;
 
@@ -0,0 +1,60 @@
+use std::io;
+
+/// The magic number of a snapshot with a checksum.
+///
+/// A snapshot with a checksum is framed as `MAGIC + checksum(8 bytes, big-endian) + data`, where data is the bytes
+/// encoded by a [`SnapshotCodec`](crate::SnapshotCodec).
+const CHECKSUM_MAGIC: &[u8] = b"MSCK";
+
+const HEADER_SIZE: usize = CHECKSUM_MAGIC.len() + 8;
+
+/// Prepend the magic number and the checksum of `data` to it.
+pub(crate) fn add_checksum(data: Vec<u8>) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(HEADER_SIZE + data.len());
+    framed.extend_from_slice(CHECKSUM_MAGIC);
+    framed.extend_from_slice(&checksum(&data).to_be_bytes());
+    framed.extend_from_slice(&data);
+    framed
+}
+
+/// Verify the checksum of `framed` and return the data in it.
+///
+/// Data without the magic number has no checksum, and is returned as is.
+pub(crate) fn verify_checksum(framed: &[u8]) -> Result<&[u8], io::Error> {
+    if !framed.starts_with(CHECKSUM_MAGIC) {
+        return Ok(framed);
+    }
+
+    if framed.len() < HEADER_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "snapshot is shorter than the checksum header",
+        ));
+    }
+
+    let mut want = [0u8; 8];
+    want.copy_from_slice(&framed[CHECKSUM_MAGIC.len()..HEADER_SIZE]);
+    let want = u64::from_be_bytes(want);
+
+    let data = &framed[HEADER_SIZE..];
+    let got = checksum(data);
+
+    if got != want {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("snapshot checksum mismatch: expect: {:016x}, got: {:016x}", want, got),
+        ));
+    }
+
+    Ok(data)
+}
+
+/// 64-bit FNV-1a hash, which is stable across platforms and releases.
+fn checksum(data: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for b in data {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
@@ -0,0 +1,93 @@
+use std::borrow::Cow;
+use std::io;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How the serialized state machine is encoded into the bytes of a `MemStore` snapshot.
+///
+/// The state machine is always serialized to JSON first. A compressing codec then compresses the JSON bytes.
+/// The compressed formats start with a magic number, thus the codec of a snapshot is detected from its bytes, see
+/// [`SnapshotCodec::detect`]. A node is able to install a snapshot encoded with any enabled codec, no matter what
+/// codec it uses to build snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnapshotCodec {
+    /// Uncompressed JSON.
+    #[default]
+    Json,
+
+    /// Gzip compressed JSON.
+    #[cfg(feature = "gzip")]
+    Gzip,
+
+    /// Zstd compressed JSON.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+#[cfg(feature = "zstd")]
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+impl SnapshotCodec {
+    /// Detect the codec that produced `data` by its magic number.
+    ///
+    /// Data without a known magic number is treated as uncompressed JSON, which is what snapshots built before
+    /// codecs were introduced contain.
+    pub fn detect(data: &[u8]) -> Self {
+        #[cfg(feature = "gzip")]
+        if data.starts_with(GZIP_MAGIC) {
+            return SnapshotCodec::Gzip;
+        }
+
+        #[cfg(feature = "zstd")]
+        if data.starts_with(ZSTD_MAGIC) {
+            return SnapshotCodec::Zstd;
+        }
+
+        let _ = data;
+        SnapshotCodec::Json
+    }
+
+    /// Encode the JSON bytes of a state machine.
+    pub fn encode(&self, json: Vec<u8>) -> Result<Vec<u8>, io::Error> {
+        match self {
+            SnapshotCodec::Json => Ok(json),
+
+            #[cfg(feature = "gzip")]
+            SnapshotCodec::Gzip => {
+                use std::io::Write;
+
+                let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                enc.write_all(&json)?;
+                enc.finish()
+            }
+
+            #[cfg(feature = "zstd")]
+            SnapshotCodec::Zstd => zstd::encode_all(json.as_slice(), 0),
+        }
+    }
+
+    /// Decode snapshot bytes encoded by this codec back into the JSON bytes of a state machine.
+    ///
+    /// Uncompressed JSON is returned as is, without copying.
+    pub fn decode<'a>(&self, data: &'a [u8]) -> Result<Cow<'a, [u8]>, io::Error> {
+        match self {
+            SnapshotCodec::Json => Ok(Cow::Borrowed(data)),
+
+            #[cfg(feature = "gzip")]
+            SnapshotCodec::Gzip => {
+                use std::io::Read;
+
+                let mut json = Vec::new();
+                flate2::read::GzDecoder::new(data).read_to_end(&mut json)?;
+                Ok(Cow::Owned(json))
+            }
+
+            #[cfg(feature = "zstd")]
+            SnapshotCodec::Zstd => Ok(Cow::Owned(zstd::decode_all(data)?)),
+        }
+    }
+}
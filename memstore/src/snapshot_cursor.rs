@@ -0,0 +1,126 @@
+use std::io;
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use tokio::io::AsyncRead;
+use tokio::io::AsyncSeek;
+use tokio::io::AsyncWrite;
+use tokio::io::ReadBuf;
+
+/// A cursor over snapshot bytes that are shared with the `MemStore`, instead of copied.
+///
+/// Reading and seeking never copy the bytes.
+/// Writing copies them first if they are still shared, like `Arc::make_mut()`.
+/// Otherwise it behaves the same as `std::io::Cursor<Vec<u8>>`.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotCursor {
+    data: Arc<Vec<u8>>,
+    pos: u64,
+}
+
+impl SnapshotCursor {
+    pub fn new(data: Arc<Vec<u8>>) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Get a reference to the underlying bytes.
+    pub fn get_ref(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    /// Returns the underlying shared bytes.
+    pub fn into_shared(self) -> Arc<Vec<u8>> {
+        self.data
+    }
+
+    /// Returns the underlying bytes. They are copied only if they are still shared.
+    pub fn into_inner(self) -> Vec<u8> {
+        Arc::try_unwrap(self.data).unwrap_or_else(|shared| shared.as_ref().clone())
+    }
+
+    fn remaining(&self) -> &[u8] {
+        let start = std::cmp::min(self.pos, self.data.len() as u64) as usize;
+        &self.data[start..]
+    }
+}
+
+impl From<Vec<u8>> for SnapshotCursor {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(Arc::new(data))
+    }
+}
+
+impl AsyncRead for SnapshotCursor {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        let remaining = this.remaining();
+        let n = std::cmp::min(remaining.len(), buf.remaining());
+        buf.put_slice(&remaining[..n]);
+
+        this.pos += n as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for SnapshotCursor {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+
+        let (base, offset) = match position {
+            SeekFrom::Start(n) => {
+                this.pos = n;
+                return Ok(());
+            }
+            SeekFrom::End(n) => (this.data.len() as u64, n),
+            SeekFrom::Current(n) => (this.pos, n),
+        };
+
+        match base.checked_add_signed(offset) {
+            Some(n) => {
+                this.pos = n;
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.pos))
+    }
+}
+
+impl AsyncWrite for SnapshotCursor {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        let pos = this.pos as usize;
+        let data = Arc::make_mut(&mut this.data);
+
+        // Same as `Cursor<Vec<u8>>`: fill the gap with zeros if the position is beyond the end.
+        if data.len() < pos {
+            data.resize(pos, 0);
+        }
+
+        let overlap = std::cmp::min(data.len() - pos, buf.len());
+        data[pos..pos + overlap].copy_from_slice(&buf[..overlap]);
+        data.extend_from_slice(&buf[overlap..]);
+
+        this.pos += buf.len() as u64;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
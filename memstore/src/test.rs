@@ -1,14 +1,46 @@
 use std::future::Future;
+use std::io::SeekFrom;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use maplit::btreeset;
 use openraft::testing::StoreBuilder;
 use openraft::testing::Suite;
+use openraft::EffectiveMembership;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::ErrorSubject;
+use openraft::ErrorVerb;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::Membership;
+use openraft::RaftLogReader;
+use openraft::RaftSnapshotBuilder;
+use openraft::RaftStorage;
+use openraft::RaftStorageDebug;
+use openraft::SnapshotMeta;
 use openraft::StorageError;
+use openraft::StorageHelper;
+use openraft::Violation;
+use openraft::Vote;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use tokio::io::AsyncWriteExt;
 
+use crate::CachedStore;
+use crate::ClientRequest;
+use crate::ClientState;
 use crate::Config;
+use crate::FaultPoint;
+use crate::FaultPolicy;
+use crate::FaultyStore;
+use crate::FileSnapshotStore;
 use crate::MemNodeId;
 use crate::MemStore;
+use crate::MemStoreStateMachine;
+use crate::SnapshotCodec;
+use crate::SnapshotCursor;
+use crate::StateMachineApply;
 
 struct MemBuilder {}
 #[async_trait]
@@ -50,3 +82,1138 @@ pub fn test_mem_store() -> Result<(), StorageError<MemNodeId>> {
     Suite::test_all(MemBuilder {})?;
     Ok(())
 }
+
+/// Build a `CachedStore` with a small capacity, so that reads are served by both the cache and the underlying store.
+struct CachedBuilder {}
+#[async_trait]
+impl StoreBuilder<Config, CachedStore<Config, Arc<MemStore>>> for CachedBuilder {
+    async fn run_test<Fun, Ret, Res>(&self, t: Fun) -> Result<Ret, StorageError<MemNodeId>>
+    where
+        Res: Future<Output = Result<Ret, StorageError<MemNodeId>>> + Send,
+        Fun: Fn(CachedStore<Config, Arc<MemStore>>) -> Res + Sync + Send,
+    {
+        let store = MemStore::new_async().await;
+        t(CachedStore::new(store, 3)).await
+    }
+}
+
+#[test]
+pub fn test_cached_store() -> Result<(), StorageError<MemNodeId>> {
+    Suite::test_all(CachedBuilder {})?;
+    Ok(())
+}
+
+/// Create an empty directory for the snapshot files of a `FileSnapshotStore`.
+fn new_snapshot_dir(name: &str) -> std::path::PathBuf {
+    static SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+    let seq = SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("memstore-test-{}-{}-{}", name, std::process::id(), seq));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Build a `FileSnapshotStore` in a new directory for every test.
+struct FileSnapshotStoreBuilder {}
+#[async_trait]
+impl StoreBuilder<Config, FileSnapshotStore> for FileSnapshotStoreBuilder {
+    async fn run_test<Fun, Ret, Res>(&self, t: Fun) -> Result<Ret, StorageError<MemNodeId>>
+    where
+        Res: Future<Output = Result<Ret, StorageError<MemNodeId>>> + Send,
+        Fun: Fn(FileSnapshotStore) -> Res + Sync + Send,
+    {
+        let dir = new_snapshot_dir("file-snapshot-suite");
+        let store = MemStore::new_async().await;
+        let res = t(FileSnapshotStore::new(store, &dir).await).await;
+        let _ = std::fs::remove_dir_all(&dir);
+        res
+    }
+}
+
+#[test]
+pub fn test_file_snapshot_store() -> Result<(), StorageError<MemNodeId>> {
+    Suite::test_all(FileSnapshotStoreBuilder {})?;
+    Ok(())
+}
+
+/// A snapshot of a `FileSnapshotStore` is read from and received into a file.
+#[tokio::test]
+pub async fn test_file_snapshot_store_transfer() -> Result<(), StorageError<MemNodeId>> {
+    let src_dir = new_snapshot_dir("file-snapshot-src");
+    let dst_dir = new_snapshot_dir("file-snapshot-dst");
+
+    let mut src = FileSnapshotStore::new(MemStore::new_async().await, &src_dir).await;
+
+    src.apply_to_state_machine(&[&Entry {
+        log_id: LogId::new(LeaderId::new(1, 0), 1),
+        payload: EntryPayload::Normal(ClientRequest {
+            client: "foo".to_string(),
+            serial: 1,
+            status: "bar".to_string(),
+        }),
+    }])
+    .await?;
+
+    tracing::info!("--- the built snapshot is stored in a file");
+    let snap = src.get_snapshot_builder().await.build_snapshot().await?;
+    let path = src.snapshot_path(&snap.meta.snapshot_id).unwrap();
+    assert_eq!(path.as_path(), snap.snapshot.path());
+
+    let want = std::fs::read(&path).unwrap();
+
+    tracing::info!("--- read the current snapshot from the file");
+    let mut got = vec![];
+    let mut current = src.get_current_snapshot().await?.unwrap();
+    current.snapshot.read_to_end(&mut got).await.unwrap();
+    assert_eq!(want, got);
+
+    tracing::info!("--- a new snapshot supersedes the file of the previous one");
+    let snap = src.get_snapshot_builder().await.build_snapshot().await?;
+    assert!(!path.exists());
+    assert!(src.get_snapshot_by_id(&current.meta.snapshot_id).await?.is_none());
+
+    let want = std::fs::read(snap.snapshot.path()).unwrap();
+
+    tracing::info!("--- receive the snapshot in chunks into a file and install it");
+    let mut dst = FileSnapshotStore::new(MemStore::new_async().await, &dst_dir).await;
+    {
+        let mut data = dst.begin_receiving_snapshot().await?;
+        for (i, chunk) in want.chunks(3).enumerate() {
+            data.seek(SeekFrom::Start(i as u64 * 3)).await.unwrap();
+            data.write_all(chunk).await.unwrap();
+        }
+
+        dst.install_snapshot(&snap.meta, data).await?;
+    }
+
+    let sm = dst.get_state_machine().await;
+    assert_eq!(Some(&"bar".to_string()), sm.data.client_status.get("foo"));
+
+    let installed = std::fs::read(dst.snapshot_path(&snap.meta.snapshot_id).unwrap()).unwrap();
+    assert_eq!(want, installed);
+
+    tracing::info!("--- only the file of the current snapshot is left");
+    let files = std::fs::read_dir(&dst_dir).unwrap().count();
+    assert_eq!(1, files);
+
+    let _ = std::fs::remove_dir_all(&src_dir);
+    let _ = std::fs::remove_dir_all(&dst_dir);
+
+    Ok(())
+}
+
+/// A snapshot id that can not be used as a file name is rejected by a `FileSnapshotStore`.
+#[tokio::test]
+pub async fn test_file_snapshot_store_invalid_snapshot_id() -> Result<(), StorageError<MemNodeId>> {
+    let dir = new_snapshot_dir("file-snapshot-invalid-id");
+
+    let mut store = FileSnapshotStore::new(MemStore::new_async().await, &dir).await;
+
+    for id in ["", ".", "..", "../x", "a/b", "a\\b"] {
+        assert!(store.snapshot_path(id).is_err(), "id: {:?}", id);
+    }
+
+    let meta = SnapshotMeta {
+        last_log_id: None,
+        last_membership: Default::default(),
+        snapshot_id: "../x".to_string(),
+    };
+
+    let data = store.begin_receiving_snapshot().await?;
+    let res = store.install_snapshot(&meta, data).await;
+    assert!(res.is_err());
+
+    let files = std::fs::read_dir(&dir).unwrap().count();
+    assert_eq!(0, files, "the received file is removed");
+
+    let _ = std::fs::remove_dir_all(&dir);
+
+    Ok(())
+}
+
+/// A `FileSnapshotStore` recovers from the newest snapshot file left in its directory, and removes the other files.
+#[tokio::test]
+pub async fn test_file_snapshot_store_recover() -> Result<(), StorageError<MemNodeId>> {
+    let dir = new_snapshot_dir("file-snapshot-recover");
+
+    let req = |serial: u64| ClientRequest {
+        client: "foo".to_string(),
+        serial,
+        status: format!("bar-{}", serial),
+    };
+
+    let want = {
+        let mut store = FileSnapshotStore::new(MemStore::new_async().await, &dir).await;
+
+        store
+            .apply_to_state_machine(&[&Entry {
+                log_id: LogId::new(LeaderId::new(1, 0), 1),
+                payload: EntryPayload::Normal(req(1)),
+            }])
+            .await?;
+
+        tracing::info!("--- keep a copy of an older snapshot file");
+        let old = store.get_snapshot_builder().await.build_snapshot().await?;
+        std::fs::copy(old.snapshot.path(), dir.join("snapshot-old")).unwrap();
+
+        store
+            .apply_to_state_machine(&[&Entry {
+                log_id: LogId::new(LeaderId::new(1, 0), 2),
+                payload: EntryPayload::Normal(req(2)),
+            }])
+            .await?;
+
+        store.get_snapshot_builder().await.build_snapshot().await?.meta
+    };
+
+    tracing::info!("--- leave a temp file and a corrupted snapshot file");
+    std::fs::write(dir.join("tmp-100"), b"partial").unwrap();
+    std::fs::write(dir.join("snapshot-corrupted"), b"\xff not a json").unwrap();
+
+    tracing::info!("--- a new store recovers from the newest snapshot");
+    let mut store = FileSnapshotStore::new(MemStore::new_async().await, &dir).await;
+
+    let current = store.get_current_snapshot().await?.unwrap();
+    assert_eq!(want.snapshot_id, current.meta.snapshot_id);
+    assert_eq!(want.last_log_id, current.meta.last_log_id);
+
+    let sm = store.get_state_machine().await;
+    assert_eq!(want.last_log_id, sm.last_applied_log);
+    assert_eq!(Some(&"bar-2".to_string()), sm.data.client_status.get("foo"));
+
+    tracing::info!("--- only the file of the recovered snapshot is left");
+    let files = std::fs::read_dir(&dir).unwrap().map(|x| x.unwrap().path()).collect::<Vec<_>>();
+    assert_eq!(vec![store.snapshot_path(&want.snapshot_id).unwrap()], files);
+
+    let _ = std::fs::remove_dir_all(&dir);
+
+    Ok(())
+}
+
+/// A snapshot that can not be decoded is likely corrupted during transfer, thus it is retryable.
+#[tokio::test]
+pub async fn test_install_corrupted_snapshot() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = MemStore::new_async().await;
+
+    let meta = SnapshotMeta {
+        last_log_id: Some(LogId::new(LeaderId::new(1, 0), 1)),
+        last_membership: EffectiveMembership::default(),
+        snapshot_id: "1-1-0".to_string(),
+    };
+
+    let data = Box::new(SnapshotCursor::from(b"\xff not a json".to_vec()));
+    let res = store.install_snapshot(&meta, data).await;
+
+    let err = res.unwrap_err();
+
+    let io_err = err.into_io().unwrap();
+    assert!(io_err.is_transient(), "corrupted snapshot should be retryable");
+    assert_eq!(&ErrorSubject::Snapshot(meta.signature()), io_err.subject());
+    assert_eq!(&ErrorVerb::Read, io_err.verb());
+
+    Ok(())
+}
+
+/// The deduplication state is included in a snapshot: after installing it, a duplicate request returns the cached
+/// response instead of being applied again.
+#[tokio::test]
+pub async fn test_dedup_after_install_snapshot() -> Result<(), StorageError<MemNodeId>> {
+    let req = |index: u64, serial: u64, status: &str| Entry {
+        log_id: LogId::new(LeaderId::new(1, 0), index),
+        payload: EntryPayload::Normal(ClientRequest {
+            client: "foo".to_string(),
+            serial,
+            status: status.to_string(),
+        }),
+    };
+
+    let mut src = MemStore::new_async().await;
+
+    let resps = src.apply_to_state_machine(&[&req(1, 1, "bar"), &req(2, 2, "baz")]).await?;
+    assert_eq!(None, resps[0].0);
+    assert_eq!(Some("bar".to_string()), resps[1].0);
+
+    let snap = src.get_snapshot_builder().await.build_snapshot().await?;
+
+    let mut dst = MemStore::new_async().await;
+    dst.install_snapshot(&snap.meta, snap.snapshot).await?;
+
+    tracing::info!("--- a duplicate of the last request returns the cached response");
+    {
+        let resps = dst.apply_to_state_machine(&[&req(3, 2, "qux")]).await?;
+        assert_eq!(Some("bar".to_string()), resps[0].0);
+
+        let sm = dst.get_state_machine().await;
+        assert_eq!(
+            Some(&"baz".to_string()),
+            sm.data.client_status.get("foo"),
+            "not applied again"
+        );
+    }
+
+    tracing::info!("--- a new request is applied");
+    {
+        let resps = dst.apply_to_state_machine(&[&req(4, 3, "qux")]).await?;
+        assert_eq!(Some("baz".to_string()), resps[0].0);
+
+        let sm = dst.get_state_machine().await;
+        assert_eq!(Some(&"qux".to_string()), sm.data.client_status.get("foo"));
+    }
+
+    Ok(())
+}
+
+/// A snapshot with a checksum that is corrupted during transfer is rejected before decoding.
+#[tokio::test]
+pub async fn test_install_snapshot_checksum() -> Result<(), StorageError<MemNodeId>> {
+    let mut src = Arc::new(MemStore::new().with_snapshot_checksum(true));
+
+    src.apply_to_state_machine(&[&Entry {
+        log_id: LogId::new(LeaderId::new(1, 0), 1),
+        payload: EntryPayload::Normal(ClientRequest {
+            client: "foo".to_string(),
+            serial: 1,
+            status: "bar".to_string(),
+        }),
+    }])
+    .await?;
+
+    let snap = src.get_snapshot_builder().await.build_snapshot().await?;
+    let data = snap.snapshot.get_ref().to_vec();
+
+    // Flip a bit of the last byte, as if it is corrupted during transfer.
+    let mut corrupted = data.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0x01;
+
+    let mut dst = MemStore::new_async().await;
+    let res = dst.install_snapshot(&snap.meta, Box::new(SnapshotCursor::from(corrupted))).await;
+
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"), "got: {}", err);
+
+    let io_err = err.into_io().unwrap();
+    assert!(io_err.is_transient(), "corrupted snapshot should be retryable");
+    assert_eq!(&ErrorSubject::Snapshot(snap.meta.signature()), io_err.subject());
+    assert_eq!(&ErrorVerb::Read, io_err.verb());
+
+    // A store without checksum enabled installs an intact snapshot with a checksum.
+    dst.install_snapshot(&snap.meta, Box::new(SnapshotCursor::from(data))).await?;
+
+    let sm = dst.get_state_machine().await;
+    assert_eq!(Some(&"bar".to_string()), sm.data.client_status.get("foo"));
+
+    Ok(())
+}
+
+/// Stores seeded with distinct snapshot index bases generate distinct and predictable snapshot ids.
+#[tokio::test]
+pub async fn test_snapshot_idx_seed() -> Result<(), StorageError<MemNodeId>> {
+    let log_id = LogId::new(LeaderId::new(1, 0), 1);
+
+    let mut ids = vec![];
+    for base in [100, 200] {
+        let mut store = Arc::new(<MemStore>::with_snapshot_idx(base));
+
+        store
+            .apply_to_state_machine(&[&Entry {
+                log_id,
+                payload: EntryPayload::Blank,
+            }])
+            .await?;
+
+        let mut b = store.get_snapshot_builder().await;
+        let snap = b.build_snapshot().await?;
+        ids.push(snap.meta.snapshot_id);
+    }
+
+    assert_eq!(
+        vec![
+            format!("{}-{}-{}", log_id.leader_id, log_id.index, 101),
+            format!("{}-{}-{}", log_id.leader_id, log_id.index, 201),
+        ],
+        ids
+    );
+
+    Ok(())
+}
+
+/// A snapshot installed into a new store before the node starts sets the initial state of the node.
+#[tokio::test]
+pub async fn test_install_initial_snapshot() -> Result<(), StorageError<MemNodeId>> {
+    let mut src = MemStore::new_async().await;
+
+    src.apply_to_state_machine(&[
+        &Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 1),
+            payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+        },
+        &Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 2),
+            payload: EntryPayload::Normal(ClientRequest {
+                client: "foo".to_string(),
+                serial: 1,
+                status: "bar".to_string(),
+            }),
+        },
+    ])
+    .await?;
+
+    let snap = src.get_snapshot_builder().await.build_snapshot().await?;
+
+    let mut dst = MemStore::new_async().await;
+    dst.install_initial_snapshot(&snap.meta, snap.snapshot.into_inner()).await?;
+
+    let st = StorageHelper::new(&mut dst).get_initial_state().await?;
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 2)), st.committed);
+    assert_eq!(
+        Some(LogId::new(LeaderId::new(1, 0), 1)),
+        st.membership_state.effective.log_id
+    );
+
+    let st = dst.get_log_state().await?;
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 2)), st.last_purged_log_id);
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 2)), st.last_log_id);
+
+    assert_eq!(
+        Some("bar".to_string()),
+        dst.sm.read().await.data.client_status.get("foo").cloned()
+    );
+
+    Ok(())
+}
+
+/// A membership exported from one store and imported into another results in the same membership config, while
+/// client data is not copied.
+#[tokio::test]
+pub async fn test_export_import_membership() -> Result<(), StorageError<MemNodeId>> {
+    let mut src = MemStore::new_async().await;
+
+    src.apply_to_state_machine(&[
+        &Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 1),
+            payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1,2,3}], None)),
+        },
+        &Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 2),
+            payload: EntryPayload::Normal(ClientRequest {
+                client: "foo".to_string(),
+                serial: 1,
+                status: "bar".to_string(),
+            }),
+        },
+    ])
+    .await?;
+
+    let em = src.export_membership().await;
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 1)), em.log_id);
+
+    let mut dst = MemStore::new_async().await;
+
+    // The membership entry can not leave a hole in the log.
+    let res = dst.import_membership(em.clone()).await;
+    let e = res.unwrap_err().into_defensive().unwrap();
+    assert_eq!(
+        Violation::LogsNonConsecutive {
+            prev: None,
+            next: LogId::new(LeaderId::new(1, 0), 1),
+        },
+        e.violation
+    );
+
+    dst.append_to_log(&[&Entry {
+        log_id: LogId::new(LeaderId::new(0, 0), 0),
+        payload: EntryPayload::Blank,
+    }])
+    .await?;
+    dst.import_membership(em).await?;
+    assert_eq!(2, dst.log_stats().await?.entries);
+
+    let want = StorageHelper::new(&mut src).get_membership().await?;
+    let got = StorageHelper::new(&mut dst).get_membership().await?;
+    assert_eq!(want, got);
+
+    let (last_applied, _) = dst.last_applied_state().await?;
+    assert_eq!(None, last_applied);
+    assert!(dst.sm.read().await.data.client_status.is_empty());
+
+    Ok(())
+}
+
+/// A retained snapshot can be fetched by its id, while older ones are dropped.
+#[tokio::test]
+pub async fn test_get_snapshot_by_id() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = Arc::new(MemStore::new().with_retained_snapshots(3));
+
+    let mut metas = vec![];
+    for index in 1..=4 {
+        store
+            .apply_to_state_machine(&[&Entry {
+                log_id: LogId::new(LeaderId::new(1, 0), index),
+                payload: EntryPayload::Blank,
+            }])
+            .await?;
+
+        let mut b = store.get_snapshot_builder().await;
+        let snap = b.build_snapshot().await?;
+        metas.push(snap.meta);
+    }
+
+    // The first one is no longer retained.
+    assert!(store.get_snapshot_by_id(&metas[0].snapshot_id).await?.is_none());
+
+    // Fetch the middle one of the three retained snapshots.
+    let snap = store.get_snapshot_by_id(&metas[2].snapshot_id).await?.unwrap();
+    assert_eq!(metas[2], snap.meta);
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 3)), snap.meta.last_log_id);
+
+    let sm: crate::MemStoreStateMachine = serde_json::from_slice(snap.snapshot.get_ref()).unwrap();
+    assert_eq!(snap.meta.last_log_id, sm.last_applied_log);
+
+    assert!(store.get_snapshot_by_id("no-such-snapshot").await?.is_none());
+
+    Ok(())
+}
+
+/// With only one snapshot retained, only the current snapshot can be fetched by id.
+#[tokio::test]
+pub async fn test_get_snapshot_by_id_retain_one() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = Arc::new(MemStore::new().with_retained_snapshots(1));
+
+    let mut metas = vec![];
+    for index in 1..=2 {
+        store
+            .apply_to_state_machine(&[&Entry {
+                log_id: LogId::new(LeaderId::new(1, 0), index),
+                payload: EntryPayload::Blank,
+            }])
+            .await?;
+
+        let mut b = store.get_snapshot_builder().await;
+        let snap = b.build_snapshot().await?;
+        metas.push(snap.meta);
+    }
+
+    assert!(store.get_snapshot_by_id(&metas[0].snapshot_id).await?.is_none());
+
+    let snap = store.get_snapshot_by_id(&metas[1].snapshot_id).await?.unwrap();
+    assert_eq!(metas[1], snap.meta);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_replace_state_machine() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = MemStore::new_async().await;
+
+    store
+        .apply_to_state_machine(&[&Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 1),
+            payload: EntryPayload::Blank,
+        }])
+        .await?;
+    store.get_snapshot_builder().await.build_snapshot().await?;
+    assert!(store.get_current_snapshot().await?.is_some());
+
+    let last_applied = LogId::new(LeaderId::new(3, 1), 10);
+    let membership = EffectiveMembership::new(
+        Some(LogId::new(LeaderId::new(2, 1), 5)),
+        Membership::new(vec![btreeset! {1,2,3}], None),
+    );
+
+    let mut sm: MemStoreStateMachine = MemStoreStateMachine {
+        last_applied_log: Some(last_applied),
+        last_membership: membership.clone(),
+        ..Default::default()
+    };
+    sm.data.client_status.insert("foo".to_string(), "bar".to_string());
+
+    store.replace_state_machine(sm).await;
+
+    let (got_applied, got_membership) = store.last_applied_state().await?;
+    assert_eq!(Some(last_applied), got_applied);
+    assert_eq!(membership, got_membership);
+
+    let got_sm = store.get_state_machine().await;
+    assert_eq!(Some(&"bar".to_string()), got_sm.data.client_status.get("foo"));
+
+    // The snapshot of the replaced state machine is dropped.
+    assert!(store.get_current_snapshot().await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_purge_logs_upto_concurrent_with_append() -> Result<(), StorageError<MemNodeId>> {
+    let store = MemStore::new_async().await;
+
+    let ent = |index| Entry {
+        log_id: LogId::new(LeaderId::new(1, 0), index),
+        payload: EntryPayload::Blank,
+    };
+
+    let first = (1..=50).map(ent).collect::<Vec<_>>();
+    store.clone().append_to_log(&first.iter().collect::<Vec<_>>()).await?;
+
+    let mut appender = store.clone();
+    let append = async move {
+        for index in 51..=100 {
+            appender.append_to_log(&[&ent(index)]).await?;
+        }
+        Ok::<(), StorageError<MemNodeId>>(())
+    };
+
+    let mut purger = store.clone();
+    let purge = async move { purger.purge_logs_upto(LogId::new(LeaderId::new(1, 0), 50)).await };
+
+    let (append_res, purge_res) = tokio::join!(tokio::spawn(append), tokio::spawn(purge));
+    append_res.unwrap()?;
+    purge_res.unwrap()?;
+
+    let mut store = store;
+    let st = store.get_log_state().await?;
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 50)), st.last_purged_log_id);
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 100)), st.last_log_id);
+
+    let logs = store.try_get_log_entries(..).await?;
+    assert_eq!(
+        (51..=100).collect::<Vec<_>>(),
+        logs.iter().map(|x| x.log_id.index).collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_get_log_entries_limited() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = MemStore::new_async().await;
+
+    let entries = (1..=10)
+        .map(|index| Entry::<Config> {
+            log_id: LogId::new(LeaderId::new(1, 0), index),
+            payload: EntryPayload::Blank,
+        })
+        .collect::<Vec<_>>();
+    store.append_to_log(&entries.iter().collect::<Vec<_>>()).await?;
+
+    // A blank payload is serialized to `"Blank"`, 7 bytes.
+    let indexes = |logs: Vec<Entry<Config>>| logs.iter().map(|x| x.log_id.index).collect::<Vec<_>>();
+
+    let logs = store.get_log_entries_limited(2..8, 3, u64::MAX).await?;
+    assert_eq!(vec![2, 3, 4], indexes(logs));
+
+    let logs = store.get_log_entries_limited(2..8, 100, 20).await?;
+    assert_eq!(vec![2, 3], indexes(logs));
+
+    let logs = store.get_log_entries_limited(2..8, 100, 21).await?;
+    assert_eq!(vec![2, 3, 4], indexes(logs));
+
+    // The first entry is returned even if it exceeds the limit.
+    let logs = store.get_log_entries_limited(2..8, 100, 0).await?;
+    assert_eq!(vec![2], indexes(logs));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_log_stats() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = MemStore::new_async().await;
+
+    let entries = (1..=10)
+        .map(|index| Entry::<Config> {
+            log_id: LogId::new(LeaderId::new(1, 0), index),
+            payload: EntryPayload::Blank,
+        })
+        .collect::<Vec<_>>();
+    store.append_to_log(&entries.iter().collect::<Vec<_>>()).await?;
+
+    // A blank payload is serialized to `"Blank"`, 7 bytes.
+    let stats = store.log_stats().await?;
+    assert_eq!(10, stats.entries);
+    assert_eq!(Some(70), stats.bytes);
+
+    store.purge_logs_upto(LogId::new(LeaderId::new(1, 0), 4)).await?;
+
+    let stats = store.log_stats().await?;
+    assert_eq!(6, stats.entries);
+    assert_eq!(Some(42), stats.bytes);
+
+    store.delete_conflict_logs_since(LogId::new(LeaderId::new(1, 0), 8)).await?;
+
+    let stats = store.log_stats().await?;
+    assert_eq!(3, stats.entries);
+    assert_eq!(Some(21), stats.bytes);
+
+    // Overwriting an entry does not count it twice.
+    store.append_to_log(&[&entries[6]]).await?;
+
+    let stats = store.log_stats().await?;
+    assert_eq!(3, stats.entries);
+    assert_eq!(Some(21), stats.bytes);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_faulty_store_nth_call() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = FaultyStore::new(MemStore::new_async().await)
+        .fail(FaultPoint::AppendToLog, FaultPolicy::NthCall(2))
+        .fail(FaultPoint::BuildSnapshot, FaultPolicy::NthCall(1));
+
+    let ent = |index| Entry::<Config> {
+        log_id: LogId::new(LeaderId::new(1, 0), index),
+        payload: EntryPayload::Blank,
+    };
+
+    store.append_to_log(&[&ent(1)]).await?;
+
+    let err = store.append_to_log(&[&ent(2)]).await.unwrap_err();
+    assert_eq!(&ErrorSubject::Logs, err.into_io().unwrap().subject());
+
+    store.append_to_log(&[&ent(2)]).await?;
+    assert_eq!(3, store.calls(FaultPoint::AppendToLog));
+
+    // The failed call does not reach the underlying store.
+    let logs = store.try_get_log_entries(..).await?;
+    assert_eq!(vec![1, 2], logs.iter().map(|x| x.log_id.index).collect::<Vec<_>>());
+
+    // Methods without a fault policy are not affected.
+    store.apply_to_state_machine(&[&ent(1)]).await?;
+    assert_eq!(0, store.calls(FaultPoint::ApplyToStateMachine));
+
+    // The snapshot builder shares the fault policies with the store.
+    assert!(store.get_snapshot_builder().await.build_snapshot().await.is_err());
+    store.get_snapshot_builder().await.build_snapshot().await?;
+
+    store.clear_fault(FaultPoint::BuildSnapshot);
+    assert_eq!(0, store.calls(FaultPoint::BuildSnapshot));
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "probability must be in [0, 1], got: 1.5")]
+fn test_faulty_store_invalid_probability() {
+    let store = FaultyStore::new(Arc::new(MemStore::new()));
+    store.set_fault(FaultPoint::AppendToLog, FaultPolicy::Probability { p: 1.5, seed: 0 });
+}
+
+#[tokio::test]
+pub async fn test_faulty_store_transient() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = FaultyStore::new(MemStore::new_async().await)
+        .fail_transient(FaultPoint::AppendToLog, FaultPolicy::NthCall(1))
+        .fail(FaultPoint::ApplyToStateMachine, FaultPolicy::NthCall(1));
+
+    let ent = Entry::<Config> {
+        log_id: LogId::new(LeaderId::new(1, 0), 1),
+        payload: EntryPayload::Blank,
+    };
+
+    let err = store.append_to_log(&[&ent]).await.unwrap_err();
+    assert!(err.into_io().unwrap().is_transient());
+
+    let err = store.apply_to_state_machine(&[&ent]).await.unwrap_err();
+    assert!(!err.into_io().unwrap().is_transient());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_faulty_store_probability() -> Result<(), StorageError<MemNodeId>> {
+    let run = |seed| async move {
+        let mut store = FaultyStore::new(MemStore::new_async().await).fail(
+            FaultPoint::ApplyToStateMachine,
+            FaultPolicy::Probability { p: 0.5, seed },
+        );
+
+        let mut failed = vec![];
+        for index in 1..=32 {
+            let ent = Entry::<Config> {
+                log_id: LogId::new(LeaderId::new(1, 0), index),
+                payload: EntryPayload::Blank,
+            };
+
+            if let Err(e) = store.apply_to_state_machine(&[&ent]).await {
+                assert_eq!(&ErrorSubject::Apply(ent.log_id), e.into_io().unwrap().subject());
+                failed.push(index);
+            }
+        }
+        failed
+    };
+
+    // The same seed fails the same calls.
+    let failed = run(7).await;
+    assert_eq!(failed, run(7).await);
+    assert!(!failed.is_empty());
+    assert!(failed.len() < 32);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_faulty_store_payload() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = FaultyStore::new(MemStore::new_async().await).fail(
+        FaultPoint::ApplyToStateMachine,
+        FaultPolicy::Payload(Arc::new(|p| matches!(p, EntryPayload::Membership(_)))),
+    );
+
+    let blank = |index| Entry::<Config> {
+        log_id: LogId::new(LeaderId::new(1, 0), index),
+        payload: EntryPayload::Blank,
+    };
+    let membership = Entry::<Config> {
+        log_id: LogId::new(LeaderId::new(1, 0), 3),
+        payload: EntryPayload::Membership(Membership::new(vec![btreeset! {1}], None)),
+    };
+
+    store.apply_to_state_machine(&[&blank(1)]).await?;
+
+    // The entry before the membership entry in the same batch is not applied either.
+    let err = store.apply_to_state_machine(&[&blank(2), &membership]).await.unwrap_err();
+    assert_eq!(&ErrorSubject::Apply(blank(2).log_id), err.into_io().unwrap().subject());
+
+    let (last_applied, _) = store.last_applied_state().await?;
+    assert_eq!(Some(blank(1).log_id), last_applied);
+
+    store.apply_to_state_machine(&[&blank(2)]).await?;
+    assert_eq!(3, store.calls(FaultPoint::ApplyToStateMachine));
+
+    Ok(())
+}
+
+/// A store saved to a file and loaded back returns the same initial state, logs and snapshot.
+#[tokio::test]
+pub async fn test_save_load_path() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = Arc::new(MemStore::new().with_retained_snapshots(2));
+
+    let ent = |index| Entry::<Config> {
+        log_id: LogId::new(LeaderId::new(if index == 0 { 0 } else { 1 }, 0), index),
+        payload: EntryPayload::Blank,
+    };
+
+    let entries = (0..=5).map(ent).collect::<Vec<_>>();
+    store.append_to_log(&entries.iter().collect::<Vec<_>>()).await?;
+    store.apply_to_state_machine(&entries[..=2].iter().collect::<Vec<_>>()).await?;
+    let prev_snap = store.get_snapshot_builder().await.build_snapshot().await?;
+    store.apply_to_state_machine(&entries[3..=3].iter().collect::<Vec<_>>()).await?;
+    store.save_vote(&Vote::new(1, 0)).await?;
+    store.get_snapshot_builder().await.build_snapshot().await?;
+    store.purge_logs_upto(LogId::new(LeaderId::new(1, 0), 1)).await?;
+
+    let path = std::env::temp_dir().join(format!("memstore-test-save-load-{}.json", std::process::id()));
+    store.save_to_path(&path).await?;
+
+    let mut loaded = Arc::new(MemStore::load_from_path(0, &path).await?);
+    let _ = std::fs::remove_file(&path);
+
+    let want = StorageHelper::new(&mut store).get_initial_state().await?;
+    let got = StorageHelper::new(&mut loaded).get_initial_state().await?;
+    assert_eq!(want, got);
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 3)), got.committed);
+
+    let st = loaded.get_log_state().await?;
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 1)), st.last_purged_log_id);
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 5)), st.last_log_id);
+
+    let logs = loaded.try_get_log_entries(..).await?;
+    assert_eq!(
+        vec![2, 3, 4, 5],
+        logs.iter().map(|x| x.log_id.index).collect::<Vec<_>>()
+    );
+
+    let want_snap = store.get_current_snapshot().await?.unwrap();
+    let got_snap = loaded.get_current_snapshot().await?.unwrap();
+    assert_eq!(want_snap.meta, got_snap.meta);
+    assert_eq!(want_snap.snapshot.get_ref(), got_snap.snapshot.get_ref());
+
+    // Snapshots other than the current one are retained too.
+    let got_prev = loaded.get_snapshot_by_id(&prev_snap.meta.snapshot_id).await?.unwrap();
+    assert_eq!(prev_snap.meta, got_prev.meta);
+    assert_eq!(prev_snap.snapshot.get_ref(), got_prev.snapshot.get_ref());
+
+    // The loaded store still retains 2 snapshots.
+    loaded.apply_to_state_machine(&entries[4..=4].iter().collect::<Vec<_>>()).await?;
+    loaded.get_snapshot_builder().await.build_snapshot().await?;
+    assert!(loaded.get_snapshot_by_id(&prev_snap.meta.snapshot_id).await?.is_none());
+    assert!(loaded.get_snapshot_by_id(&want_snap.meta.snapshot_id).await?.is_some());
+
+    Ok(())
+}
+
+/// The blank entry at index 0 is preserved through save and load.
+#[tokio::test]
+pub async fn test_save_load_path_index_0() -> Result<(), StorageError<MemNodeId>> {
+    let store = MemStore::new_async().await;
+
+    let ent0 = Entry::<Config> {
+        log_id: LogId::new(LeaderId::new(0, 0), 0),
+        payload: EntryPayload::Blank,
+    };
+    store.clone().append_to_log(&[&ent0]).await?;
+
+    let path = std::env::temp_dir().join(format!("memstore-test-save-load-0-{}.json", std::process::id()));
+    store.save_to_path(&path).await?;
+
+    let mut loaded = Arc::new(MemStore::load_from_path(0, &path).await?);
+    let _ = std::fs::remove_file(&path);
+
+    let logs = loaded.try_get_log_entries(..).await?;
+    assert_eq!(1, logs.len());
+    assert_eq!(ent0.log_id, logs[0].log_id);
+    assert!(matches!(logs[0].payload, EntryPayload::Blank));
+
+    Ok(())
+}
+
+/// Only the most recently applied `max_clients` clients are kept.
+#[test]
+pub fn test_client_state_max_clients() {
+    let req = |client: &str, serial: u64| ClientRequest {
+        client: client.to_string(),
+        serial,
+        status: format!("{}-{}", client, serial),
+    };
+
+    let mut st = ClientState::default();
+
+    st.apply_bounded(&req("a", 1), 2);
+    st.apply_bounded(&req("b", 1), 2);
+    st.apply_bounded(&req("a", 2), 2);
+    st.apply_bounded(&req("c", 1), 2);
+
+    // "b" is the least recently applied and is forgotten.
+    let mut clients = st.client_serial_responses.keys().cloned().collect::<Vec<_>>();
+    clients.sort();
+    assert_eq!(vec!["a".to_string(), "c".to_string()], clients);
+
+    let mut clients = st.client_status.keys().cloned().collect::<Vec<_>>();
+    clients.sort();
+    assert_eq!(vec!["a".to_string(), "c".to_string()], clients);
+
+    // A retried request of an active client is still deduplicated.
+    let resp = st.apply_bounded(&req("a", 2), 2);
+    assert_eq!(Some("a-1".to_string()), resp.0);
+    assert_eq!(Some(&"a-2".to_string()), st.client_status.get("a"));
+
+    // A retried request of a forgotten client is applied again.
+    let resp = st.apply_bounded(&req("b", 1), 2);
+    assert_eq!(None, resp.0);
+
+    // A deduplicated request is not applied, thus "a" is now the least recently applied one.
+    assert!(!st.client_status.contains_key("a"));
+}
+
+/// A snapshot built with a codec is installed by a store using another codec.
+async fn check_snapshot_codec(codec: SnapshotCodec) -> Result<(), StorageError<MemNodeId>> {
+    let mut src = Arc::new(MemStore::new().with_snapshot_codec(codec));
+
+    src.apply_to_state_machine(&[&Entry {
+        log_id: LogId::new(LeaderId::new(1, 0), 1),
+        payload: EntryPayload::Normal(ClientRequest {
+            client: "foo".to_string(),
+            serial: 1,
+            status: "bar".to_string(),
+        }),
+    }])
+    .await?;
+
+    let snap = src.get_snapshot_builder().await.build_snapshot().await?;
+    assert_eq!(codec, SnapshotCodec::detect(snap.snapshot.get_ref()));
+
+    let mut dst = MemStore::new_async().await;
+    dst.install_snapshot(&snap.meta, snap.snapshot).await?;
+
+    let sm = dst.get_state_machine().await;
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 1)), sm.last_applied_log);
+    assert_eq!(Some(&"bar".to_string()), sm.data.client_status.get("foo"));
+    assert_eq!(codec, dst.current_snapshot.read().await.as_ref().unwrap().codec);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_snapshot_codec_json() -> Result<(), StorageError<MemNodeId>> {
+    check_snapshot_codec(SnapshotCodec::Json).await
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+pub async fn test_snapshot_codec_gzip() -> Result<(), StorageError<MemNodeId>> {
+    check_snapshot_codec(SnapshotCodec::Gzip).await
+}
+
+#[cfg(feature = "zstd")]
+#[tokio::test]
+pub async fn test_snapshot_codec_zstd() -> Result<(), StorageError<MemNodeId>> {
+    check_snapshot_codec(SnapshotCodec::Zstd).await
+}
+
+/// A custom command type: add a number to a counter and return the sum.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct Add(u64);
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+struct Counter {
+    sum: u64,
+}
+
+impl StateMachineApply<Add, u64> for Counter {
+    fn apply(&mut self, data: &Add) -> u64 {
+        self.sum += data.0;
+        self.sum
+    }
+
+    fn empty_response(&self) -> u64 {
+        self.sum
+    }
+}
+
+#[tokio::test]
+pub async fn test_custom_state_machine_apply() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = Arc::new(MemStore::<Add, u64, Counter>::default());
+
+    let entries = [
+        Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 1),
+            payload: EntryPayload::Normal(Add(2)),
+        },
+        Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 2),
+            payload: EntryPayload::Blank,
+        },
+        Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), 3),
+            payload: EntryPayload::Normal(Add(3)),
+        },
+    ];
+
+    let resp = store.apply_to_state_machine(&entries.iter().collect::<Vec<_>>()).await?;
+    assert_eq!(vec![2, 2, 5], resp);
+
+    let sm = store.get_state_machine().await;
+    assert_eq!(5, sm.data.sum);
+    assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 3)), sm.last_applied_log);
+
+    Ok(())
+}
+
+/// A state machine serialized before the application data is moved into `data` is still able to be decoded.
+#[test]
+fn test_decode_legacy_state_machine() -> Result<(), serde_json::Error> {
+    let last_applied = LogId::new(LeaderId::new(1, 0), 3);
+    let membership = EffectiveMembership::new(Some(last_applied), Membership::new(vec![btreeset! {1,2,3}], None));
+
+    let legacy = serde_json::json!({
+        "last_applied_log": last_applied,
+        "last_membership": membership,
+        "client_serial_responses": {"foo": [1, null]},
+        "client_status": {"foo": "bar"},
+    });
+
+    let sm: MemStoreStateMachine = serde_json::from_value(legacy)?;
+    assert_eq!(Some(last_applied), sm.last_applied_log);
+    assert_eq!(membership, sm.last_membership);
+    assert_eq!(Some(&"bar".to_string()), sm.data.client_status.get("foo"));
+    assert_eq!(Some(&(1, None)), sm.data.client_serial_responses.get("foo"));
+
+    // The current form is decoded as well.
+    let current: MemStoreStateMachine = serde_json::from_slice(&serde_json::to_vec(&sm)?)?;
+    assert_eq!(Some(&"bar".to_string()), current.data.client_status.get("foo"));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_get_current_snapshot_shares_data() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = MemStore::new_async().await;
+
+    store.get_snapshot_builder().await.build_snapshot().await?;
+
+    let a = store.get_current_snapshot().await?.unwrap();
+    let b = store.get_current_snapshot().await?.unwrap();
+
+    assert!(Arc::ptr_eq(&a.snapshot.into_shared(), &b.snapshot.into_shared()));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_snapshot_cursor() -> std::io::Result<()> {
+    let shared = Arc::new(b"foobar".to_vec());
+    let mut c = SnapshotCursor::new(shared.clone());
+
+    let mut buf = vec![0; 3];
+    c.read_exact(&mut buf).await?;
+    assert_eq!(b"foo", buf.as_slice());
+
+    // Writing to a shared cursor copies the bytes first.
+    c.write_all(b"BARBAZ").await?;
+    assert_eq!(b"foobar", shared.as_slice());
+    assert_eq!(b"fooBARBAZ", c.get_ref());
+
+    c.seek(SeekFrom::End(-3)).await?;
+    let mut rest = vec![];
+    c.read_to_end(&mut rest).await?;
+    assert_eq!(b"BAZ", rest.as_slice());
+
+    assert!(c.seek(SeekFrom::Current(-100)).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_cached_store_no_stale_read() -> Result<(), StorageError<MemNodeId>> {
+    let mut store = CachedStore::new(MemStore::new_async().await, 5);
+
+    let ent = |term, index| Entry::<Config> {
+        log_id: LogId::new(LeaderId::new(term, 0), index),
+        payload: EntryPayload::Blank,
+    };
+    let indexes = |logs: Vec<Entry<Config>>| logs.iter().map(|x| x.log_id.index).collect::<Vec<_>>();
+
+    let entries = (1..=10).map(|i| ent(1, i)).collect::<Vec<_>>();
+    store.append_to_log(&entries.iter().collect::<Vec<_>>()).await?;
+    assert_eq!(vec![6, 7, 8, 9, 10], store.cached_indexes());
+
+    // Reads of the tail are served by the cache.
+    assert_eq!(vec![7, 8, 9], indexes(store.try_get_log_entries(7..10).await?));
+    assert_eq!(Some(1), store.get_log_term(10).await?);
+    assert_eq!(2, store.cache_hits());
+
+    assert_eq!(vec![4, 5, 6], indexes(store.try_get_log_entries(4..7).await?));
+    assert_eq!(2, store.cache_hits());
+
+    // Deleted entries are not returned.
+    store.delete_conflict_logs_since(LogId::new(LeaderId::new(1, 0), 8)).await?;
+    assert_eq!(vec![6, 7], store.cached_indexes());
+
+    assert_eq!(vec![6, 7], indexes(store.try_get_log_entries(6..11).await?));
+    assert_eq!(None, store.get_log_term(8).await?);
+
+    // Overwritten entries are replaced.
+    store.append_to_log(&[&ent(2, 8), &ent(2, 9)]).await?;
+    assert_eq!(vec![6, 7, 8, 9], store.cached_indexes());
+
+    let logs = store.try_get_log_entries(8..).await?;
+    assert_eq!(
+        vec![LogId::new(LeaderId::new(2, 0), 8), LogId::new(LeaderId::new(2, 0), 9)],
+        logs.iter().map(|x| x.log_id).collect::<Vec<_>>()
+    );
+
+    let mut reader = store.get_log_reader().await;
+    assert_eq!(
+        logs.iter().map(|x| x.log_id).collect::<Vec<_>>(),
+        reader.try_get_log_entries(8..).await?.iter().map(|x| x.log_id).collect::<Vec<_>>()
+    );
+
+    // Purged entries are not returned.
+    store.purge_logs_upto(LogId::new(LeaderId::new(1, 0), 7)).await?;
+    assert_eq!(vec![8, 9], store.cached_indexes());
+    assert_eq!(vec![8, 9], indexes(store.try_get_log_entries(..).await?));
+
+    // The greatest index as an inclusive bound does not overflow.
+    assert_eq!(vec![9], indexes(store.try_get_log_entries(9..=u64::MAX).await?));
+
+    Ok(())
+}
@@ -21,6 +21,29 @@ pub enum SnapshotPolicy {
     /// A snapshot will be generated once the log has grown the specified number of logs since
     /// the last snapshot.
     LogsSinceLast(u64),
+
+    /// A snapshot will be generated once the applied logs not included in the last snapshot have
+    /// grown to the specified number of bytes.
+    ///
+    /// The leader also sends a snapshot to a follower that lacks this many bytes of applied logs.
+    ///
+    /// The size of a log is the serialized size of its payload reported by
+    /// [`RaftStorage::serialized_size`]. Logs whose size is unknown are not counted.
+    ///
+    /// [`RaftStorage::serialized_size`]: `crate::RaftStorage::serialized_size`
+    SizeSinceLast(u64),
+
+    /// A snapshot is never generated automatically.
+    ///
+    /// Snapshots are only built when requested with [`Raft::trigger_snapshot`], e.g., by an
+    /// application that manages compaction on its own. A follower that falls far behind can still
+    /// receive the last such snapshot, but the leader never starts building one for it.
+    ///
+    /// Logs are purged only after a snapshot is built. Thus with this policy the logs grow
+    /// unbounded unless the application builds snapshots.
+    ///
+    /// [`Raft::trigger_snapshot`]: `crate::Raft::trigger_snapshot`
+    Never,
 }
 
 /// Parse number with unit such as 5.3 KB
@@ -34,26 +57,37 @@ fn parse_bytes_with_unit(src: &str) -> Result<u64, ConfigError> {
 }
 
 fn parse_snapshot_policy(src: &str) -> Result<SnapshotPolicy, ConfigError> {
+    let syntax = "since_last:<num>|size_since_last:<bytes>|never";
+
+    if src == "never" {
+        return Ok(SnapshotPolicy::Never);
+    }
+
     let elts = src.split(':').collect::<Vec<_>>();
     if elts.len() != 2 {
         return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "since_last:<num>".to_string(),
+            syntax: syntax.to_string(),
             invalid: src.to_string(),
         });
     }
 
-    if elts[0] != "since_last" {
-        return Err(ConfigError::InvalidSnapshotPolicy {
-            syntax: "since_last:<num>".to_string(),
+    match elts[0] {
+        "since_last" => {
+            let n_logs = elts[1].parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
+                invalid: src.to_string(),
+                reason: e.to_string(),
+            })?;
+            Ok(SnapshotPolicy::LogsSinceLast(n_logs))
+        }
+        "size_since_last" => {
+            let n_bytes = parse_bytes_with_unit(elts[1])?;
+            Ok(SnapshotPolicy::SizeSinceLast(n_bytes))
+        }
+        _ => Err(ConfigError::InvalidSnapshotPolicy {
+            syntax: syntax.to_string(),
             invalid: src.to_string(),
-        });
+        }),
     }
-
-    let n_logs = elts[1].parse::<u64>().map_err(|e| ConfigError::InvalidNumber {
-        invalid: src.to_string(),
-        reason: e.to_string(),
-    })?;
-    Ok(SnapshotPolicy::LogsSinceLast(n_logs))
 }
 
 /// The runtime configuration for a Raft node.
@@ -96,6 +130,8 @@ pub struct Config {
     pub election_timeout_max: u64,
 
     /// The heartbeat interval in milliseconds at which leaders will send heartbeats to followers
+    ///
+    /// It must be at most half of `election_timeout_min`.
     #[clap(long, default_value = "50")]
     pub heartbeat_interval: u64,
 
@@ -103,6 +139,14 @@ pub struct Config {
     #[clap(long, default_value = "200")]
     pub install_snapshot_timeout: u64,
 
+    /// The timeout in milliseconds for a follower to wait for the next chunk of the snapshot it is receiving, 0 to
+    /// wait forever.
+    ///
+    /// If the transfer stalls, e.g., the leader dies while sending, the partially received snapshot data is dropped
+    /// after this timeout, instead of being held until another snapshot is sent.
+    #[clap(long, default_value = "0")]
+    pub snapshot_receive_timeout: u64,
+
     /// The maximum number of entries per payload allowed to be transmitted during replication
     ///
     /// If this is too low, it will take longer for the nodes to be brought up to
@@ -110,6 +154,25 @@ pub struct Config {
     #[clap(long, default_value = "300")]
     pub max_payload_entries: u64,
 
+    /// The maximum total size of the entries per payload allowed to be transmitted during replication (in bytes)
+    ///
+    /// A payload always contains at least one entry, even if it is larger than this limit.
+    /// It is only respected if the log reader is able to tell the size of an entry, see
+    /// [`RaftLogReader::get_log_entries_limited`].
+    ///
+    /// [`RaftLogReader::get_log_entries_limited`]: `crate::RaftLogReader::get_log_entries_limited`
+    #[clap(long, default_value = "64MiB", parse(try_from_str=parse_bytes_with_unit))]
+    pub max_payload_bytes: u64,
+
+    /// The maximum number of AppendEntries requests sent to a follower in one round trip.
+    ///
+    /// When a follower is lagging, its replication stream loads up to this many consecutive payloads and sends them
+    /// with [`RaftNetwork::send_append_entries_batch`], so that a transport is able to pipeline them.
+    ///
+    /// [`RaftNetwork::send_append_entries_batch`]: `crate::RaftNetwork::send_append_entries_batch`
+    #[clap(long, default_value = "1")]
+    pub max_append_batches: u64,
+
     /// The distance behind in log replication a follower must fall before it is considered lagging
     ///
     /// Once a replication stream transition into line-rate state, the target node will be considered safe to join a
@@ -129,9 +192,22 @@ pub struct Config {
     #[clap(long, default_value = "3MiB", parse(try_from_str=parse_bytes_with_unit))]
     pub snapshot_max_chunk_size: u64,
 
+    /// The maximum size of a single client write entry allowed to be appended to the log (in bytes).
+    ///
+    /// The size of an entry is the serialized size reported by [`RaftStorage::serialized_size`].
+    /// An entry exceeding this limit is rejected with [`EntryTooLarge`] before it enters the log.
+    ///
+    /// [`RaftStorage::serialized_size`]: `crate::RaftStorage::serialized_size`
+    /// [`EntryTooLarge`]: `crate::error::EntryTooLarge`
+    #[clap(long, default_value = "64MiB", parse(try_from_str=parse_bytes_with_unit))]
+    pub max_entry_bytes: u64,
+
     /// The maximum number of logs to keep that are already included in **snapshot**.
     ///
     /// Logs that are not in snapshot will never be purged.
+    ///
+    /// Keeping a tail of logs lets a follower that is only slightly behind catch up by replicating logs,
+    /// instead of receiving a full snapshot.
     #[clap(long, default_value = "1000")]
     pub max_in_snapshot_log_to_keep: u64,
 
@@ -139,6 +215,56 @@ pub struct Config {
     #[clap(long, default_value = "1")]
     pub purge_batch_size: u64,
 
+    /// The maximum number of committed logs to apply to the state machine in one call, 0 for no limit.
+    ///
+    /// When many logs are committed at once, e.g., when a follower catches up, they are applied in several batches,
+    /// so that a state machine lock held by `RaftStorage::apply_to_state_machine()` is released in between.
+    #[clap(long, default_value = "0")]
+    pub max_apply_entries: u64,
+
+    /// The minimum interval in milliseconds between two metrics reports, 0 to report every change at once.
+    ///
+    /// Changes of replication progress and local data, such as log, applied log or snapshot, are coalesced and
+    /// reported at most once per interval. A pending change is reported when the next message or tick is handled.
+    /// Changes of server state, leader or membership are always reported at once.
+    #[clap(long, default_value = "0")]
+    pub metrics_report_min_interval: u64,
+
+    /// The priority of this node to become the leader. A node with a higher priority is preferred.
+    ///
+    /// The priority is sent in vote requests. When the election timer of a node is armed, it is delayed by
+    /// `heartbeat_interval` for every point by which its own priority is below the highest one it has seen in vote
+    /// requests, but by at most `election_timeout_max`. It is only a preference: a node with a lower priority still
+    /// elects itself if nodes with higher priority are down.
+    ///
+    /// All nodes have the same priority by default.
+    #[clap(long, default_value = "0")]
+    pub election_priority: u32,
+
+    /// The seed of the random number generator that samples election timeouts, to make elections reproducible in
+    /// tests.
+    ///
+    /// If it is set, election timeouts are sampled from a `StdRng` seeded with it and the node id. Otherwise they are
+    /// sampled from the thread RNG. Nodes in a cluster can share the same seed: the node id makes them sample
+    /// different timeouts.
+    #[clap(long)]
+    pub election_timeout_seed: Option<u64>,
+
+    /// The number of times to retry appending logs or applying logs to the state machine, when the storage fails with
+    /// a transient error, see `StorageIOError::transient`.
+    ///
+    /// The n-th retry is made after `10ms * 2^(n-1)`. The node shuts down if the last retry also fails.
+    #[clap(long, default_value = "3")]
+    pub storage_retry_count: u64,
+
+    /// The number of times in a row a replication target may ask a leader for a snapshot without getting one, before
+    /// the leader logs an error, e.g., because building the snapshot keeps failing with a transient error.
+    ///
+    /// The n-th request is answered after `10ms * 2^(n-1)`, at most about 10 seconds. It keeps being retried after
+    /// this many times.
+    #[clap(long, default_value = "3")]
+    pub snapshot_retry_count: u64,
+
     /// Enable or disable tick.
     ///
     /// If ticking is disabled, timeout based events are all disabled:
@@ -170,6 +296,21 @@ pub struct Config {
            action = clap::ArgAction::Set,
            default_missing_value = "true")]
     pub enable_elect: bool,
+
+    /// Whether a follower runs a pre-vote round before starting an election.
+    ///
+    /// With pre-vote, a follower whose election timer expires first asks the voters whether they would grant its
+    /// vote, without increasing its term. It starts a real election only if a quorum would.
+    /// A voter does not grant a pre-vote if it has heard from a leader within the election timeout. Thus a node that
+    /// is partitioned away does not keep increasing its term and does not disrupt the leader when it reconnects.
+    ///
+    /// All voters have to support pre-vote RPC, i.e., `RaftNetwork::send_pre_vote()` and `Raft::pre_vote()`,
+    /// before it is enabled.
+    #[clap(long,
+           default_value_t = false,
+           action = clap::ArgAction::Set,
+           default_missing_value = "true")]
+    pub enable_pre_vote: bool,
 }
 
 /// Updatable config for a raft runtime.
@@ -195,8 +336,16 @@ impl Default for Config {
 
 impl Config {
     /// Generate a new random election timeout within the configured min & max.
+    ///
+    /// The result is in `[election_timeout_min, election_timeout_max)`. It is used each time the election timer of a
+    /// follower or candidate is armed, so that nodes do not time out at the same moment.
     pub fn new_rand_election_timeout(&self) -> u64 {
-        thread_rng().gen_range(self.election_timeout_min..self.election_timeout_max)
+        self.new_rand_election_timeout_with(&mut thread_rng())
+    }
+
+    /// Generate a new random election timeout within the configured min & max, from the given RNG.
+    pub fn new_rand_election_timeout_with<R: Rng>(&self, rng: &mut R) -> u64 {
+        rng.gen_range(self.election_timeout_min..self.election_timeout_max)
     }
 
     pub fn build(args: &[&str]) -> Result<Config, ConfigError> {
@@ -220,10 +369,22 @@ impl Config {
             });
         }
 
+        // A follower must be able to miss a heartbeat without starting an election.
+        if self.election_timeout_min < self.heartbeat_interval.saturating_mul(2) {
+            return Err(ConfigError::ElectionTimeoutTooCloseToHeartBeat {
+                election_timeout_min: self.election_timeout_min,
+                heartbeat_interval: self.heartbeat_interval,
+            });
+        }
+
         if self.max_payload_entries == 0 {
             return Err(ConfigError::MaxPayloadIs0);
         }
 
+        if self.max_append_batches == 0 {
+            return Err(ConfigError::MaxAppendBatchesIs0);
+        }
+
         Ok(self)
     }
 }
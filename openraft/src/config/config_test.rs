@@ -1,3 +1,6 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
 use crate::config::error::ConfigError;
 use crate::Config;
 use crate::SnapshotPolicy;
@@ -11,10 +14,16 @@ fn test_config_defaults() {
 
     assert_eq!(50, cfg.heartbeat_interval);
     assert_eq!(300, cfg.max_payload_entries);
+    assert_eq!(1, cfg.max_append_batches);
     assert_eq!(1000, cfg.replication_lag_threshold);
 
     assert_eq!(3 * 1024 * 1024, cfg.snapshot_max_chunk_size);
+    assert_eq!(64 * 1024 * 1024, cfg.max_entry_bytes);
     assert_eq!(SnapshotPolicy::LogsSinceLast(5000), cfg.snapshot_policy);
+    assert_eq!(3, cfg.storage_retry_count);
+    assert_eq!(3, cfg.snapshot_retry_count);
+    assert_eq!(0, cfg.max_apply_entries);
+    assert_eq!(0, cfg.snapshot_receive_timeout);
 }
 
 #[test]
@@ -42,6 +51,53 @@ fn test_invalid_election_timeout_config_produces_expected_error() {
         election_timeout_min: 1000,
         heartbeat_interval: 1500
     });
+
+    let config = Config {
+        election_timeout_min: 1000,
+        election_timeout_max: 2000,
+        heartbeat_interval: 501,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::ElectionTimeoutTooCloseToHeartBeat {
+        election_timeout_min: 1000,
+        heartbeat_interval: 501
+    });
+
+    // Doubling the heartbeat interval does not overflow.
+    let config = Config {
+        election_timeout_min: u64::MAX - 1,
+        election_timeout_max: u64::MAX,
+        heartbeat_interval: u64::MAX / 2 + 1,
+        ..Default::default()
+    };
+
+    let res = config.validate();
+    let err = res.unwrap_err();
+    assert_eq!(err, ConfigError::ElectionTimeoutTooCloseToHeartBeat {
+        election_timeout_min: u64::MAX - 1,
+        heartbeat_interval: u64::MAX / 2 + 1
+    });
+}
+
+#[test]
+fn test_new_rand_election_timeout_in_range() -> anyhow::Result<()> {
+    let config = Config {
+        election_timeout_min: 100,
+        election_timeout_max: 110,
+        heartbeat_interval: 50,
+        ..Default::default()
+    }
+    .validate()?;
+
+    for _ in 0..1000 {
+        let t = config.new_rand_election_timeout();
+        assert!((100..110).contains(&t), "timeout {} not in [100, 110)", t);
+    }
+
+    Ok(())
 }
 
 #[test]
@@ -57,6 +113,7 @@ fn test_build() -> anyhow::Result<()> {
         "--replication-lag-threshold=202",
         "--snapshot-policy=since_last:203",
         "--snapshot-max-chunk-size=204",
+        "--max-entry-bytes=206",
         "--max-in-snapshot-log-to-keep=205",
         "--purge-batch-size=207",
     ])?;
@@ -70,12 +127,27 @@ fn test_build() -> anyhow::Result<()> {
     assert_eq!(202, config.replication_lag_threshold);
     assert_eq!(SnapshotPolicy::LogsSinceLast(203), config.snapshot_policy);
     assert_eq!(204, config.snapshot_max_chunk_size);
+    assert_eq!(206, config.max_entry_bytes);
     assert_eq!(205, config.max_in_snapshot_log_to_keep);
     assert_eq!(207, config.purge_batch_size);
 
     Ok(())
 }
 
+#[test]
+fn test_config_snapshot_policy() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--snapshot-policy=since_last:100"])?;
+    assert_eq!(SnapshotPolicy::LogsSinceLast(100), config.snapshot_policy);
+
+    let config = Config::build(&["foo", "--snapshot-policy=size_since_last:2KiB"])?;
+    assert_eq!(SnapshotPolicy::SizeSinceLast(2048), config.snapshot_policy);
+
+    let config = Config::build(&["foo", "--snapshot-policy=never"])?;
+    assert_eq!(SnapshotPolicy::Never, config.snapshot_policy);
+
+    Ok(())
+}
+
 #[test]
 fn test_config_enable_tick() -> anyhow::Result<()> {
     let config = Config::build(&["foo", "--enable-tick=false"])?;
@@ -126,3 +198,62 @@ fn test_config_enable_elect() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_config_enable_pre_vote() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--enable-pre-vote=false"])?;
+    assert_eq!(false, config.enable_pre_vote);
+
+    let config = Config::build(&["foo", "--enable-pre-vote=true"])?;
+    assert_eq!(true, config.enable_pre_vote);
+
+    let config = Config::build(&["foo", "--enable-pre-vote"])?;
+    assert_eq!(true, config.enable_pre_vote);
+
+    let config = Config::build(&["foo"])?;
+    assert_eq!(false, config.enable_pre_vote);
+
+    Ok(())
+}
+
+#[test]
+fn test_config_metrics_report_min_interval() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--metrics-report-min-interval=200"])?;
+    assert_eq!(200, config.metrics_report_min_interval);
+
+    let config = Config::build(&["foo"])?;
+    assert_eq!(0, config.metrics_report_min_interval);
+
+    Ok(())
+}
+
+#[test]
+fn test_config_max_payload_bytes() -> anyhow::Result<()> {
+    let config = Config::build(&["foo", "--max-payload-bytes=1MiB"])?;
+    assert_eq!(1024 * 1024, config.max_payload_bytes);
+
+    let config = Config::build(&["foo"])?;
+    assert_eq!(64 * 1024 * 1024, config.max_payload_bytes);
+
+    Ok(())
+}
+
+#[test]
+fn test_config_election_timeout_seed() -> anyhow::Result<()> {
+    let config = Config::build(&["foo"])?;
+    assert_eq!(None, config.election_timeout_seed);
+
+    let config = Config::build(&["foo", "--election-timeout-seed=7"])?;
+    assert_eq!(Some(7), config.election_timeout_seed);
+
+    // The same seed samples the same timeouts.
+    let sample = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..10).map(|_| config.new_rand_election_timeout_with(&mut rng)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(sample(7), sample(7));
+    assert!(sample(7).iter().all(|t| (150..300).contains(t)));
+
+    Ok(())
+}
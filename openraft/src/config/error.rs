@@ -8,12 +8,21 @@ pub enum ConfigError {
     #[error("max_payload_entries must be > 0")]
     MaxPayloadIs0,
 
+    #[error("max_append_batches must be > 0")]
+    MaxAppendBatchesIs0,
+
     #[error("election_timeout_min({election_timeout_min}) must be > heartbeat_interval({heartbeat_interval})")]
     ElectionTimeoutLTHeartBeat {
         election_timeout_min: u64,
         heartbeat_interval: u64,
     },
 
+    #[error("election_timeout_min({election_timeout_min}) must be >= 2 * heartbeat_interval({heartbeat_interval})")]
+    ElectionTimeoutTooCloseToHeartBeat {
+        election_timeout_min: u64,
+        heartbeat_interval: u64,
+    },
+
     #[error("snapshot policy string is invalid: '{invalid:?}' expect: '{syntax}'")]
     InvalidSnapshotPolicy { invalid: String, syntax: String },
 
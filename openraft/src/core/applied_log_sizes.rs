@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+
+/// The serialized sizes of the applied logs that are not included in a snapshot, used by
+/// [`SnapshotPolicy::SizeSinceLast`](`crate::SnapshotPolicy::SizeSinceLast`).
+///
+/// Only applied logs are counted: they are committed thus never deleted by a conflict.
+/// It is shared by `RaftCore` and the replication streams.
+#[derive(Debug, Default)]
+pub(crate) struct AppliedLogSizes {
+    /// The index of every applied log not in a snapshot, and the total size of the applied logs up to and including
+    /// it, since the node starts.
+    totals: VecDeque<(u64, u64)>,
+
+    /// The total size of the applied logs that are included in a snapshot and removed from `totals`.
+    purged: u64,
+}
+
+impl AppliedLogSizes {
+    /// Add the size of an applied log. Logs have to be added in index order.
+    pub(crate) fn append(&mut self, index: u64, size: u64) {
+        debug_assert!(self.totals.back().map(|x| x.0 < index).unwrap_or(true));
+
+        let total = self.last_total() + size;
+        self.totals.push_back((index, total));
+    }
+
+    /// Remove the logs up to and including `index`, because they are included in a snapshot.
+    pub(crate) fn purge_upto(&mut self, index: Option<u64>) {
+        while let Some((i, total)) = self.totals.front() {
+            if Some(*i) > index {
+                break;
+            }
+            self.purged = *total;
+            self.totals.pop_front();
+        }
+    }
+
+    /// The total size of the applied logs that are not in a snapshot.
+    pub(crate) fn bytes(&self) -> u64 {
+        self.last_total() - self.purged
+    }
+
+    /// The total size of the applied logs after `index` that are not in a snapshot.
+    pub(crate) fn bytes_after(&self, index: Option<u64>) -> u64 {
+        let n = self.totals.partition_point(|(i, _)| Some(*i) <= index);
+
+        let base = if n == 0 { self.purged } else { self.totals[n - 1].1 };
+        self.last_total() - base
+    }
+
+    fn last_total(&self) -> u64 {
+        self.totals.back().map(|x| x.1).unwrap_or(self.purged)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::applied_log_sizes::AppliedLogSizes;
+
+    #[test]
+    fn test_applied_log_sizes() -> anyhow::Result<()> {
+        let mut s = AppliedLogSizes::default();
+        assert_eq!(0, s.bytes());
+        assert_eq!(0, s.bytes_after(None));
+
+        s.append(3, 10);
+        s.append(4, 20);
+        s.append(5, 30);
+        assert_eq!(60, s.bytes());
+        assert_eq!(60, s.bytes_after(None));
+        assert_eq!(60, s.bytes_after(Some(2)));
+        assert_eq!(50, s.bytes_after(Some(3)));
+        assert_eq!(0, s.bytes_after(Some(5)));
+        assert_eq!(0, s.bytes_after(Some(9)));
+
+        s.purge_upto(Some(3));
+        assert_eq!(50, s.bytes());
+        assert_eq!(50, s.bytes_after(Some(1)));
+        assert_eq!(30, s.bytes_after(Some(4)));
+
+        s.purge_upto(Some(9));
+        assert_eq!(0, s.bytes());
+
+        s.append(10, 5);
+        assert_eq!(5, s.bytes());
+        assert_eq!(5, s.bytes_after(Some(5)));
+        assert_eq!(0, s.bytes_after(Some(10)));
+        Ok(())
+    }
+}
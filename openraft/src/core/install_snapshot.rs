@@ -45,8 +45,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         self.set_next_election_time(false);
 
         // Clear the state to None if it is building a snapshot locally.
-        if let SnapshotState::Snapshotting { abort_handle, .. } = &mut self.snapshot_state {
+        if let SnapshotState::Snapshotting {
+            abort_handle, waiters, ..
+        } = &mut self.snapshot_state
+        {
             abort_handle.abort(); // Abort the current compaction in favor of installation from leader.
+            for tx in waiters.drain(..) {
+                let _ = tx.send(Ok(None));
+            }
             self.snapshot_state = SnapshotState::None;
         }
 
@@ -74,6 +80,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         if let SnapshotState::Streaming(streaming) = &mut self.snapshot_state {
             debug_assert_eq!(req_meta.snapshot_id, streaming.snapshot_id);
             streaming.receive(req).await?;
+            self.engine.metrics_flags.set_data_changed();
         } else {
             unreachable!("It has to be Streaming")
         }
@@ -4,6 +4,7 @@
 //! Also it receives and execute `Command` emitted by `Engine` to apply raft state to underlying storage or forward
 //! messages to other raft nodes.
 
+mod applied_log_sizes;
 mod install_snapshot;
 mod raft_core;
 mod replication_expectation;
@@ -13,6 +14,8 @@ mod snapshot_state;
 mod streaming_state;
 mod tick;
 
+pub(crate) use applied_log_sizes::AppliedLogSizes;
+pub(crate) use raft_core::EventSenders;
 pub use raft_core::RaftCore;
 pub(crate) use replication_expectation::Expectation;
 pub(crate) use replication_state::replication_lag;
@@ -1,10 +1,16 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::fmt::Display;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::mem::swap;
 use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::SystemTime;
 
 use futures::future::select;
 use futures::future::AbortHandle;
@@ -15,11 +21,14 @@ use futures::StreamExt;
 use futures::TryFutureExt;
 use maplit::btreeset;
 use pin_utils::pin_mut;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
+use tokio::time::sleep;
 use tokio::time::timeout;
 use tokio::time::Duration;
 use tokio::time::Instant;
@@ -32,6 +41,7 @@ use crate::config::Config;
 use crate::config::RuntimeConfig;
 use crate::config::SnapshotPolicy;
 use crate::core::replication_lag;
+use crate::core::AppliedLogSizes;
 use crate::core::Expectation;
 use crate::core::ServerState;
 use crate::core::SnapshotResult;
@@ -42,10 +52,13 @@ use crate::engine::Engine;
 use crate::engine::EngineConfig;
 use crate::entry::EntryRef;
 use crate::error::AddLearnerError;
+use crate::error::AlreadyLeader;
 use crate::error::ChangeMembershipError;
 use crate::error::CheckIsLeaderError;
 use crate::error::ClientWriteError;
+use crate::error::ElectNowError;
 use crate::error::EmptyMembership;
+use crate::error::EntryTooLarge;
 use crate::error::ExtractFatal;
 use crate::error::Fatal;
 use crate::error::ForwardToLeader;
@@ -54,25 +67,38 @@ use crate::error::InitializeError;
 use crate::error::LearnerIsLagging;
 use crate::error::LearnerNotFound;
 use crate::error::NetworkError;
+use crate::error::NoCaughtUpFollower;
+use crate::error::NotAVoter;
 use crate::error::QuorumNotEnough;
+use crate::error::QuorumNotLive;
 use crate::error::RPCError;
 use crate::error::Timeout;
+use crate::error::TransferRejected;
 use crate::error::VoteError;
 use crate::metrics::RaftMetrics;
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::UpdateLeaderLastLog;
 use crate::metrics::UpdateMatchedLogId;
 use crate::progress::entry::ProgressEntry;
 use crate::progress::Progress;
+use crate::quorum::is_quorum;
 use crate::quorum::QuorumSet;
 use crate::raft::AddLearnerResponse;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::ApplyHook;
 use crate::raft::ClientWriteResponse;
 use crate::raft::ClientWriteTx;
 use crate::raft::ExternalCommand;
+use crate::raft::LearnerState;
 use crate::raft::RaftAddLearnerTx;
 use crate::raft::RaftMsg;
 use crate::raft::RaftRespTx;
+use crate::raft::ReplicationState;
+use crate::raft::StepDownTx;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
+use crate::raft::TriggerSnapshotTx;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_types::LogIdOptionExt;
@@ -81,6 +107,7 @@ use crate::replication::Replicate;
 use crate::replication::ReplicationCore;
 use crate::replication::ReplicationStream;
 use crate::runtime::RaftRuntime;
+use crate::storage::LogStats;
 use crate::storage::RaftSnapshotBuilder;
 use crate::storage::Snapshot;
 use crate::storage::StorageHelper;
@@ -89,6 +116,8 @@ use crate::versioned::Versioned;
 use crate::ChangeMembers;
 use crate::Entry;
 use crate::EntryPayload;
+use crate::LeaderChanged;
+use crate::LeaderEvent;
 use crate::LogId;
 use crate::Membership;
 use crate::MessageSummary;
@@ -101,6 +130,7 @@ use crate::SnapshotId;
 use crate::StorageError;
 use crate::Update;
 use crate::Vote;
+use crate::VoteEvent;
 
 /// Data for a Leader.
 ///
@@ -119,6 +149,12 @@ pub(crate) struct LeaderData<C: RaftTypeConfig> {
 
     /// The time to send next heartbeat.
     pub(crate) next_heartbeat: Instant,
+
+    /// The number of consecutive snapshot requests from each replication target that are not served with a snapshot.
+    pub(crate) snapshot_retries: BTreeMap<C::NodeId, u64>,
+
+    /// The leadership transfer in progress, started by `Raft::step_down()`.
+    pub(crate) transfer: Option<LeaderTransfer<C>>,
 }
 
 impl<C: RaftTypeConfig> LeaderData<C> {
@@ -128,10 +164,29 @@ impl<C: RaftTypeConfig> LeaderData<C> {
             nodes: BTreeMap::new(),
             replication_metrics: Versioned::new(ReplicationMetrics::default()),
             next_heartbeat: Instant::now(),
+            snapshot_retries: BTreeMap::new(),
+            transfer: None,
         }
     }
 }
 
+/// A leadership transfer to `target`.
+///
+/// The leader does not write any log until the transfer finishes, so that the target is able to catch up with
+/// `last_log_id` and win the election.
+pub(crate) struct LeaderTransfer<C: RaftTypeConfig> {
+    pub(crate) target: C::NodeId,
+
+    /// The leader's last log id when the transfer started.
+    pub(crate) last_log_id: Option<LogId<C::NodeId>>,
+
+    /// The transfer is given up at this time, and the leader accepts writes again.
+    pub(crate) deadline: Instant,
+
+    /// The channel to send the result back to the caller. It is taken when the timeout-now request is sent.
+    pub(crate) tx: Option<StepDownTx<C::NodeId, C::Node>>,
+}
+
 /// The core type implementing the Raft protocol.
 pub struct RaftCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
     /// This node's ID.
@@ -158,19 +213,84 @@ pub struct RaftCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<
     /// Received snapshot that are ready to install.
     pub(crate) received_snapshot: BTreeMap<SnapshotId, Box<S::SnapshotData>>,
 
+    /// The number of snapshots sent to followers, shared with every replication stream.
+    pub(crate) snapshots_sent: Arc<AtomicU64>,
+
+    /// The number of snapshots received from a leader.
+    pub(crate) snapshots_received: u64,
+
+    /// The number of times a replication target reported a term more than 1 greater than the term of this node.
+    pub(crate) term_jumps: u64,
+
+    /// The serialized sizes of the applied logs not in a snapshot, shared with every replication stream.
+    ///
+    /// It is used by [`SnapshotPolicy::SizeSinceLast`].
+    pub(crate) applied_log_sizes: Arc<Mutex<AppliedLogSizes>>,
+
+    /// The log stats last polled from storage, reported in `RaftMetrics::log_stats`.
+    pub(crate) log_stats: Option<LogStats>,
+
     /// The time to elect if a follower does not receive any append-entry message.
     pub(crate) next_election_time: VoteWiseTime<C::NodeId>,
 
+    /// The vote asked for in the pre-vote round in progress, and the nodes that granted it.
+    pub(crate) pre_vote: Option<(Vote<C::NodeId>, BTreeSet<C::NodeId>)>,
+
+    /// The highest election priority seen in vote requests of a term, including the priority of this node.
+    ///
+    /// It is reset when this node enters a greater term, so that a high priority node that has left does not delay
+    /// elections forever.
+    pub(crate) max_seen_priority: (u64, u32),
+
+    /// The RNG to sample election timeouts, if `Config::election_timeout_seed` is set.
+    pub(crate) election_rng: Option<StdRng>,
+
     pub(crate) tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
     pub(crate) rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, S>>,
 
     tx_metrics: watch::Sender<RaftMetrics<C::NodeId, C::Node>>,
 
+    /// When metrics are reported last time, for coalescing reports, see `Config::metrics_report_min_interval`.
+    last_metrics_report: Option<Instant>,
+
+    /// Notifies the committed log id when it advances.
+    tx_committed: watch::Sender<Option<LogId<C::NodeId>>>,
+
+    /// Notifies every received vote request and the decision on it.
+    tx_vote_events: broadcast::Sender<VoteEvent<C::NodeId>>,
+
+    /// Notifies the believed leader changes caused by AppendEntries requests.
+    tx_leader_events: broadcast::Sender<LeaderEvent<C::NodeId>>,
+
+    /// Notifies every believed leader change.
+    tx_leader_changes: broadcast::Sender<LeaderChanged<C::NodeId>>,
+
+    /// The vote saved by the last `Command::SaveVote`.
+    ///
+    /// The believed leader is updated with it when running commands, because the vote in the engine state may have
+    /// been changed again by a later command.
+    saved_vote: Vote<C::NodeId>,
+
+    /// The last leader change sent to `tx_leader_changes`.
+    last_leader_changed: LeaderChanged<C::NodeId>,
+
+    /// Called with every applied log entry and its result, see `Raft::with_apply_hook()`.
+    apply_hook: Option<ApplyHook<C>>,
+
     pub(crate) span: Span,
 }
 
 pub(crate) type RaftSpawnHandle<NID> = JoinHandle<Result<(), Fatal<NID>>>;
 
+/// The channels through which `RaftCore` reports metrics and events to `Raft`.
+pub(crate) struct EventSenders<C: RaftTypeConfig> {
+    pub(crate) tx_metrics: watch::Sender<RaftMetrics<C::NodeId, C::Node>>,
+    pub(crate) tx_committed: watch::Sender<Option<LogId<C::NodeId>>>,
+    pub(crate) tx_vote_events: broadcast::Sender<VoteEvent<C::NodeId>>,
+    pub(crate) tx_leader_events: broadcast::Sender<LeaderEvent<C::NodeId>>,
+    pub(crate) tx_leader_changes: broadcast::Sender<LeaderChanged<C::NodeId>>,
+}
+
 impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C, N, S> {
     pub(crate) fn spawn(
         id: C::NodeId,
@@ -180,9 +300,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         storage: S,
         tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
         rx_api: mpsc::UnboundedReceiver<RaftMsg<C, N, S>>,
-        tx_metrics: watch::Sender<RaftMetrics<C::NodeId, C::Node>>,
+        senders: EventSenders<C>,
+        apply_hook: Option<ApplyHook<C>>,
         rx_shutdown: oneshot::Receiver<()>,
     ) -> RaftSpawnHandle<C::NodeId> {
+        let EventSenders {
+            tx_metrics,
+            tx_committed,
+            tx_vote_events,
+            tx_leader_events,
+            tx_leader_changes,
+        } = senders;
+
         let span = tracing::span!(
             parent: tracing::Span::current(),
             Level::DEBUG,
@@ -191,6 +320,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             cluster = display(&config.cluster_name)
         );
 
+        let max_seen_priority = (0, config.election_priority);
+        // Mix in the node id, so that nodes sharing a seed still sample different timeouts.
+        let election_rng = config.election_timeout_seed.map(|seed| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            id.hash(&mut hasher);
+            StdRng::seed_from_u64(hasher.finish())
+        });
+
         let this = Self {
             id,
             config,
@@ -203,12 +341,28 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
             snapshot_state: SnapshotState::None,
             received_snapshot: BTreeMap::new(),
+            snapshots_sent: Arc::new(AtomicU64::new(0)),
+            snapshots_received: 0,
+            term_jumps: 0,
+            applied_log_sizes: Arc::new(Mutex::new(AppliedLogSizes::default())),
+            log_stats: None,
             next_election_time: VoteWiseTime::new(Vote::default(), Instant::now() + Duration::from_secs(86400)),
+            pre_vote: None,
+            max_seen_priority,
+            election_rng,
 
             tx_api,
             rx_api,
 
             tx_metrics,
+            last_metrics_report: None,
+            tx_committed,
+            tx_vote_events,
+            tx_leader_events,
+            tx_leader_changes,
+            saved_vote: Vote::default(),
+            last_leader_changed: LeaderChanged { term: 0, leader: None },
+            apply_hook,
 
             span,
         };
@@ -261,8 +415,25 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             self.engine.metrics_flags.set_data_changed();
         }
 
+        // The applied logs not in the snapshot are counted again after a restart.
+        let since = self.engine.snapshot_meta.last_log_id.next_index();
+        let end = state.committed.next_index();
+        if let SnapshotPolicy::SizeSinceLast(_) = self.config.snapshot_policy {
+            if since < end {
+                let entries = self.storage.get_log_entries(since..end).await?;
+                self.count_applied_log_bytes(&entries);
+            }
+        }
+
         self.engine.state.server_state = self.engine.calc_server_state();
 
+        // The leader loaded from storage is not a change.
+        self.saved_vote = state.vote;
+        self.last_leader_changed = LeaderChanged {
+            term: state.vote.term,
+            leader: self.current_leader(),
+        };
+
         // To ensure that restarted nodes don't disrupt a stable cluster.
         self.set_next_election_time(false);
 
@@ -383,6 +554,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
             // If we receive a response with a greater term, then revert to follower and abort this request.
             if let AppendEntriesResponse::HigherVote(vote) = data {
+                self.check_term_jump(target, &vote);
                 let res = self.engine.handle_vote_change(&vote);
                 if let Err(e) = self.run_engine_commands::<Entry<C>>(&[]).await.extract_fatal() {
                     let _ = tx.send(Err(e.into()));
@@ -499,6 +671,38 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         Ok(())
     }
 
+    /// Remove a learner from the cluster by writing a Membership log entry without it.
+    ///
+    /// The replication to the removed learner is dropped when the new membership takes effect.
+    #[tracing::instrument(level = "debug", skip(self, tx))]
+    pub(super) async fn remove_learner(
+        &mut self,
+        target: C::NodeId,
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>>,
+    ) -> Result<(), Fatal<C::NodeId>> {
+        let res = self.check_membership_committed();
+        if let Err(e) = res {
+            let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(e)));
+            return Ok(());
+        }
+
+        let curr = &self.engine.state.membership_state.effective;
+        if !curr.contains(&target) || curr.is_voter(&target) {
+            let not_found = LearnerNotFound { node_id: target };
+            let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(
+                ChangeMembershipError::LearnerNotFound(not_found),
+            )));
+            return Ok(());
+        }
+
+        let new_membership = curr.membership.remove_learner(&target);
+
+        tracing::debug!(?new_membership, "new_membership with removed learner: {}", target);
+
+        self.write_entry(EntryPayload::Membership(new_membership), Some(tx)).await?;
+        Ok(())
+    }
+
     /// Submit change-membership by writing a Membership log entry, if the `expect` is satisfied.
     ///
     /// If `turn_to_learner` is `true`, removed `voter` will becomes `learner`. Otherwise they will be just removed.
@@ -507,6 +711,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         &mut self,
         changes: ChangeMembers<C::NodeId>,
         expectation: Option<Expectation>,
+        check_quorum_live: bool,
         turn_to_learner: bool,
         tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>>,
     ) -> Result<(), Fatal<C::NodeId>> {
@@ -552,10 +757,60 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             return Ok(());
         }
 
+        if check_quorum_live {
+            if let Err(e) = self.check_quorum_live(&members) {
+                let _ = tx.send(Err(ClientWriteError::ChangeMembershipError(e)));
+                return Ok(());
+            }
+        }
+
         self.write_entry(EntryPayload::Membership(new_config), Some(tx)).await?;
         Ok(())
     }
 
+    /// Check if the live nodes among `members` form a quorum of it.
+    ///
+    /// The leader itself is live. Another node is live if it has responded successfully to a replication RPC,
+    /// including a heartbeat, within `Config::election_timeout_max`: a node that stays silent longer than that would
+    /// have considered the leader lost.
+    fn check_quorum_live(&self, members: &BTreeSet<C::NodeId>) -> Result<(), ChangeMembershipError<C::NodeId>> {
+        let l = match &self.leader_data {
+            Some(x) => x,
+            None => unreachable!("it has to be a leader!!!"),
+        };
+
+        let replication = &l.replication_metrics.data().replication;
+        let live_within = Duration::from_millis(self.config.election_timeout_max);
+        let now = SystemTime::now();
+
+        let live = members
+            .iter()
+            .filter(|id| {
+                if **id == self.id {
+                    return true;
+                }
+
+                let rpc_ok_at = match replication.get(*id).and_then(|x| x.last_rpc_ok_at()) {
+                    Some(x) => x,
+                    None => return false,
+                };
+
+                // A time later than now is possible if the clock goes backward.
+                now.duration_since(rpc_ok_at).map(|d| d <= live_within).unwrap_or(true)
+            })
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        if is_quorum(live.len(), members.len()) {
+            return Ok(());
+        }
+
+        Err(ChangeMembershipError::QuorumNotLive(QuorumNotLive {
+            members: members.clone(),
+            live,
+        }))
+    }
+
     /// Check if the effective membership is committed, so that a new membership is allowed to be proposed.
     fn check_membership_committed(&self) -> Result<(), ChangeMembershipError<C::NodeId>> {
         let st = &self.engine.state;
@@ -648,14 +903,121 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         Ok(*entry_refs[0].get_log_id())
     }
 
+    /// Remove the response channel of a client write whose waiter has timed out.
+    ///
+    /// The channel is removed only if its receiver is dropped: the index may have been reused by another write after
+    /// a leader change.
+    pub(crate) fn remove_timed_out_client_write(&mut self, log_id: LogId<C::NodeId>) {
+        if let Some(l) = &mut self.leader_data {
+            let closed = l.client_resp_channels.get(&log_id.index).map(|tx| tx.is_closed());
+            if closed == Some(true) {
+                tracing::debug!(log_id = display(&log_id), "remove timed out client write");
+                l.client_resp_channels.remove(&log_id.index);
+            }
+        }
+    }
+
+    /// Collect the matched log id and the next index to send of every replication stream.
+    pub(crate) fn get_replication_states(&self) -> BTreeMap<C::NodeId, ReplicationState<C::NodeId>> {
+        let mut res = BTreeMap::new();
+
+        let (l, leading) = match (&self.leader_data, self.engine.state.internal_server_state.leading()) {
+            (Some(l), Some(leading)) => (l, leading),
+            _ => return res,
+        };
+
+        for (target, stream) in l.nodes.iter() {
+            res.insert(*target, ReplicationState {
+                matched: leading.progress.get(target).matching,
+                next_index: stream.next_index.load(Ordering::Relaxed),
+                applied: stream.applied_next_index.load(Ordering::Relaxed).checked_sub(1),
+            });
+        }
+
+        res
+    }
+
+    /// Get the state of every learner in the effective membership, if this node is a leader.
+    pub(crate) fn get_learner_states(&self) -> Vec<(C::NodeId, LearnerState<C::NodeId>)> {
+        let mut states = self.get_replication_states();
+        let last_log_id = self.engine.state.last_log_id();
+
+        let mut res = vec![];
+        for learner_id in self.engine.state.membership_state.effective.learner_ids() {
+            let replication = match states.remove(&learner_id) {
+                None => continue,
+                Some(x) => x,
+            };
+
+            let distance = replication_lag(&replication.matched.index(), &last_log_id.index());
+
+            res.push((learner_id, LearnerState {
+                replication,
+                ready_to_join: distance <= self.config.replication_lag_threshold,
+            }));
+        }
+
+        res
+    }
+
+    /// Check the serialized size of a client write entry against `Config::max_entry_bytes`.
+    pub(crate) fn check_entry_size(&self, payload: &EntryPayload<C>) -> Result<(), EntryTooLarge> {
+        let size = match self.storage.serialized_size(payload) {
+            None => return Ok(()),
+            Some(x) => x,
+        };
+
+        let max = self.config.max_entry_bytes;
+        if size > max {
+            tracing::info!(size, max, "reject client write: entry too large");
+            return Err(EntryTooLarge { size, max });
+        }
+
+        Ok(())
+    }
+
+    /// Add the serialized sizes of applied logs to the bytes counted by [`SnapshotPolicy::SizeSinceLast`].
+    ///
+    /// A log whose size is unknown to the storage is counted as 0 bytes.
+    fn count_applied_log_bytes(&self, entries: &[Entry<C>]) {
+        if !matches!(self.config.snapshot_policy, SnapshotPolicy::SizeSinceLast(_)) {
+            return;
+        }
+
+        let mut sizes = self.applied_log_sizes.lock().unwrap();
+
+        for ent in entries {
+            let size = self.storage.serialized_size(&ent.payload).unwrap_or_default();
+            sizes.append(ent.log_id.index, size);
+        }
+    }
+
     /// Flush cached changes of metrics to notify metrics watchers with updated metrics.
     /// Then clear flags about the cached changes, to avoid unnecessary metrics report.
+    ///
+    /// Changes other than cluster changes are kept in the flags and reported later,
+    /// if the last report is within `Config::metrics_report_min_interval`.
     #[tracing::instrument(level = "debug", skip_all)]
     pub fn flush_metrics(&mut self) {
         if !self.engine.metrics_flags.changed() {
             return;
         }
 
+        // Server state, leader and membership changes must not be delayed.
+        if !self.engine.metrics_flags.cluster {
+            if let Some(last) = self.last_metrics_report {
+                let min_interval = Duration::from_millis(self.config.metrics_report_min_interval);
+                if Instant::now() < last + min_interval {
+                    tracing::debug!("metrics report is coalesced");
+                    return;
+                }
+            }
+        }
+
+        // Updating the leader's last log id replaces the replication metrics with a clone.
+        // Do it only when a report is about to be sent, so that it is coalesced with the report.
+        self.update_leader_last_log();
+
         let leader_metrics = if self.engine.metrics_flags.replication {
             let replication_metrics = self.leader_data.as_ref().map(|x| x.replication_metrics.clone());
             Update::Update(replication_metrics)
@@ -669,16 +1031,82 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         };
 
         self.report_metrics(leader_metrics);
+        self.last_metrics_report = Some(Instant::now());
         self.engine.metrics_flags.reset();
     }
 
+    /// Update the believed leader with the last saved vote and the server state, and send a `LeaderChanged` event if
+    /// it changed since the last one sent.
+    ///
+    /// It is called by the commands that save the vote or update the server state. Thus every change is sent, even if
+    /// several of them happen when handling one message, e.g., an election that is won at once.
+    ///
+    /// The same leader in a greater term is also a change, e.g., a leader re-elected before a follower sees an
+    /// election.
+    fn update_current_leader(&mut self) {
+        let vote = self.saved_vote;
+
+        let leader = if !vote.committed {
+            None
+        } else if vote.node_id != self.id {
+            Some(vote.node_id)
+        } else if self.leader_data.is_some() {
+            Some(self.id)
+        } else {
+            None
+        };
+        let term = vote.term;
+
+        let last = &self.last_leader_changed;
+        if leader == last.leader && (leader.is_none() || term == last.term) {
+            return;
+        }
+
+        tracing::info!(
+            prev_leader = debug(last.leader),
+            leader = debug(leader),
+            term,
+            "believed leader changed"
+        );
+
+        self.last_leader_changed = LeaderChanged { term, leader };
+        let _ = self.tx_leader_changes.send(self.last_leader_changed.clone());
+    }
+
+    /// Poll the log stats from storage and mark the local data changed if they are different from the last poll.
+    ///
+    /// The stats are only informational: a failed poll is logged and the last polled stats are kept.
+    async fn update_log_stats(&mut self) {
+        let stats = match self.storage.log_stats().await {
+            Ok(x) => x,
+            Err(err) => {
+                tracing::warn!(error = display(&err), "failed to poll log stats");
+                return;
+            }
+        };
+
+        if self.log_stats.as_ref() != Some(&stats) {
+            self.log_stats = Some(stats);
+            self.engine.metrics_flags.set_data_changed();
+        }
+    }
+
+    /// Update the leader's last log id in replication metrics, if it changed since last update.
+    fn update_leader_last_log(&mut self) {
+        let last_log_id = self.engine.state.last_log_id();
+
+        if let Some(l) = &mut self.leader_data {
+            if l.replication_metrics.data().leader_last_log != last_log_id {
+                l.replication_metrics.update(UpdateLeaderLastLog { last_log_id });
+                self.engine.metrics_flags.set_replication_changed();
+            }
+        }
+    }
+
     /// Report a metrics payload on the current state of the Raft node.
     #[tracing::instrument(level = "debug", skip(self))]
     pub(crate) fn report_metrics(&self, replication: Update<Option<Versioned<ReplicationMetrics<C::NodeId>>>>) {
-        let replication = match replication {
-            Update::Update(v) => v,
-            Update::AsIs => self.tx_metrics.borrow().replication.clone(),
-        };
+        let replication = replication.unwrap_or(self.tx_metrics.borrow().replication.clone());
 
         let m = RaftMetrics {
             running_state: Ok(()),
@@ -689,11 +1117,22 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             last_log_index: self.engine.state.last_log_id().map(|id| id.index),
             last_applied: self.engine.state.committed,
             snapshot: self.engine.snapshot_meta.last_log_id,
+            snapshot_meta: self.engine.snapshot_meta.last_log_id.map(|_| self.engine.snapshot_meta.clone()),
+            snapshots_sent: self.snapshots_sent.load(Ordering::Relaxed),
+            snapshots_received: self.snapshots_received,
+            term_jumps: self.term_jumps,
+            snapshot_received_bytes: match &self.snapshot_state {
+                SnapshotState::Streaming(streaming) => Some(streaming.offset),
+                _ => None,
+            },
+            log_stats: self.log_stats.clone(),
+            commit_blocked_by_term: self.engine.commit_blocked_by_term,
 
             // --- cluster ---
             state: self.engine.state.server_state,
             current_leader: self.current_leader(),
             membership_config: self.engine.state.membership_state.effective.clone(),
+            in_joint_consensus: self.engine.state.membership_state.effective.membership.is_in_joint_consensus(),
 
             // --- replication ---
             replication,
@@ -753,14 +1192,30 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     pub(crate) fn set_next_election_time(&mut self, can_be_leader: bool) {
         let now = Instant::now();
 
-        let mut t = Duration::from_millis(self.config.new_rand_election_timeout());
+        let timeout = match &mut self.election_rng {
+            Some(rng) => self.config.new_rand_election_timeout_with(rng),
+            None => self.config.new_rand_election_timeout(),
+        };
+
+        let mut t = Duration::from_millis(timeout);
         if !can_be_leader {
             t *= 2;
         }
+
+        // Let a node with higher priority start its election first.
+        if self.engine.state.vote.term > self.max_seen_priority.0 {
+            self.max_seen_priority = (self.engine.state.vote.term, self.config.election_priority);
+        }
+        let priority_gap = self.max_seen_priority.1.saturating_sub(self.config.election_priority) as u64;
+        let delay = priority_gap.saturating_mul(self.config.heartbeat_interval);
+        let delay = delay.min(self.config.election_timeout_max);
+        t += Duration::from_millis(delay);
+
         tracing::debug!(
-            "update election timeout after: {:?}, can_be_leader: {}",
+            "update election timeout after: {:?}, can_be_leader: {}, priority delay: {}ms",
             t,
-            can_be_leader
+            can_be_leader,
+            delay
         );
 
         // TODO: election timer should be bound to `(vote, membership_log_id)`:
@@ -781,16 +1236,47 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             return Ok(());
         }
 
+        let waiters = match &mut self.snapshot_state {
+            SnapshotState::Snapshotting { waiters, .. } => std::mem::take(waiters),
+            _ => vec![],
+        };
+
         // TODO: add building-session id to identify different building
         match result {
             SnapshotResult::Ok(meta) => {
-                self.engine.finish_building_snapshot(meta);
+                // Logs applied during building are not in the snapshot and are still counted.
+                self.applied_log_sizes.lock().unwrap().purge_upto(meta.last_log_id.index());
+
+                self.engine.finish_building_snapshot(meta.clone());
                 self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+                for tx in waiters {
+                    let _ = tx.send(Ok(Some(meta.clone())));
+                }
+            }
+            SnapshotResult::StorageError(sto_err) if Self::is_transient(&sto_err) => {
+                // A failed compaction does not change the state, the next one is triggered by the snapshot policy or
+                // by a replication stream that needs a snapshot.
+                tracing::warn!(
+                    error = display(&sto_err),
+                    "building snapshot failed with a transient error"
+                );
+
+                for tx in waiters {
+                    let _ = tx.send(Ok(None));
+                }
             }
             SnapshotResult::StorageError(sto_err) => {
+                for tx in waiters {
+                    let _ = tx.send(Err(Fatal::StorageError(sto_err.clone())));
+                }
                 return Err(sto_err);
             }
-            SnapshotResult::Aborted => {}
+            SnapshotResult::Aborted => {
+                for tx in waiters {
+                    let _ = tx.send(Ok(None));
+                }
+            }
         }
 
         self.snapshot_state = SnapshotState::None;
@@ -798,6 +1284,20 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         Ok(())
     }
 
+    /// Build a snapshot for `Raft::trigger_snapshot()` and respond to `tx` when it is done.
+    ///
+    /// If a snapshot is already being built, `tx` waits for it instead of starting another one.
+    pub(crate) async fn handle_trigger_snapshot(&mut self, tx: TriggerSnapshotTx<C::NodeId, C::Node>) {
+        self.trigger_snapshot_if_needed(true).await;
+
+        if let SnapshotState::Snapshotting { waiters, .. } = &mut self.snapshot_state {
+            waiters.push(tx);
+        } else {
+            // It is streaming a snapshot from the leader, no snapshot is built.
+            let _ = tx.send(Ok(None));
+        }
+    }
+
     /// Trigger a log compaction (snapshot) job if needed.
     /// If force is True, it will skip the threshold check and start creating snapshot as demanded.
     #[tracing::instrument(level = "debug", skip(self))]
@@ -811,13 +1311,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             return;
         }
 
-        let SnapshotPolicy::LogsSinceLast(threshold) = &self.config.snapshot_policy;
-
         if !force {
             // If we are below the threshold, then there is nothing to do.
-            if self.engine.state.committed.next_index() - self.engine.snapshot_meta.last_log_id.next_index()
-                < *threshold
-            {
+            let below_threshold = match &self.config.snapshot_policy {
+                SnapshotPolicy::LogsSinceLast(threshold) => {
+                    self.engine.state.committed.next_index() - self.engine.snapshot_meta.last_log_id.next_index()
+                        < *threshold
+                }
+                SnapshotPolicy::SizeSinceLast(threshold) => self.applied_log_sizes.lock().unwrap().bytes() < *threshold,
+                SnapshotPolicy::Never => true,
+            };
+
+            if below_threshold {
                 return;
             }
         }
@@ -831,6 +1336,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         self.snapshot_state = SnapshotState::Snapshotting {
             abort_handle,
             sender: chan_tx.clone(),
+            waiters: vec![],
         };
 
         tokio::spawn(
@@ -909,6 +1415,67 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         self.engine.state.membership_state.effective.get_node(&leader_id).cloned()
     }
 
+    /// Notify the subscribers of `Raft::committed()` that logs upto `upto` are committed.
+    ///
+    /// It is called before applying the committed logs.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn notify_committed(&self, upto: LogId<C::NodeId>) {
+        let _ = self.tx_committed.send(Some(upto));
+    }
+
+    /// Append logs to storage, retrying if it fails with a transient error.
+    async fn append_to_storage_with_retry(&mut self, entries: &[&Entry<C>]) -> Result<(), StorageError<C::NodeId>> {
+        let mut attempt = 0;
+        loop {
+            let err = match self.storage.append_to_log(entries).await {
+                Ok(x) => return Ok(x),
+                Err(e) => e,
+            };
+
+            let backoff = self.storage_retry_backoff(&err, attempt).ok_or(err)?;
+            sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// Apply logs to the state machine, retrying if it fails with a transient error.
+    async fn apply_to_storage_with_retry(
+        &mut self,
+        entries: &[&Entry<C>],
+    ) -> Result<Vec<C::R>, StorageError<C::NodeId>> {
+        let mut attempt = 0;
+        loop {
+            let err = match self.storage.apply_to_state_machine(entries).await {
+                Ok(x) => return Ok(x),
+                Err(e) => e,
+            };
+
+            let backoff = self.storage_retry_backoff(&err, attempt).ok_or(err)?;
+            sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    /// Returns how long to wait before retrying a storage call that failed with `err` for the `attempt`-th time,
+    /// counting from 0, or `None` if it should not be retried.
+    fn storage_retry_backoff(&self, err: &StorageError<C::NodeId>, attempt: u64) -> Option<Duration> {
+        if !Self::is_transient(err) || attempt >= self.config.storage_retry_count {
+            return None;
+        }
+
+        tracing::warn!(
+            error = display(err),
+            attempt,
+            "retry storage call after a transient error"
+        );
+
+        Some(Duration::from_millis(10 << attempt.min(16)))
+    }
+
+    fn is_transient(err: &StorageError<C::NodeId>) -> bool {
+        matches!(err, StorageError::IO { source } if source.is_transient())
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) async fn apply_to_state_machine(
         &mut self,
@@ -930,15 +1497,41 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             return Ok(());
         }
 
+        let batch_size = match self.config.max_apply_entries {
+            0 => end - since,
+            n => n,
+        };
+
+        let mut batch_start = since;
+        while batch_start < end {
+            let batch_end = std::cmp::min(batch_start + batch_size, end);
+            self.apply_batch_to_state_machine(batch_start, batch_end).await?;
+            batch_start = batch_end;
+        }
+
+        self.trigger_snapshot_if_needed(false).await;
+        Ok(())
+    }
+
+    /// Apply logs in range `[since, end)` to the state machine in one call, and send the results to the clients.
+    async fn apply_batch_to_state_machine(&mut self, since: u64, end: u64) -> Result<(), StorageError<C::NodeId>> {
         let entries = self.storage.get_log_entries(since..end).await?;
         tracing::debug!(entries=%entries.as_slice().summary(), "about to apply");
 
         let entry_refs = entries.iter().collect::<Vec<_>>();
-        let apply_results = self.storage.apply_to_state_machine(&entry_refs).await?;
+        let apply_results = self.apply_to_storage_with_retry(&entry_refs).await?;
 
         let last_applied = entries[entries.len() - 1].log_id;
         tracing::debug!(last_applied = display(last_applied), "update last_applied");
 
+        self.count_applied_log_bytes(&entries);
+
+        if let Some(hook) = &mut self.apply_hook {
+            for (entry, res) in entries.iter().zip(apply_results.iter()) {
+                hook(entry, res);
+            }
+        }
+
         if let Some(l) = &mut self.leader_data {
             let mut results = apply_results.into_iter();
 
@@ -953,10 +1546,53 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             }
         }
 
-        self.trigger_snapshot_if_needed(false).await;
         Ok(())
     }
 
+    /// Drop the snapshot being received if no chunk is received within `Config::snapshot_receive_timeout`.
+    fn check_snapshot_receive_timeout(&mut self, now: Instant) {
+        if self.config.snapshot_receive_timeout == 0 {
+            return;
+        }
+
+        let timeout = Duration::from_millis(self.config.snapshot_receive_timeout);
+
+        if let SnapshotState::Streaming(streaming) = &self.snapshot_state {
+            if now < streaming.last_received + timeout {
+                return;
+            }
+
+            tracing::warn!(
+                snapshot_id = display(&streaming.snapshot_id),
+                received_bytes = streaming.offset,
+                "no snapshot chunk received in {:?}, drop the partially received snapshot",
+                timeout
+            );
+
+            self.snapshot_state = SnapshotState::None;
+            self.engine.metrics_flags.set_data_changed();
+        }
+    }
+
+    /// Warn if the `higher` vote reported by `target` is more than 1 term ahead of this node.
+    ///
+    /// A term that jumps by more than 1 usually indicates a split-brain or a misbehaving node.
+    fn check_term_jump(&mut self, target: C::NodeId, higher: &Vote<C::NodeId>) {
+        let current_term = self.engine.state.vote.term;
+
+        if higher.term > current_term + 1 {
+            tracing::warn!(
+                target = display(target),
+                current_term,
+                observed_term = higher.term,
+                "replication target reports a term more than 1 ahead of this node"
+            );
+
+            self.term_jumps += 1;
+            self.engine.metrics_flags.set_data_changed();
+        }
+    }
+
     /// Send result of applying a log entry to its client.
     #[tracing::instrument(level = "debug", skip_all)]
     pub(super) fn send_response(entry: &Entry<C>, resp: C::R, tx: Option<ClientWriteTx<C, C::NodeId, C::Node>>) {
@@ -1010,6 +1646,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             network,
             self.storage.get_log_reader().await,
             self.tx_api.clone(),
+            self.snapshots_sent.clone(),
+            self.applied_log_sizes.clone(),
             tracing::span!(parent: &self.span, Level::DEBUG, "replication", id=display(self.id), target=display(target)),
         ))
     }
@@ -1106,8 +1744,11 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     }
 
     /// Spawn parallel vote requests to all cluster members.
+    ///
+    /// A pre-vote request is sent instead if `pre_vote` is true. Responses are sent back to `RaftCore` as
+    /// `RaftMsg::VoteResponse` or `RaftMsg::PreVoteResponse`.
     #[tracing::instrument(level = "trace", skip_all, fields(vote=vote_req.summary()))]
-    async fn spawn_parallel_vote_requests(&mut self, vote_req: &VoteRequest<C::NodeId>) {
+    async fn spawn_parallel_vote_requests(&mut self, vote_req: &VoteRequest<C::NodeId>, pre_vote: bool) {
         let members = self.engine.state.membership_state.effective.voter_ids();
 
         let vote = vote_req.vote;
@@ -1117,7 +1758,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 continue;
             }
 
-            let req = vote_req.clone();
+            let req = vote_req.clone().with_priority(self.config.election_priority);
 
             // Safe unwrap(): target must be in membership
             let target_node = self.engine.state.membership_state.effective.get_node(&target).unwrap().clone();
@@ -1136,13 +1777,18 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
             let _ = tokio::spawn(
                 async move {
-                    let tm_res = timeout(ttl, client.send_vote(req)).await;
+                    let (action, tm_res) = if pre_vote {
+                        (RPCTypes::PreVote, timeout(ttl, client.send_pre_vote(req)).await)
+                    } else {
+                        (RPCTypes::Vote, timeout(ttl, client.send_vote(req)).await)
+                    };
+
                     let res = match tm_res {
                         Ok(res) => res,
 
                         Err(_timeout) => {
                             let timeout_err = Timeout {
-                                action: RPCTypes::Vote,
+                                action,
                                 id,
                                 target,
                                 timeout: ttl,
@@ -1154,9 +1800,16 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
 
                     match res {
                         Ok(resp) => {
-                            let _ = tx.send(RaftMsg::VoteResponse { target, resp, vote });
+                            let msg = if pre_vote {
+                                RaftMsg::PreVoteResponse { target, resp, vote }
+                            } else {
+                                RaftMsg::VoteResponse { target, resp, vote }
+                            };
+                            let _ = tx.send(msg);
+                        }
+                        Err(err) => {
+                            tracing::error!({error=%err, target=display(target), pre_vote}, "while requesting vote")
                         }
-                        Err(err) => tracing::error!({error=%err, target=display(target)}, "while requesting vote"),
                     }
                 }
                 .instrument(tracing::debug_span!(
@@ -1175,12 +1828,342 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     ) -> Result<VoteResponse<C::NodeId>, VoteError<C::NodeId>> {
         tracing::debug!(req = display(req.summary()), "handle_vote_request");
 
-        let resp = self.engine.handle_vote_req(req);
+        let (term, priority) = self.max_seen_priority;
+        if req.vote.term > term {
+            self.max_seen_priority = (
+                req.vote.term,
+                std::cmp::max(self.config.election_priority, req.priority),
+            );
+        } else if req.vote.term == term {
+            self.max_seen_priority = (term, std::cmp::max(priority, req.priority));
+        }
+
+        let req_vote = req.vote;
+
+        let (resp, reason) = self.engine.handle_vote_req(req);
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+        let _ = self.tx_vote_events.send(VoteEvent {
+            from: req_vote.node_id,
+            term: req_vote.term,
+            granted: resp.vote_granted,
+            reason,
+        });
+
+        Ok(resp)
+    }
+
+    /// Start a pre-vote round: ask the voters if they would grant this node a vote of the next term.
+    ///
+    /// The local vote is not changed. An election is started when a quorum grants the pre-vote, see
+    /// [`Self::handle_pre_vote_resp`]. Otherwise another round is started when the election timer expires again.
+    pub(crate) async fn start_pre_vote(&mut self) -> Result<(), StorageError<C::NodeId>> {
+        let vote = Vote::new(self.engine.state.vote.term + 1, self.id);
+        let granted = btreeset! {self.id};
+
+        // Fast-path: if there is only one voter in the cluster.
+        if self.engine.state.membership_state.effective.is_quorum(granted.iter()) {
+            self.engine.elect();
+            self.run_engine_commands::<Entry<C>>(&[]).await?;
+            return Ok(());
+        }
+
+        tracing::debug!(vote = display(&vote), "start pre-vote");
+
+        self.pre_vote = Some((vote, granted));
+        self.set_next_election_time(true);
+
+        let req = VoteRequest::new(vote, self.engine.state.last_log_id());
+        self.spawn_parallel_vote_requests(&req, true).await;
+
+        Ok(())
+    }
+
+    /// Handle a pre-vote request: respond whether the vote in it would be granted, without changing any state.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) fn handle_pre_vote_request(&self, req: VoteRequest<C::NodeId>) -> VoteResponse<C::NodeId> {
+        tracing::debug!(req = display(req.summary()), "handle_pre_vote_request");
+
+        self.engine.handle_pre_vote_req(&req, self.is_leader_alive())
+    }
+
+    /// Whether this node is a leader, or it has heard from a leader before the election timer expires.
+    fn is_leader_alive(&self) -> bool {
+        if self.engine.state.server_state == ServerState::Leader {
+            return true;
+        }
+
+        let vote = &self.engine.state.vote;
+        if !vote.committed {
+            return false;
+        }
+
+        match self.next_election_time.get_time(vote) {
+            Some(t) => Instant::now() < t,
+            None => false,
+        }
+    }
+
+    /// Handle response from a pre-vote request sent to a peer.
+    ///
+    /// When a quorum grants the pre-vote, it starts a real election.
+    #[tracing::instrument(level = "debug", skip(self, resp))]
+    async fn handle_pre_vote_resp(
+        &mut self,
+        resp: VoteResponse<C::NodeId>,
+        target: C::NodeId,
+        vote: Vote<C::NodeId>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        tracing::debug!(
+            resp = debug(&resp),
+            target = display(target),
+            my_vote = display(&self.engine.state.vote),
+            "recv pre-vote response"
+        );
+
+        // The local vote changed since the pre-vote round started, e.g., it has seen a leader.
+        if self.engine.state.vote.term + 1 != vote.term {
+            self.pre_vote = None;
+            return Ok(());
+        }
+
+        let granted = match &mut self.pre_vote {
+            Some((v, granted)) if *v == vote => granted,
+            _ => {
+                tracing::debug!(vote = display(&vote), "ignore stale pre-vote response");
+                return Ok(());
+            }
+        };
+
+        if resp.vote_granted {
+            granted.insert(target);
+
+            if self.engine.state.membership_state.effective.is_quorum(granted.iter()) {
+                tracing::debug!(vote = display(&vote), "quorum granted pre-vote, start election");
+
+                self.pre_vote = None;
+                self.engine.elect();
+                self.run_engine_commands::<Entry<C>>(&[]).await?;
+            }
+            return Ok(());
+        }
+
+        // A voter has seen a vote not less than the one asked for: catch up with it, so that the next pre-vote round
+        // asks for a greater vote.
+        if resp.vote.term >= vote.term {
+            self.pre_vote = None;
+
+            // Rejected vote change is ok.
+            let _ = self.engine.handle_vote_change(&resp.vote);
+            self.run_engine_commands::<Entry<C>>(&[]).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Start an election at once, as requested by `Raft::elect_now()`.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) async fn handle_elect_now(&mut self) -> Result<(), ElectNowError<C::NodeId>> {
+        if self.engine.state.server_state == ServerState::Leader {
+            return Err(AlreadyLeader {
+                node_id: self.id,
+                vote: self.engine.state.vote,
+            }
+            .into());
+        }
+
+        if !self.engine.state.membership_state.effective.is_voter(&self.id) {
+            return Err(NotAVoter { node_id: self.id }.into());
+        }
+
+        self.engine.elect();
+        self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+        tracing::debug!("elect_now: election started");
+        Ok(())
+    }
+
+    /// Handle a timeout-now request from the leader, which is transferring its leadership to this node.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) async fn handle_timeout_now_request(
+        &mut self,
+        req: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, VoteError<C::NodeId>> {
+        tracing::debug!(req = display(req.summary()), "handle_timeout_now_request");
+
+        let resp = self.engine.handle_timeout_now(&req);
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
         Ok(resp)
     }
 
+    /// Transfer leadership to the most up-to-date voter.
+    ///
+    /// This node stops accepting writes, waits for the target to replicate its last log, then sends it a
+    /// timeout-now request. It keeps acting as leader until it receives the vote request from the target.
+    /// The result is sent back via `tx` once the target responds, or when the target does not catch up in time.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(super) async fn handle_step_down(&mut self, tx: StepDownTx<C::NodeId, C::Node>) {
+        let last_log_id = self.engine.state.last_log_id();
+
+        let target = match self.engine.leader_transfer_target() {
+            Some(x) => x,
+            None => {
+                let _ = tx.send(Err(NoCaughtUpFollower { last_log_id }.into()));
+                return;
+            }
+        };
+
+        tracing::info!(
+            target = display(target),
+            last_log_id = debug(last_log_id),
+            "transfer leadership, stop accepting writes"
+        );
+
+        if let Some(l) = &mut self.leader_data {
+            l.transfer = Some(LeaderTransfer {
+                target,
+                last_log_id,
+                deadline: Instant::now() + Duration::from_millis(self.config.election_timeout_max),
+                tx: Some(tx),
+            });
+        }
+
+        self.try_send_timeout_now().await;
+    }
+
+    /// Send a timeout-now request to the leadership transfer target, if it has caught up with the leader's last log
+    /// when the transfer started.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn try_send_timeout_now(&mut self) {
+        let transfer = match self.leader_data.as_mut().and_then(|l| l.transfer.as_mut()) {
+            Some(t) if t.tx.is_some() => t,
+            _ => return,
+        };
+
+        let matching = match self.engine.state.internal_server_state.leading() {
+            Some(l) => l.progress.get(&transfer.target).matching,
+            None => return,
+        };
+
+        if matching < transfer.last_log_id {
+            tracing::debug!(
+                target = display(transfer.target),
+                matching = debug(matching),
+                "leadership transfer target has not yet caught up"
+            );
+            return;
+        }
+
+        // Give the target another election timeout to become leader, before accepting writes again.
+        transfer.deadline = Instant::now() + Duration::from_millis(self.config.election_timeout_max);
+
+        // Safe unwrap(): checked above
+        let tx = transfer.tx.take().unwrap();
+        let target = transfer.target;
+        let last_log_id = transfer.last_log_id;
+
+        tracing::info!(
+            target = display(target),
+            "leadership transfer target caught up, send timeout-now"
+        );
+
+        // Safe unwrap(): target is a voter in the effective membership
+        let target_node = self.engine.state.membership_state.effective.get_node(&target).unwrap().clone();
+        let mut client = match self.network.new_client(target, &target_node).await {
+            Ok(n) => n,
+            Err(err) => {
+                let _ = tx.send(Err(RPCError::Network(err).into()));
+                return;
+            }
+        };
+
+        let req = TimeoutNowRequest::new(self.engine.state.vote, last_log_id);
+        let ttl = Duration::from_millis(self.config.election_timeout_min);
+        let id = self.id;
+
+        let _ = tokio::spawn(
+            async move {
+                let res = match timeout(ttl, client.send_timeout_now(req)).await {
+                    Ok(res) => res,
+                    Err(_timeout) => Err(RPCError::Timeout(Timeout {
+                        action: RPCTypes::TimeoutNow,
+                        id,
+                        target,
+                        timeout: ttl,
+                    })),
+                };
+
+                let res = match res {
+                    Ok(resp) if resp.accepted => Ok(target),
+                    Ok(resp) => Err(TransferRejected {
+                        target,
+                        vote: resp.vote,
+                    }
+                    .into()),
+                    Err(err) => {
+                        tracing::error!({error=%err, target=display(target)}, "while sending timeout-now");
+                        Err(err.into())
+                    }
+                };
+
+                let _ = tx.send(res);
+            }
+            .instrument(tracing::debug_span!(
+                parent: &Span::current(),
+                "send_timeout_now",
+                target = display(target)
+            )),
+        );
+    }
+
+    /// Whether this leader is transferring its leadership, during which it does not accept writes.
+    fn is_transferring_leadership(&self) -> bool {
+        match self.leader_data.as_ref().and_then(|l| l.transfer.as_ref()) {
+            Some(t) => Instant::now() < t.deadline,
+            None => false,
+        }
+    }
+
+    /// Give up the leadership transfer if the target did not catch up or did not become leader in time.
+    fn check_transfer_timeout(&mut self, now: Instant) {
+        let l = match &mut self.leader_data {
+            Some(l) => l,
+            None => return,
+        };
+
+        if l.transfer.as_ref().map(|t| now < t.deadline).unwrap_or(true) {
+            return;
+        }
+
+        // Safe unwrap(): checked above
+        let transfer = l.transfer.take().unwrap();
+        tracing::info!(
+            target = display(transfer.target),
+            "leadership transfer timed out, accept writes again"
+        );
+
+        if let Some(tx) = transfer.tx {
+            let _ = tx.send(Err(NoCaughtUpFollower {
+                last_log_id: transfer.last_log_id,
+            }
+            .into()));
+        }
+    }
+
+    /// Reject a write request while this leader is transferring its leadership.
+    ///
+    /// The new leader is not yet known, thus the caller is not told where to forward the request.
+    fn reject_while_transferring<T, E>(&self, tx: RaftRespTx<T, E>)
+    where E: From<ForwardToLeader<C::NodeId, C::Node>> {
+        let err = ForwardToLeader {
+            leader_id: None,
+            leader_node: None,
+        };
+
+        let _ = tx.send(Err(err.into()));
+    }
+
     /// Handle response from a vote request sent to a peer.
     #[tracing::instrument(level = "debug", skip(self, resp))]
     async fn handle_vote_resp(
@@ -1207,12 +2190,32 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         tracing::debug!("recv from rx_api: {}", msg.summary());
 
         let is_leader = || self.engine.state.server_state == ServerState::Leader;
+        let transferring = self.is_transferring_leadership();
 
         match msg {
             RaftMsg::AppendEntries { rpc, tx } => {
+                let prev_leader = self.current_leader();
+
                 let resp =
                     self.engine.handle_append_entries_req(&rpc.vote, rpc.prev_log_id, &rpc.entries, rpc.leader_commit);
                 self.run_engine_commands(rpc.entries.as_slice()).await?;
+
+                if let Some(leader) = self.current_leader() {
+                    if prev_leader != Some(leader) {
+                        tracing::info!(
+                            prev_leader = debug(prev_leader),
+                            leader = display(leader),
+                            "leader changed by AppendEntries"
+                        );
+
+                        let _ = self.tx_leader_events.send(LeaderEvent {
+                            prev_leader,
+                            leader,
+                            term: self.engine.state.vote.term,
+                        });
+                    }
+                }
+
                 let _ = tx.send(Ok(resp));
             }
             RaftMsg::RequestVote { rpc, tx } => {
@@ -1223,12 +2226,50 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.handle_vote_resp(resp, target).await?;
                 }
             }
+            RaftMsg::RequestPreVote { rpc, tx } => {
+                let _ = tx.send(Ok(self.handle_pre_vote_request(rpc)));
+            }
+            RaftMsg::PreVoteResponse { target, resp, vote } => {
+                self.handle_pre_vote_resp(resp, target, vote).await?;
+            }
+            RaftMsg::TimeoutNow { rpc, tx } => {
+                let _ = tx.send(self.handle_timeout_now_request(rpc).await.extract_fatal()?);
+            }
             RaftMsg::InstallSnapshot { rpc, tx } => {
                 let _ = tx.send(self.handle_install_snapshot_request(rpc).await.extract_fatal()?);
             }
             RaftMsg::BuildingSnapshotResult { result } => {
                 self.handle_building_snapshot_result(result).await?;
             }
+            RaftMsg::ClientWriteWithTicket { payload, tx_log_id, tx } => {
+                if !is_leader() {
+                    self.reject_with_forward_to_leader(tx_log_id);
+                } else if transferring {
+                    self.reject_while_transferring(tx_log_id);
+                } else if let Err(e) = self.check_entry_size(&payload) {
+                    let _ = tx_log_id.send(Err(e.into()));
+                } else {
+                    let log_id = self.write_entry(payload, Some(tx)).await?;
+                    let _ = tx_log_id.send(Ok(log_id));
+                }
+            }
+            RaftMsg::ClientWriteTimeout { log_id } => {
+                self.remove_timed_out_client_write(log_id);
+            }
+            RaftMsg::GetReplicationStates { tx } => {
+                if is_leader() {
+                    let _ = tx.send(Ok(self.get_replication_states()));
+                } else {
+                    self.reject_with_forward_to_leader(tx);
+                }
+            }
+            RaftMsg::ListLearners { tx } => {
+                if is_leader() {
+                    let _ = tx.send(Ok(self.get_learner_states()));
+                } else {
+                    self.reject_with_forward_to_leader(tx);
+                }
+            }
             RaftMsg::CheckIsLeaderRequest { tx } => {
                 if is_leader() {
                     self.handle_check_is_leader_request(tx).await;
@@ -1236,33 +2277,68 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     self.reject_with_forward_to_leader(tx);
                 }
             }
-            RaftMsg::ClientWriteRequest { payload: rpc, tx } => {
-                if is_leader() {
-                    self.write_entry(rpc, Some(tx)).await?;
+            RaftMsg::ElectNow { tx } => {
+                let _ = tx.send(self.handle_elect_now().await.extract_fatal()?);
+            }
+            RaftMsg::PurgeLog { upto, tx } => {
+                let res = self.engine.purge_in_snapshot_log_upto(upto);
+                self.run_engine_commands::<Entry<C>>(&[]).await?;
+                let _ = tx.send(res.map_err(|e| e.into()));
+            }
+            RaftMsg::StepDown { tx } => {
+                if !is_leader() {
+                    self.reject_with_forward_to_leader(tx);
+                } else if transferring {
+                    self.reject_while_transferring(tx);
                 } else {
+                    self.handle_step_down(tx).await;
+                }
+            }
+            RaftMsg::ClientWriteRequest { payload: rpc, tx } => {
+                if !is_leader() {
                     self.reject_with_forward_to_leader(tx);
+                } else if transferring {
+                    self.reject_while_transferring(tx);
+                } else if let Err(e) = self.check_entry_size(&rpc) {
+                    let _ = tx.send(Err(e.into()));
+                } else {
+                    self.write_entry(rpc, Some(tx)).await?;
                 }
             }
             RaftMsg::Initialize { members, tx } => {
                 let _ = tx.send(self.handle_initialize(members).await.extract_fatal()?);
             }
             RaftMsg::AddLearner { id, node, tx } => {
-                if is_leader() {
-                    self.add_learner(id, node, tx).await?;
+                if !is_leader() {
+                    self.reject_with_forward_to_leader(tx);
+                } else if transferring {
+                    self.reject_while_transferring(tx);
                 } else {
+                    self.add_learner(id, node, tx).await?;
+                }
+            }
+            RaftMsg::RemoveLearner { id, tx } => {
+                if !is_leader() {
                     self.reject_with_forward_to_leader(tx);
+                } else if transferring {
+                    self.reject_while_transferring(tx);
+                } else {
+                    self.remove_learner(id, tx).await?;
                 }
             }
             RaftMsg::ChangeMembership {
                 changes,
                 when,
+                check_quorum_live,
                 turn_to_learner,
                 tx,
             } => {
-                if is_leader() {
-                    self.change_membership(changes, when, turn_to_learner, tx).await?;
-                } else {
+                if !is_leader() {
                     self.reject_with_forward_to_leader(tx);
+                } else if transferring {
+                    self.reject_while_transferring(tx);
+                } else {
+                    self.change_membership(changes, when, check_quorum_live, turn_to_learner, tx).await?;
                 }
             }
             RaftMsg::ExternalRequest { req } => {
@@ -1285,9 +2361,11 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                         let log_id = self.write_entry(EntryPayload::Blank, None).await?;
                         tracing::debug!(log_id = display(&log_id), "ExternalCommand: sent heartbeat log");
                     }
-                    ExternalCommand::Snapshot => self.trigger_snapshot_if_needed(true).await,
                 }
             }
+            RaftMsg::TriggerSnapshot { tx } => {
+                self.handle_trigger_snapshot(tx).await;
+            }
             RaftMsg::Tick { i } => {
                 // check every timer
 
@@ -1305,8 +2383,12 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                         #[allow(clippy::collapsible_else_if)]
                         if self.runtime_config.enable_elect.load(Ordering::Relaxed) {
                             if self.engine.state.membership_state.effective.is_voter(&self.id) {
-                                self.engine.elect();
-                                self.run_engine_commands::<Entry<C>>(&[]).await?;
+                                if self.config.enable_pre_vote {
+                                    self.start_pre_vote().await?;
+                                } else {
+                                    self.engine.elect();
+                                    self.run_engine_commands::<Entry<C>>(&[]).await?;
+                                }
                             } else {
                                 // Node is switched to learner after setting up next election time.
                             }
@@ -1314,6 +2396,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                     }
                 }
 
+                self.check_snapshot_receive_timeout(now);
+                self.check_transfer_timeout(now);
+
                 // TODO: test: with heartbeat log, election is automatically rejected.
                 // TODO: test: fixture: make isolated_nodes a single-way isolating.
 
@@ -1321,7 +2406,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 let heartbeat_at = self.leader_data.as_ref().map(|x| x.next_heartbeat);
                 if let Some(t) = heartbeat_at {
                     if now >= t {
-                        if self.runtime_config.enable_heartbeat.load(Ordering::Relaxed) {
+                        // No log is written while transferring leadership, see `LeaderTransfer`.
+                        if self.runtime_config.enable_heartbeat.load(Ordering::Relaxed)
+                            && !self.is_transferring_leadership()
+                        {
                             // heartbeat by sending a blank log
                             // TODO: use Engine::append_blank_log
                             let log_id = self.write_entry(EntryPayload::Blank, None).await?;
@@ -1342,14 +2430,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 // again, electing itself.
                 self.engine.leader_step_down();
                 self.run_engine_commands::<Entry<C>>(&[]).await?;
+
+                self.update_log_stats().await;
             }
 
-            RaftMsg::HigherVote {
-                target: _,
-                higher,
-                vote,
-            } => {
+            RaftMsg::HigherVote { target, higher, vote } => {
                 if self.does_vote_match(vote, "HigherVote") {
+                    self.check_term_jump(target, &higher);
+
                     // Rejected vote change is ok.
                     let _ = self.engine.handle_vote_change(&higher);
                     self.run_engine_commands::<Entry<C>>(&[]).await?;
@@ -1371,9 +2459,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 }
             }
 
-            RaftMsg::NeedsSnapshot { target: _, tx, vote } => {
+            RaftMsg::NeedsSnapshot { target, tx, vote } => {
                 if self.does_vote_match(vote, "NeedsSnapshot") {
-                    self.handle_needs_snapshot(tx).await?;
+                    self.handle_needs_snapshot(target, tx).await?;
                 }
             }
             RaftMsg::ReplicationFatal => {
@@ -1412,10 +2500,25 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
             }
         };
 
+        if let Some(l) = &mut self.leader_data {
+            let prev = l.replication_metrics.data().replication.get(&target).map(|x| x.matched());
+            if prev == Some(matched) {
+                // Nothing to commit, e.g., a heartbeat response. Only record when the target responded.
+                // It is updated in place and does not trigger a metrics report.
+                l.replication_metrics.update(UpdateMatchedLogId {
+                    target,
+                    matched,
+                    rpc_ok_at: SystemTime::now(),
+                });
+                return Ok(());
+            }
+        }
+
         self.engine.update_progress(target, Some(matched));
         self.run_engine_commands::<Entry<C>>(&[]).await?;
 
         self.update_replication_metrics(target, matched);
+        self.try_send_timeout_now().await;
 
         Ok(())
     }
@@ -1430,7 +2533,11 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
                 matched = debug(&matched),
                 "update replication_metrics"
             );
-            l.replication_metrics.update(UpdateMatchedLogId { target, matched });
+            l.replication_metrics.update(UpdateMatchedLogId {
+                target,
+                matched,
+                rpc_ok_at: SystemTime::now(),
+            });
         } else {
             // This method is only called after `update_progress()`.
             // And this node may become a non-leader after `update_progress()`
@@ -1461,12 +2568,16 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
     #[tracing::instrument(level = "debug", skip(self, tx))]
     async fn handle_needs_snapshot(
         &mut self,
+        target: C::NodeId,
         tx: oneshot::Sender<Snapshot<C::NodeId, C::Node, S::SnapshotData>>,
     ) -> Result<(), StorageError<C::NodeId>> {
         // Check for existence of current snapshot.
         let current_snapshot_opt = self.storage.get_current_snapshot().await?;
 
         if let Some(snapshot) = current_snapshot_opt {
+            if let Some(l) = &mut self.leader_data {
+                l.snapshot_retries.remove(&target);
+            }
             let _ = tx.send(snapshot);
             return Ok(());
         }
@@ -1494,9 +2605,53 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftCore<C,
         //
         // If this block is executed, and a snapshot is needed, the repl stream will submit another
         // request here shortly, and will hit the above logic where it will await the snapshot completion.
+        //
+        // With `SnapshotPolicy::Never` this does nothing: a snapshot is only built when the application asks for it.
         self.trigger_snapshot_if_needed(false).await;
+
+        // The repl stream re-sends the request as soon as `tx` is dropped. If compaction keeps failing, it would
+        // spin, thus the response is delayed with an exponential backoff.
+        let backoff = self.snapshot_retry_backoff(target);
+        tokio::spawn(
+            async move {
+                sleep(backoff).await;
+                drop(tx);
+            }
+            .instrument(tracing::debug_span!("snapshot-retry-backoff")),
+        );
         Ok(())
     }
+
+    /// Count a snapshot request from `target` that is not served with a snapshot, and returns how long to wait before
+    /// `target` requests again.
+    ///
+    /// After `Config::snapshot_retry_count` such requests in a row, it logs an error for every further one.
+    fn snapshot_retry_backoff(&mut self, target: C::NodeId) -> Duration {
+        let attempt = match &mut self.leader_data {
+            Some(l) => {
+                let n = l.snapshot_retries.entry(target).or_default();
+                *n += 1;
+                *n
+            }
+            None => 1,
+        };
+
+        if attempt > self.config.snapshot_retry_count {
+            tracing::error!(
+                target = display(target),
+                attempt,
+                "no snapshot is available for replication target, compaction may keep failing"
+            );
+        } else {
+            tracing::info!(
+                target = display(target),
+                attempt,
+                "no snapshot is available yet for replication target"
+            );
+        }
+
+        Duration::from_millis(10 << (attempt - 1).min(10))
+    }
 }
 
 #[async_trait::async_trait]
@@ -1529,6 +2684,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                     }
                     self.leader_data = None;
                 }
+
+                self.update_current_leader();
             }
             Command::AppendInputEntries { range } => {
                 let entry_refs = &input_ref_entries[range.clone()];
@@ -1541,7 +2698,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                 // Build a slice of references.
                 let entry_refs = entries.iter().collect::<Vec<_>>();
 
-                self.storage.append_to_log(&entry_refs).await?
+                self.append_to_storage_with_retry(&entry_refs).await?;
             }
             Command::AppendBlankLog { log_id } => {
                 let ent = Entry {
@@ -1549,11 +2706,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                     payload: EntryPayload::Blank,
                 };
                 let entry_refs = vec![&ent];
-                self.storage.append_to_log(&entry_refs).await?
+                self.append_to_storage_with_retry(&entry_refs).await?;
             }
             Command::MoveInputCursorBy { n } => *cur += n,
             Command::SaveVote { vote } => {
                 self.storage.save_vote(vote).await?;
+
+                self.saved_vote = *vote;
+                self.update_current_leader();
             }
             Command::InstallElectionTimer { can_be_leader } => {
                 self.set_next_election_time(*can_be_leader);
@@ -1564,7 +2724,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
             }
             Command::BuildSnapshot { .. } => {}
             Command::SendVote { vote_req } => {
-                self.spawn_parallel_vote_requests(vote_req).await;
+                self.spawn_parallel_vote_requests(vote_req, false).await;
             }
             Command::ReplicateCommitted { committed } => {
                 if let Some(l) = &self.leader_data {
@@ -1579,12 +2739,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                 already_committed: ref committed,
                 ref upto,
             } => {
+                self.notify_committed(*upto);
                 self.apply_to_state_machine(committed.next_index(), upto.index).await?;
             }
             Command::FollowerCommit {
                 already_committed: ref committed,
                 ref upto,
             } => {
+                self.notify_committed(*upto);
                 self.apply_to_state_machine(committed.next_index(), upto.index).await?;
             }
             Command::ReplicateEntries { upto } => {
@@ -1614,6 +2776,12 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                         }
                     };
                 }
+
+                // Forget the snapshot requests of removed targets.
+                if let Some(l) = &mut self.leader_data {
+                    let nodes = &l.nodes;
+                    l.snapshot_retries.retain(|target, _| nodes.contains_key(target));
+                }
             }
             Command::UpdateMembership { .. } => {
                 // TODO: not used
@@ -1628,6 +2796,11 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> RaftRuntime
                 if let Some(data) = snapshot_data {
                     self.storage.install_snapshot(snapshot_meta, data).await?;
                     tracing::debug!("Done install_snapshot, meta: {:?}", snapshot_meta);
+
+                    self.snapshots_received += 1;
+                    // Logs before this snapshot are replaced by it.
+                    self.applied_log_sizes.lock().unwrap().purge_upto(snapshot_meta.last_log_id.index());
+                    self.engine.metrics_flags.set_data_changed();
                 } else {
                     unreachable!("buffered snapshot not found: snapshot meta: {:?}", snapshot_meta)
                 }
@@ -2,6 +2,7 @@ use futures::future::AbortHandle;
 use tokio::sync::broadcast;
 
 use crate::core::streaming_state::StreamingState;
+use crate::raft::TriggerSnapshotTx;
 use crate::LogId;
 use crate::Node;
 use crate::NodeId;
@@ -19,6 +20,8 @@ pub(crate) enum SnapshotState<C: RaftTypeConfig, SD> {
         abort_handle: AbortHandle,
         /// A sender for notifying any other tasks of the completion of this compaction.
         sender: broadcast::Sender<Option<LogId<C::NodeId>>>,
+        /// The callers of `Raft::trigger_snapshot()` waiting for this compaction to finish.
+        waiters: Vec<TriggerSnapshotTx<C::NodeId, C::Node>>,
     },
     /// The Raft node is streaming in a snapshot from the leader.
     Streaming(StreamingState<C, SD>),
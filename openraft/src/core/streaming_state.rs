@@ -5,6 +5,7 @@ use tokio::io::AsyncSeek;
 use tokio::io::AsyncSeekExt;
 use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
+use tokio::time::Instant;
 
 use crate::raft::InstallSnapshotRequest;
 use crate::ErrorSubject;
@@ -21,6 +22,8 @@ pub(crate) struct StreamingState<C: RaftTypeConfig, SD> {
     pub(crate) snapshot_id: SnapshotId,
     /// A handle to the snapshot writer.
     pub(crate) snapshot_data: Box<SD>,
+    /// When the last chunk is received, or when the streaming began.
+    pub(crate) last_received: Instant,
 
     _p: PhantomData<C>,
 }
@@ -33,6 +36,7 @@ where SD: AsyncSeek + AsyncWrite + Unpin
             offset: 0,
             snapshot_id,
             snapshot_data,
+            last_received: Instant::now(),
             _p: Default::default(),
         }
     }
@@ -63,6 +67,7 @@ where SD: AsyncSeek + AsyncWrite + Unpin
             ));
         }
         self.offset += req.data.len() as u64;
+        self.last_received = Instant::now();
         Ok(req.done)
     }
 }
@@ -11,6 +11,7 @@ use crate::ErrorSubject;
 use crate::LogId;
 use crate::RaftStorage;
 use crate::RaftTypeConfig;
+use crate::SnapshotMeta;
 use crate::StorageError;
 use crate::Violation;
 use crate::Vote;
@@ -238,6 +239,31 @@ where
         Ok(())
     }
 
+    /// A snapshot to install must not revert the state machine to a previous state.
+    async fn defensive_install_snapshot_ge_last_applied(
+        &mut self,
+        meta: &SnapshotMeta<C::NodeId, C::Node>,
+    ) -> Result<(), StorageError<C::NodeId>> {
+        if !self.is_defensive() {
+            return Ok(());
+        }
+
+        let (last_applied, _) = self.inner().last_applied_state().await?;
+
+        if meta.last_log_id < last_applied {
+            return Err(DefensiveError::new(
+                ErrorSubject::Snapshot(meta.signature()),
+                Violation::SnapshotNotAscending {
+                    last_applied,
+                    snapshot_last_log_id: meta.last_log_id,
+                },
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
     /// The entries to apply to state machien has to be last_applied_log_id.index + 1
     async fn defensive_apply_index_is_last_applied_plus_one(
         &mut self,
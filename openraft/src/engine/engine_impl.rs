@@ -7,6 +7,7 @@ use crate::error::InitializeError;
 use crate::error::NotAMembershipEntry;
 use crate::error::NotAllowed;
 use crate::error::NotInMembers;
+use crate::error::NotInSnapshot;
 use crate::error::RejectVoteRequest;
 use crate::internal_server_state::InternalServerState;
 use crate::membership::EffectiveMembership;
@@ -15,6 +16,8 @@ use crate::node::Node;
 use crate::progress::entry::ProgressEntry;
 use crate::progress::Progress;
 use crate::raft::AppendEntriesResponse;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::raft_state::RaftState;
@@ -28,6 +31,7 @@ use crate::MetricsChangeFlags;
 use crate::NodeId;
 use crate::SnapshotMeta;
 use crate::Vote;
+use crate::VoteReason;
 
 /// Config for Engine
 #[derive(Clone, Debug)]
@@ -80,6 +84,10 @@ where
     /// Tracks what kind of metrics changed
     pub(crate) metrics_flags: MetricsChangeFlags,
 
+    /// The number of times a log accepted by a quorum is not committed, because it is not proposed by the current
+    /// leader, see [`Engine::update_progress`].
+    pub(crate) commit_blocked_by_term: u64,
+
     /// Command queue that need to be executed by `RaftRuntime`.
     pub(crate) commands: Vec<Command<NID, N>>,
 }
@@ -96,6 +104,7 @@ where
             snapshot_meta: Default::default(),
             state: init_state.clone(),
             metrics_flags: MetricsChangeFlags::default(),
+            commit_blocked_by_term: 0,
             commands: vec![],
         }
     }
@@ -166,8 +175,9 @@ where
         self.push_command(Command::InstallElectionTimer { can_be_leader: true });
     }
 
+    /// Handle a vote request and return the response along with why the vote is granted or rejected.
     #[tracing::instrument(level = "debug", skip_all)]
-    pub(crate) fn handle_vote_req(&mut self, req: VoteRequest<NID>) -> VoteResponse<NID> {
+    pub(crate) fn handle_vote_req(&mut self, req: VoteRequest<NID>) -> (VoteResponse<NID>, VoteReason<NID>) {
         tracing::debug!(req = display(req.summary()), "Engine::handle_vote_req");
         tracing::debug!(
             my_vote = display(self.state.vote.summary()),
@@ -175,32 +185,120 @@ where
             "Engine::handle_vote_req"
         );
 
-        let res = if req.last_log_id >= self.state.last_log_id() {
+        let res = if self.is_log_fresh_enough(req.last_log_id.as_ref()) {
             self.handle_vote_change(&req.vote)
         } else {
             Err(RejectVoteRequest::ByLastLogId(self.state.last_log_id()))
         };
 
-        let vote_granted = if let Err(reject) = res {
-            tracing::debug!(
-                req = display(req.summary()),
-                err = display(reject),
-                "reject vote request"
-            );
-            false
-        } else {
-            true
+        let reason = match res {
+            Ok(()) => VoteReason::Granted,
+            Err(reject) => {
+                tracing::debug!(
+                    req = display(req.summary()),
+                    err = display(&reject),
+                    "reject vote request"
+                );
+
+                match reject {
+                    RejectVoteRequest::ByLastLogId(my_last_log_id) => VoteReason::LogNotFreshEnough { my_last_log_id },
+                    RejectVoteRequest::ByVote(my_vote) if req.vote.term < my_vote.term => {
+                        VoteReason::TermTooLow { my_vote }
+                    }
+                    RejectVoteRequest::ByVote(my_vote) => VoteReason::AlreadyVoted { my_vote },
+                }
+            }
         };
 
-        VoteResponse {
+        let resp = VoteResponse {
             // Return the updated vote, this way the candidate knows which vote is granted, in case the candidate's vote
             // is changed after sending the vote request.
+            vote: self.state.vote,
+            vote_granted: reason == VoteReason::Granted,
+            last_log_id: self.state.last_log_id(),
+        };
+
+        (resp, reason)
+    }
+
+    /// Decide if the vote in a pre-vote request would be granted, without changing any state.
+    ///
+    /// A pre-vote is granted if the candidate's vote is greater than the local one, the candidate's log is fresh
+    /// enough, and this node does not see a live leader, i.e., `leader_alive` is false.
+    /// Thus a node that is partitioned away can not disrupt a working leader when it reconnects.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn handle_pre_vote_req(&self, req: &VoteRequest<NID>, leader_alive: bool) -> VoteResponse<NID> {
+        tracing::debug!(
+            req = display(req.summary()),
+            my_vote = display(self.state.vote.summary()),
+            leader_alive,
+            "Engine::handle_pre_vote_req"
+        );
+
+        let vote_granted =
+            !leader_alive && req.vote > self.state.vote && self.is_log_fresh_enough(req.last_log_id.as_ref());
+
+        VoteResponse {
             vote: self.state.vote,
             vote_granted,
             last_log_id: self.state.last_log_id(),
         }
     }
 
+    /// Choose a voter to transfer leadership to: the most up-to-date one other than this node.
+    ///
+    /// If several voters are equally up-to-date, the smallest node id is chosen.
+    /// It returns `None` if this node is not leading or there is no other voter.
+    pub(crate) fn leader_transfer_target(&self) -> Option<NID> {
+        let leader = self.state.internal_server_state.leading()?;
+
+        let (target, _matching) = leader
+            .progress
+            .iter()
+            .filter(|(id, _)| *id != self.id && self.state.membership_state.is_voter(id))
+            .map(|(id, p)| (*id, p.matching))
+            .min_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)))?;
+
+        Some(target)
+    }
+
+    /// Handle a timeout-now request sent by a leader that is transferring its leadership to this node.
+    ///
+    /// The request is accepted only if it is sent by the leader this node follows and this node has all of the
+    /// leader's logs. This node then starts an election at once.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn handle_timeout_now(&mut self, req: &TimeoutNowRequest<NID>) -> TimeoutNowResponse<NID> {
+        tracing::debug!(
+            req = display(req.summary()),
+            my_vote = display(self.state.vote.summary()),
+            my_last_log_id = display(self.state.last_log_id().summary()),
+            "Engine::handle_timeout_now"
+        );
+
+        let accepted = req.vote == self.state.vote
+            && req.vote.committed
+            && req.vote.node_id != self.id
+            && self.is_voter()
+            && self.state.last_log_id() >= req.last_log_id;
+
+        if accepted {
+            self.elect();
+        }
+
+        TimeoutNowResponse {
+            vote: self.state.vote,
+            accepted,
+        }
+    }
+
+    /// Return if a candidate's last log id is at least as up-to-date as the local last log id.
+    ///
+    /// Freshness is decided by the total order of `LogId`: the leader id(term first) is compared first, then the
+    /// index. Thus a candidate with a greater index but a smaller term is not fresh enough.
+    pub(crate) fn is_log_fresh_enough(&self, candidate_last_log_id: Option<&LogId<NID>>) -> bool {
+        candidate_last_log_id >= self.state.last_log_id().as_ref()
+    }
+
     #[tracing::instrument(level = "debug", skip(self, resp))]
     pub(crate) fn handle_vote_resp(&mut self, target: NID, resp: VoteResponse<NID>) {
         tracing::debug!(
@@ -381,6 +479,9 @@ where
             }
         }
         // else `prev_log_id.is_none()` means replicating logs from the very beginning.
+        //
+        // The log at index 0 is always `(0,0,0)`, thus a `prev_log_id` at index 0 matches as long as the local
+        // bootstrap log is present, and the first real entry at index 1 is appended without a conflict.
 
         tracing::debug!(
             ?self.state.committed,
@@ -418,7 +519,7 @@ where
 
         // Committed index can not > last_log_id.index
         let last = entries.last().map(|x| *x.get_log_id());
-        let last = std::cmp::max(last, prev_log_id);
+        let last = last.max_log_id(prev_log_id);
         let committed = std::cmp::min(leader_committed, last);
 
         tracing::debug!(committed = display(committed.summary()), "update committed");
@@ -592,6 +693,37 @@ where
         log_id
     }
 
+    /// Purge logs upto index `upto`, inclusive, that are already in the current snapshot, without building a new one.
+    ///
+    /// The last `max_in_snapshot_log_to_keep` logs in snapshot are kept, thus fewer logs may be purged.
+    /// `purge_batch_size` does not apply.
+    /// It returns the last purged log id after purging, or an error if the log at `upto` is not in snapshot.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub(crate) fn purge_in_snapshot_log_upto(&mut self, upto: u64) -> Result<Option<LogId<NID>>, NotInSnapshot<NID>> {
+        let snapshot_last_log_id = self.snapshot_meta.last_log_id;
+
+        if Some(upto) > snapshot_last_log_id.index() {
+            return Err(NotInSnapshot {
+                index: upto,
+                snapshot_last_log_id,
+            });
+        }
+
+        let max_keep = self.config.max_in_snapshot_log_to_keep;
+        let purge_end = std::cmp::min(upto + 1, snapshot_last_log_id.next_index().saturating_sub(max_keep));
+
+        if purge_end > self.state.last_purged_log_id().next_index() {
+            let log_id = self.state.log_ids.get(purge_end - 1);
+            debug_assert!(log_id.is_some(), "log id not found at {}", purge_end - 1);
+
+            if let Some(log_id) = log_id {
+                self.purge_log(log_id);
+            }
+        }
+
+        Ok(self.state.last_purged_log_id())
+    }
+
     /// Purge log entries upto `upto`, inclusive.
     #[tracing::instrument(level = "debug", skip(self))]
     pub(crate) fn purge_log(&mut self, upto: LogId<NID>) {
@@ -610,7 +742,7 @@ where
     /// Update membership state with a committed membership config
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) fn update_committed_membership(&mut self, membership: EffectiveMembership<NID, N>) {
-        tracing::debug!("update committed membership: {}", membership.summary());
+        tracing::debug!("update committed membership: {}", membership);
 
         let server_state = self.calc_server_state();
 
@@ -647,7 +779,7 @@ where
 
     #[tracing::instrument(level = "debug", skip_all)]
     pub(crate) fn update_effective_membership(&mut self, log_id: &LogId<NID>, m: &Membership<NID, N>) {
-        tracing::debug!("update effective membership: log_id:{} {}", log_id, m.summary());
+        tracing::debug!("update effective membership: {} {}", log_id, m);
 
         let server_state = self.calc_server_state();
 
@@ -730,6 +862,11 @@ where
         // Only when the log id is proposed by current leader, it is committed.
         if let Some(c) = committed {
             if c.leader_id.term != self.state.vote.term || c.leader_id.node_id != self.state.vote.node_id {
+                if Some(c) > self.state.committed {
+                    self.commit_blocked_by_term += 1;
+                    self.metrics_flags.set_data_changed();
+                    self.log_commit_blocked(c);
+                }
                 return;
             }
         }
@@ -745,6 +882,28 @@ where
         }
     }
 
+    /// Log that committing is blocked by a quorum-accepted log of a previous leader, once for every leader.
+    ///
+    /// It happens right after a leader is elected, until a log proposed by it is accepted by a quorum.
+    fn log_commit_blocked(&mut self, quorum_accepted: LogId<NID>) {
+        let leader = match self.state.internal_server_state.leading_mut() {
+            None => return,
+            Some(x) => x,
+        };
+
+        if leader.commit_blocked_logged {
+            return;
+        }
+        leader.commit_blocked_logged = true;
+
+        tracing::info!(
+            quorum_accepted = display(quorum_accepted),
+            committed = debug(self.state.committed),
+            vote = display(self.state.vote),
+            "commit index does not advance: the log accepted by a quorum is not proposed by the current leader"
+        );
+    }
+
     /// Leader steps down(convert to learner) once the membership not containing it is committed.
     ///
     /// This is only called by leader.
@@ -420,3 +420,49 @@ fn test_handle_append_entries_req_entries_conflict() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_handle_append_entries_req_fresh_follower_accepts_first_entry() -> anyhow::Result<()> {
+    // A fresh follower has only the bootstrap log at index 0 and nothing committed.
+    // prev_log_id pointing to index 0 must match and the first real entry must be appended without a conflict.
+    let zero = LogId::new(LeaderId::new(0, 0), 0);
+
+    let mut eng = Engine::<u64, ()> {
+        id: 2,
+        ..Default::default()
+    };
+    eng.state.log_ids.append(zero);
+    eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(zero), m23()));
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(zero), m23()));
+
+    let resp = eng.handle_append_entries_req(&Vote::new_committed(1, 1), Some(zero), &[blank(1, 1)], Some(zero));
+
+    assert_eq!(AppendEntriesResponse::Success, resp);
+    assert_eq!(
+        &[
+            zero,         //
+            log_id(1, 1), //
+        ],
+        eng.state.log_ids.key_log_ids()
+    );
+    assert_eq!(Some(log_id(1, 1)), eng.state.last_log_id());
+    assert_eq!(Some(zero), eng.state.committed);
+
+    assert_eq!(
+        vec![
+            Command::SaveVote {
+                vote: Vote::new_committed(1, 1)
+            },
+            Command::InstallElectionTimer { can_be_leader: false },
+            Command::AppendInputEntries { range: 0..1 },
+            Command::MoveInputCursorBy { n: 1 },
+            Command::FollowerCommit {
+                already_committed: None,
+                upto: zero
+            },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::core::ServerState;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::raft::VoteRequest;
+use crate::raft::VoteResponse;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m01() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {0,1}], None)
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::<u64, ()>::default();
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.server_state = ServerState::Follower;
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng
+}
+
+#[test]
+fn test_handle_pre_vote_req_grant() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let resp = eng.handle_pre_vote_req(&VoteRequest::new(Vote::new(3, 2), Some(log_id(2, 3))), false);
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new_committed(2, 1),
+            vote_granted: true,
+            last_log_id: Some(log_id(2, 3))
+        },
+        resp
+    );
+
+    // Pre-vote does not change any state.
+    assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_req_reject_leader_alive() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let resp = eng.handle_pre_vote_req(&VoteRequest::new(Vote::new(3, 2), Some(log_id(2, 3))), true);
+
+    assert!(!resp.vote_granted);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let resp = eng.handle_pre_vote_req(&VoteRequest::new(Vote::new(1, 2), Some(log_id(2, 3))), false);
+
+    assert!(!resp.vote_granted);
+    assert_eq!(Vote::new_committed(2, 1), resp.vote);
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_pre_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
+    let eng = eng();
+
+    let resp = eng.handle_pre_vote_req(&VoteRequest::new(Vote::new(3, 2), Some(log_id(1, 5))), false);
+
+    assert!(!resp.vote_granted);
+    assert_eq!(Some(log_id(2, 3)), resp.last_log_id);
+
+    Ok(())
+}
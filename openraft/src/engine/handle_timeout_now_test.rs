@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::raft::TimeoutNowRequest;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m123() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], None)
+}
+
+/// A follower of leader 1.
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::<u64, ()> {
+        id: 2,
+        ..Default::default()
+    };
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.server_state = ServerState::Follower;
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123()));
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng
+}
+
+#[test]
+fn test_handle_timeout_now_accept() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let resp = eng.handle_timeout_now(&TimeoutNowRequest::new(Vote::new_committed(2, 1), Some(log_id(2, 3))));
+
+    assert!(resp.accepted);
+    assert_eq!(Vote::new(3, 2), resp.vote);
+    assert_eq!(Vote::new(3, 2), eng.state.vote);
+    assert_eq!(ServerState::Candidate, eng.state.server_state);
+    assert!(eng.commands.iter().any(|c| matches!(c, Command::SendVote { .. })));
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_timeout_now_reject_other_leader() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let resp = eng.handle_timeout_now(&TimeoutNowRequest::new(Vote::new_committed(3, 3), Some(log_id(2, 3))));
+
+    assert!(!resp.accepted);
+    assert_eq!(Vote::new_committed(2, 1), resp.vote);
+    assert_eq!(ServerState::Follower, eng.state.server_state);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_timeout_now_reject_lagging() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let resp = eng.handle_timeout_now(&TimeoutNowRequest::new(Vote::new_committed(2, 1), Some(log_id(2, 5))));
+
+    assert!(!resp.accepted);
+    assert_eq!(Vote::new_committed(2, 1), eng.state.vote);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
@@ -14,6 +14,7 @@ use crate::LogId;
 use crate::Membership;
 use crate::MetricsChangeFlags;
 use crate::Vote;
+use crate::VoteReason;
 
 fn log_id(term: u64, index: u64) -> LogId<u64> {
     LogId::<u64> {
@@ -39,10 +40,7 @@ fn eng() -> Engine<u64, ()> {
 fn test_handle_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
     let mut eng = eng();
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(1, 2),
-        last_log_id: None,
-    });
+    let (resp, reason) = eng.handle_vote_req(VoteRequest::new(Vote::new(1, 2), None));
 
     assert_eq!(
         VoteResponse {
@@ -52,6 +50,12 @@ fn test_handle_vote_req_reject_smaller_vote() -> anyhow::Result<()> {
         },
         resp
     );
+    assert_eq!(
+        VoteReason::TermTooLow {
+            my_vote: Vote::new(2, 1)
+        },
+        reason
+    );
 
     assert_eq!(Vote::new(2, 1), eng.state.vote);
     assert!(eng.state.internal_server_state.is_leading());
@@ -76,10 +80,7 @@ fn test_handle_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(3, 2),
-        last_log_id: Some(log_id(1, 3)),
-    });
+    let (resp, reason) = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(1, 3))));
 
     assert_eq!(
         VoteResponse {
@@ -89,6 +90,12 @@ fn test_handle_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
         },
         resp
     );
+    assert_eq!(
+        VoteReason::LogNotFreshEnough {
+            my_last_log_id: Some(log_id(2, 3))
+        },
+        reason
+    );
 
     assert_eq!(Vote::new(2, 1), eng.state.vote);
     assert!(eng.state.internal_server_state.is_leading());
@@ -107,6 +114,41 @@ fn test_handle_vote_req_reject_smaller_last_log_id() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_handle_vote_req_reject_greater_index_smaller_term() -> anyhow::Result<()> {
+    // The candidate has a greater index but a smaller term: the local log is fresher by term.
+    let mut eng = eng();
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+
+    assert!(!eng.is_log_fresh_enough(Some(&log_id(1, 5))));
+
+    let (resp, reason) = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 2), Some(log_id(1, 5))));
+
+    assert_eq!(
+        VoteResponse {
+            vote: Vote::new(2, 1),
+            vote_granted: false,
+            last_log_id: Some(log_id(2, 3))
+        },
+        resp
+    );
+    assert_eq!(
+        VoteReason::LogNotFreshEnough {
+            my_last_log_id: Some(log_id(2, 3))
+        },
+        reason
+    );
+
+    assert_eq!(Vote::new(2, 1), eng.state.vote);
+    assert_eq!(ServerState::Candidate, eng.state.server_state);
+    assert_eq!(0, eng.commands.len());
+
+    // A greater term with a smaller index is fresh enough.
+    assert!(eng.is_log_fresh_enough(Some(&log_id(3, 1))));
+
+    Ok(())
+}
+
 #[test]
 fn test_handle_vote_req_granted_equal_vote_and_last_log_id() -> anyhow::Result<()> {
     // Equal vote should not emit a SaveVote command.
@@ -114,10 +156,7 @@ fn test_handle_vote_req_granted_equal_vote_and_last_log_id() -> anyhow::Result<(
     let mut eng = eng();
     eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(2, 1),
-        last_log_id: Some(log_id(2, 3)),
-    });
+    let (resp, reason) = eng.handle_vote_req(VoteRequest::new(Vote::new(2, 1), Some(log_id(2, 3))));
 
     assert_eq!(
         VoteResponse {
@@ -127,6 +166,7 @@ fn test_handle_vote_req_granted_equal_vote_and_last_log_id() -> anyhow::Result<(
         },
         resp
     );
+    assert_eq!(VoteReason::Granted, reason);
 
     assert_eq!(Vote::new(2, 1), eng.state.vote);
     assert!(eng.state.internal_server_state.is_following());
@@ -161,10 +201,7 @@ fn test_handle_vote_req_granted_greater_vote() -> anyhow::Result<()> {
     let mut eng = eng();
     eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
 
-    let resp = eng.handle_vote_req(VoteRequest {
-        vote: Vote::new(3, 1),
-        last_log_id: Some(log_id(2, 3)),
-    });
+    let (resp, reason) = eng.handle_vote_req(VoteRequest::new(Vote::new(3, 1), Some(log_id(2, 3))));
 
     assert_eq!(
         VoteResponse {
@@ -175,6 +212,7 @@ fn test_handle_vote_req_granted_greater_vote() -> anyhow::Result<()> {
         },
         resp
     );
+    assert_eq!(VoteReason::Granted, reason);
 
     assert_eq!(Vote::new(3, 1), eng.state.vote);
     assert!(eng.state.internal_server_state.is_following());
@@ -216,10 +254,7 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.state.server_state = st;
         eng.commands = vec![];
 
-        eng.handle_vote_req(VoteRequest {
-            vote: Vote::new(3, 1),
-            last_log_id: Some(log_id(2, 3)),
-        });
+        eng.handle_vote_req(VoteRequest::new(Vote::new(3, 1), Some(log_id(2, 3))));
 
         assert_eq!(st, eng.state.server_state);
         assert_eq!(
@@ -241,10 +276,7 @@ fn test_handle_vote_req_granted_follower_learner_does_not_emit_update_server_sta
         eng.state.server_state = st;
         eng.commands = vec![];
 
-        eng.handle_vote_req(VoteRequest {
-            vote: Vote::new(3, 1),
-            last_log_id: Some(log_id(2, 3)),
-        });
+        eng.handle_vote_req(VoteRequest::new(Vote::new(3, 1), Some(log_id(2, 3))));
 
         assert_eq!(st, eng.state.server_state);
         assert_eq!(
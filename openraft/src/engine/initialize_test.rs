@@ -183,17 +183,17 @@ fn test_initialize() -> anyhow::Result<()> {
                     },
                 },
                 Command::SendVote {
-                    vote_req: VoteRequest {
-                        vote: Vote {
+                    vote_req: VoteRequest::new(
+                        Vote {
                             term: 1,
                             node_id: 1,
                             committed: false,
                         },
-                        last_log_id: Some(LogId {
+                        Some(LogId {
                             leader_id: LeaderId { term: 0, node_id: 0 },
                             index: 0,
                         },),
-                    },
+                    ),
                 },
                 Command::UpdateServerState {
                     server_state: ServerState::Candidate
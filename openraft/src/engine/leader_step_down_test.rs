@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+#[allow(unused_imports)] use pretty_assertions::assert_eq;
+
+use crate::core::ServerState;
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m01() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {0,1}], None)
+}
+
+fn m23() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {2,3}], None)
+}
+
+/// A leader of node 1, whose effective membership `{2,3}` at log-2-3 no longer contains itself.
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::<u64, ()> {
+        id: 1,
+        ..Default::default()
+    };
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.server_state = ServerState::Leader;
+    eng.state.log_ids.append(log_id(1, 1));
+    eng.state.log_ids.append(log_id(2, 3));
+    eng.state.membership_state.committed = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m01()));
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m23()));
+    eng.state.new_leader();
+    eng
+}
+
+#[test]
+fn test_leader_step_down_removed_but_not_committed() -> anyhow::Result<()> {
+    // The membership removing the leader is not yet committed: keep leading, otherwise the old config has no leader
+    // to commit it.
+    let mut eng = eng();
+    eng.state.committed = Some(log_id(2, 2));
+
+    eng.leader_step_down();
+
+    assert!(eng.state.internal_server_state.is_leading());
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_step_down_removed_and_committed() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.committed = Some(log_id(2, 3));
+
+    eng.leader_step_down();
+
+    assert!(eng.state.internal_server_state.is_following());
+    assert_eq!(ServerState::Learner, eng.state.server_state);
+    assert_eq!(
+        vec![
+            Command::InstallElectionTimer { can_be_leader: false },
+            Command::UpdateServerState {
+                server_state: ServerState::Learner
+            },
+        ],
+        eng.commands
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_step_down_not_removed() -> anyhow::Result<()> {
+    // The committed membership still contains the leader.
+    let mut eng = eng();
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(2, 3)), m01()));
+    eng.state.committed = Some(log_id(2, 3));
+
+    eng.leader_step_down();
+
+    assert!(eng.state.internal_server_state.is_leading());
+    assert_eq!(ServerState::Leader, eng.state.server_state);
+    assert_eq!(0, eng.commands.len());
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use maplit::btreeset;
+
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
+use crate::Membership;
+use crate::Vote;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 1 },
+        index,
+    }
+}
+
+fn m123() -> Membership<u64, ()> {
+    Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], None)
+}
+
+/// Leader 1 with last log at index 3.
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::<u64, ()> {
+        id: 1,
+        ..Default::default()
+    };
+    eng.state.vote = Vote::new_committed(2, 1);
+    eng.state.membership_state.effective = Arc::new(EffectiveMembership::new(Some(log_id(1, 1)), m123()));
+    eng.state.log_ids = LogIdList::new(vec![log_id(2, 3)]);
+    eng
+}
+
+#[test]
+fn test_leader_transfer_target_not_leading() -> anyhow::Result<()> {
+    let eng = eng();
+
+    assert_eq!(None, eng.leader_transfer_target());
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_transfer_target_most_up_to_date() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.new_leader();
+
+    eng.update_progress(2, Some(log_id(2, 2)));
+    eng.update_progress(3, Some(log_id(2, 3)));
+    assert_eq!(Some(3), eng.leader_transfer_target());
+
+    // The smaller node id is chosen when several voters are equally up-to-date.
+    eng.update_progress(2, Some(log_id(2, 3)));
+    assert_eq!(Some(2), eng.leader_transfer_target());
+
+    Ok(())
+}
+
+#[test]
+fn test_leader_transfer_target_none_caught_up() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.state.new_leader();
+
+    // A voter that has not yet caught up is chosen too. The leader waits for it to catch up.
+    eng.update_progress(2, Some(log_id(2, 1)));
+    eng.update_progress(3, Some(log_id(2, 2)));
+    assert_eq!(Some(3), eng.leader_transfer_target());
+
+    Ok(())
+}
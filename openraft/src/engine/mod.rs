@@ -35,13 +35,18 @@ mod log_id_list;
 #[cfg(test)] mod follower_commit_entries_test;
 #[cfg(test)] mod follower_do_append_entries_test;
 #[cfg(test)] mod handle_append_entries_req_test;
+#[cfg(test)] mod handle_pre_vote_req_test;
+#[cfg(test)] mod handle_timeout_now_test;
 #[cfg(test)] mod handle_vote_req_test;
 #[cfg(test)] mod handle_vote_resp_test;
 #[cfg(test)] mod initialize_test;
 #[cfg(test)] mod install_snapshot_test;
 #[cfg(test)] mod internal_handle_vote_req_test;
 #[cfg(test)] mod leader_append_entries_test;
+#[cfg(test)] mod leader_step_down_test;
+#[cfg(test)] mod leader_transfer_target_test;
 #[cfg(test)] mod log_id_list_test;
+#[cfg(test)] mod purge_in_snapshot_log_upto_test;
 #[cfg(test)] mod purge_log_test;
 #[cfg(test)] mod testing;
 #[cfg(test)] mod truncate_logs_test;
@@ -0,0 +1,119 @@
+use crate::engine::Command;
+use crate::engine::Engine;
+use crate::engine::LogIdList;
+use crate::error::NotInSnapshot;
+use crate::LeaderId;
+use crate::LogId;
+
+fn log_id(term: u64, index: u64) -> LogId<u64> {
+    LogId::<u64> {
+        leader_id: LeaderId { term, node_id: 0 },
+        index,
+    }
+}
+
+fn eng() -> Engine<u64, ()> {
+    let mut eng = Engine::default();
+    eng.state.log_ids = LogIdList::new(vec![
+        //
+        log_id(0, 0),
+        log_id(1, 1),
+        log_id(3, 3),
+        log_id(5, 5),
+    ]);
+    eng.snapshot_meta.last_log_id = Some(log_id(3, 4));
+    // Batch size does not apply to purging explicitly.
+    eng.config.purge_batch_size = 256;
+    eng
+}
+
+#[test]
+fn test_purge_in_snapshot_log_upto_not_in_snapshot() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let res = eng.purge_in_snapshot_log_upto(5);
+
+    assert_eq!(
+        Err(NotInSnapshot {
+            index: 5,
+            snapshot_last_log_id: Some(log_id(3, 4)),
+        }),
+        res
+    );
+    assert_eq!(None, eng.state.last_purged_log_id());
+    assert_eq!(0, eng.commands.len());
+
+    tracing::info!("--- no snapshot");
+    {
+        eng.snapshot_meta.last_log_id = None;
+
+        let res = eng.purge_in_snapshot_log_upto(0);
+        assert_eq!(
+            Err(NotInSnapshot {
+                index: 0,
+                snapshot_last_log_id: None,
+            }),
+            res
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_purge_in_snapshot_log_upto() -> anyhow::Result<()> {
+    let mut eng = eng();
+
+    let res = eng.purge_in_snapshot_log_upto(2);
+
+    assert_eq!(Ok(Some(log_id(1, 2))), res);
+    assert_eq!(Some(log_id(1, 2)), eng.state.last_purged_log_id());
+    assert_eq!(vec![Command::PurgeLog { upto: log_id(1, 2) }], eng.commands);
+
+    tracing::info!("--- purge upto the last log in snapshot");
+    {
+        eng.commands = vec![];
+
+        let res = eng.purge_in_snapshot_log_upto(4);
+
+        assert_eq!(Ok(Some(log_id(3, 4))), res);
+        assert_eq!(vec![Command::PurgeLog { upto: log_id(3, 4) }], eng.commands);
+    }
+
+    tracing::info!("--- already purged, nothing to do");
+    {
+        eng.commands = vec![];
+
+        let res = eng.purge_in_snapshot_log_upto(3);
+
+        assert_eq!(Ok(Some(log_id(3, 4))), res);
+        assert_eq!(0, eng.commands.len());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_purge_in_snapshot_log_upto_keep_logs() -> anyhow::Result<()> {
+    let mut eng = eng();
+    eng.config.max_in_snapshot_log_to_keep = 2;
+
+    // Logs at 3 and 4 are kept.
+    let res = eng.purge_in_snapshot_log_upto(4);
+
+    assert_eq!(Ok(Some(log_id(1, 2))), res);
+    assert_eq!(vec![Command::PurgeLog { upto: log_id(1, 2) }], eng.commands);
+
+    tracing::info!("--- keep more logs than there are in snapshot, nothing to do");
+    {
+        eng.commands = vec![];
+        eng.config.max_in_snapshot_log_to_keep = 10;
+
+        let res = eng.purge_in_snapshot_log_upto(4);
+
+        assert_eq!(Ok(Some(log_id(1, 2))), res);
+        assert_eq!(0, eng.commands.len());
+    }
+
+    Ok(())
+}
@@ -61,10 +61,14 @@ fn test_update_progress_update_leader_progress() -> anyhow::Result<()> {
     eng.update_progress(3, Some(log_id(1, 2)));
     assert_eq!(None, eng.state.committed);
 
+    assert_eq!(0, eng.commit_blocked_by_term);
+
     // progress: None, (2,1), (1,2); quorum-ed: (1,2), not at leader vote, not committed
     eng.update_progress(2, Some(log_id(2, 1)));
     assert_eq!(None, eng.state.committed);
     assert_eq!(0, eng.commands.len());
+    assert_eq!(1, eng.commit_blocked_by_term);
+    assert!(eng.metrics_flags.local_data);
 
     // progress: None, (2,1), (2,3); committed: (2,1)
     eng.update_progress(3, Some(log_id(2, 3)));
@@ -10,6 +10,7 @@ use anyerror::AnyError;
 use crate::node::Node;
 use crate::raft::AppendEntriesResponse;
 use crate::raft_types::SnapshotSegmentId;
+use crate::LeaderId;
 use crate::LogId;
 use crate::Membership;
 use crate::NodeId;
@@ -112,6 +113,59 @@ where
     Fatal(#[from] Fatal<NID>),
 }
 
+/// An error related to transferring leadership with `Raft::step_down()`.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum StepDownError<NID, N>
+where
+    NID: NodeId,
+    N: Node,
+{
+    #[error(transparent)]
+    ForwardToLeader(#[from] ForwardToLeader<NID, N>),
+
+    #[error(transparent)]
+    NoCaughtUpFollower(#[from] NoCaughtUpFollower<NID>),
+
+    #[error(transparent)]
+    TransferRejected(#[from] TransferRejected<NID>),
+
+    #[error(transparent)]
+    RPCError(#[from] RPCError<NID, N, VoteError<NID>>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// An error related to starting an election with `Raft::elect_now()`.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum ElectNowError<NID>
+where NID: NodeId
+{
+    #[error(transparent)]
+    AlreadyLeader(#[from] AlreadyLeader<NID>),
+
+    #[error(transparent)]
+    NotAVoter(#[from] NotAVoter<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
+/// An error related to purging logs with `Raft::purge_log()`.
+#[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum PurgeLogError<NID>
+where NID: NodeId
+{
+    #[error(transparent)]
+    NotInSnapshot(#[from] NotInSnapshot<NID>),
+
+    #[error(transparent)]
+    Fatal(#[from] Fatal<NID>),
+}
+
 /// An error related to a client write request.
 #[derive(Debug, Clone, thiserror::Error, derive_more::TryInto)]
 #[derive(PartialEq, Eq)]
@@ -128,6 +182,14 @@ where
     #[error(transparent)]
     ChangeMembershipError(#[from] ChangeMembershipError<NID>),
 
+    /// The entry to write exceeds `Config::max_entry_bytes`.
+    #[error(transparent)]
+    EntryTooLarge(#[from] EntryTooLarge),
+
+    /// The entry is not applied in time. It may still be committed and applied later.
+    #[error(transparent)]
+    Timeout(#[from] ClientWriteTimeout<NID>),
+
     #[error(transparent)]
     Fatal(#[from] Fatal<NID>),
 }
@@ -147,6 +209,9 @@ pub enum ChangeMembershipError<NID: NodeId> {
 
     #[error(transparent)]
     LearnerIsLagging(#[from] LearnerIsLagging<NID>),
+
+    #[error(transparent)]
+    QuorumNotLive(#[from] QuorumNotLive<NID>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -163,6 +228,14 @@ where
     #[error(transparent)]
     NetworkError(#[from] NetworkError),
 
+    /// When removing a caught-up learner, or when the learner is removed while waiting for it to catch up.
+    #[error(transparent)]
+    ChangeMembershipError(#[from] ChangeMembershipError<NID>),
+
+    /// When removing a caught-up learner fails with an error that is not one of the above.
+    #[error(transparent)]
+    RemoveLearner(ClientWriteError<NID, N>),
+
     #[error(transparent)]
     Fatal(#[from] Fatal<NID>),
 }
@@ -227,6 +300,24 @@ where NID: NodeId
         f.into()
     }
 }
+impl<NID> From<StorageError<NID>> for ElectNowError<NID>
+where NID: NodeId
+{
+    fn from(s: StorageError<NID>) -> Self {
+        let f: Fatal<NID> = s.into();
+        f.into()
+    }
+}
+
+impl<NID> From<StorageError<NID>> for PurgeLogError<NID>
+where NID: NodeId
+{
+    fn from(s: StorageError<NID>) -> Self {
+        let f: Fatal<NID> = s.into();
+        f.into()
+    }
+}
+
 impl<NID, N> From<StorageError<NID>> for CheckIsLeaderError<NID, N>
 where
     NID: NodeId,
@@ -407,6 +498,38 @@ where
     pub leader_node: Option<N>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("entry size {size} exceeds max_entry_bytes: {max}")]
+pub struct EntryTooLarge {
+    pub size: u64,
+    pub max: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("zero-th log entry must be (0,0,0), but {leader_id} {index}")]
+pub struct InvalidLogId<NID: NodeId> {
+    pub leader_id: LeaderId<NID>,
+    pub index: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("invalid snapshot segment id: '{input}', expect '<id>+<offset>': {reason}")]
+pub struct InvalidSnapshotSegmentId {
+    pub input: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("timeout after {timeout:?} when waiting for client write {log_id} to be applied")]
+pub struct ClientWriteTimeout<NID: NodeId> {
+    pub log_id: LogId<NID>,
+    pub timeout: Duration,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("snapshot segment id mismatch, expect: {expect}, got: {got}")]
@@ -423,6 +546,44 @@ pub struct QuorumNotEnough<NID: NodeId> {
     pub got: BTreeSet<NID>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is already the leader, vote: {vote}")]
+pub struct AlreadyLeader<NID: NodeId> {
+    pub node_id: NID,
+    pub vote: Vote<NID>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("node {node_id} is not a voter and can not be elected")]
+pub struct NotAVoter<NID: NodeId> {
+    pub node_id: NID,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("log at index {index} is not included in the snapshot, snapshot last log id: {snapshot_last_log_id:?}")]
+pub struct NotInSnapshot<NID: NodeId> {
+    pub index: u64,
+    pub snapshot_last_log_id: Option<LogId<NID>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("no voter has caught up with the leader's last log {last_log_id:?}, can not transfer leadership")]
+pub struct NoCaughtUpFollower<NID: NodeId> {
+    pub last_log_id: Option<LogId<NID>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("leadership transfer is rejected by {target}, its vote: {vote}")]
+pub struct TransferRejected<NID: NodeId> {
+    pub target: NID,
+    pub vote: Vote<NID>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("the cluster is already undergoing a configuration change at log {membership_log_id:?}, committed log id: {committed:?}")]
@@ -447,6 +608,14 @@ pub struct LearnerIsLagging<NID: NodeId> {
     pub distance: u64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+#[error("live nodes {live:?} are not a quorum of the new membership {members:?}, can not change membership")]
+pub struct QuorumNotLive<NID: NodeId> {
+    pub members: BTreeSet<NID>,
+    pub live: BTreeSet<NID>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 #[error("not allowed to initialize due to current raft state: last_log_id: {last_log_id:?} vote: {vote}")]
@@ -29,6 +29,9 @@ pub(crate) struct Leader<NID: NodeId, QS: QuorumSet<NID>> {
 
     /// Tracks the replication progress and committed index
     pub(crate) progress: VecProgress<NID, ProgressEntry<NID>, Option<LogId<NID>>, QS>,
+
+    /// Whether it has been logged that committing is blocked by a log of a previous leader.
+    pub(crate) commit_blocked_logged: bool,
 }
 
 impl<NID, QS> Leader<NID, QS>
@@ -44,6 +47,7 @@ where
                 learner_ids,
                 ProgressEntry::empty(last_log_index.next_index()),
             ),
+            commit_blocked_logged: false,
         }
     }
 
@@ -24,7 +24,7 @@ mod entry;
 mod membership;
 mod node;
 mod progress;
-mod quorum;
+pub mod quorum;
 mod raft_types;
 mod replication;
 mod storage_error;
@@ -48,6 +48,7 @@ pub mod timer;
 pub mod versioned;
 
 #[cfg(test)] mod raft_state_test;
+#[cfg(test)] mod raft_types_test;
 
 pub use anyerror;
 pub use anyerror::AnyError;
@@ -83,6 +84,7 @@ pub(crate) use crate::raft_types::MetricsChangeFlags;
 pub use crate::raft_types::SnapshotId;
 pub use crate::raft_types::SnapshotSegmentId;
 pub use crate::raft_types::Update;
+pub use crate::storage::LogStats;
 pub use crate::storage::RaftLogReader;
 pub use crate::storage::RaftSnapshotBuilder;
 pub use crate::storage::RaftStorage;
@@ -99,8 +101,12 @@ pub use crate::storage_error::Violation;
 pub use crate::store_ext::StoreExt;
 pub use crate::store_wrapper::Wrapper;
 pub use crate::summary::MessageSummary;
+pub use crate::vote::LeaderChanged;
+pub use crate::vote::LeaderEvent;
 pub use crate::vote::LeaderId;
 pub use crate::vote::Vote;
+pub use crate::vote::VoteEvent;
+pub use crate::vote::VoteReason;
 
 /// A trait defining application specific data.
 ///
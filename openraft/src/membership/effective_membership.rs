@@ -1,5 +1,7 @@
 use std::collections::BTreeSet;
 use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
 
 use crate::entry::RaftEntry;
 use crate::membership::NodeRole;
@@ -54,6 +56,21 @@ where
     }
 }
 
+/// Display the id of the log that sets the membership and the membership, e.g., `1-2-5 {1,2,3}`.
+impl<NID, N> Display for EffectiveMembership<NID, N>
+where
+    N: Node,
+    NID: NodeId,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.log_id {
+            None => write!(f, "None")?,
+            Some(log_id) => write!(f, "{}", log_id)?,
+        }
+        write!(f, " {}", self.membership)
+    }
+}
+
 impl<NID, N> PartialEq for EffectiveMembership<NID, N>
 where
     N: Node,
@@ -129,8 +146,9 @@ where
         }
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn is_voter(&self, nid: &NID) -> bool {
+    /// Returns if a node is a voter in any of the configs, e.g., in either the old or the new config during a joint
+    /// consensus.
+    pub fn is_voter(&self, nid: &NID) -> bool {
         self.membership.is_voter(nid)
     }
 
@@ -140,13 +158,12 @@ where
     }
 
     /// Returns an Iterator of all learner node ids. Voters are not included.
-    #[allow(dead_code)]
-    pub(crate) fn learner_ids(&self) -> impl Iterator<Item = NID> + '_ {
+    pub fn learner_ids(&self) -> impl Iterator<Item = NID> + '_ {
         self.membership.learner_ids()
     }
 
     /// Returns if a voter or learner exists in this membership.
-    pub(crate) fn contains(&self, id: &NID) -> bool {
+    pub fn contains(&self, id: &NID) -> bool {
         self.membership.contains(id)
     }
 
@@ -167,6 +184,13 @@ where
     pub fn get_joint_config(&self) -> &Vec<Vec<NID>> {
         self.quorum_set.children()
     }
+
+    /// Returns the minimal number of voters that constitute a quorum.
+    ///
+    /// See [`Membership::quorum_size`].
+    pub fn quorum_size(&self) -> usize {
+        self.membership.quorum_size()
+    }
 }
 
 impl<NID, N> MessageSummary<EffectiveMembership<NID, N>> for EffectiveMembership<NID, N>
@@ -2,6 +2,8 @@ use maplit::btreeset;
 
 use crate::quorum::QuorumSet;
 use crate::EffectiveMembership;
+use crate::LeaderId;
+use crate::LogId;
 use crate::Membership;
 
 #[test]
@@ -32,3 +34,61 @@ fn test_effective_membership_majority() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_effective_membership_quorum_size() -> anyhow::Result<()> {
+    let m = EffectiveMembership::new(None, Membership::<u64, ()>::new(vec![btreeset! {1,2,3,4,5}], None));
+    assert_eq!(3, m.quorum_size());
+
+    let m = EffectiveMembership::new(
+        None,
+        Membership::<u64, ()>::new(vec![btreeset! {1,2,3,4,5}, btreeset! {6,7,8}], None),
+    );
+    assert_eq!(5, m.quorum_size());
+
+    Ok(())
+}
+
+#[test]
+fn test_effective_membership_display() -> anyhow::Result<()> {
+    let m = EffectiveMembership::new(None, Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], None));
+    assert_eq!("None {1,2,3}", m.to_string());
+
+    let m = EffectiveMembership::new(
+        Some(LogId::new(LeaderId::new(1, 2), 5)),
+        Membership::<u64, ()>::new(vec![btreeset! {1,2,3}, btreeset! {1,2,4}], None),
+    );
+    assert_eq!("1-2-5 {1,2,3}=>{1,2,4}", m.to_string());
+
+    Ok(())
+}
+
+#[test]
+fn test_effective_membership_voters_and_learners() -> anyhow::Result<()> {
+    let m = EffectiveMembership::new(
+        None,
+        Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4})),
+    );
+
+    assert!(m.is_voter(&1));
+    assert!(!m.is_voter(&4));
+    assert!(m.contains(&4));
+    assert!(!m.contains(&5));
+    assert_eq!(vec![1, 2, 3], m.voter_ids().collect::<Vec<_>>());
+    assert_eq!(vec![4], m.learner_ids().collect::<Vec<_>>());
+
+    // Node 4 is only in the new config of a joint config.
+    let m = EffectiveMembership::new(
+        None,
+        Membership::<u64, ()>::new(vec![btreeset! {1,2,3}, btreeset! {1,2,4}], Some(btreeset! {5})),
+    );
+
+    assert!(m.is_voter(&3));
+    assert!(m.is_voter(&4));
+    assert!(!m.is_voter(&5));
+    assert!(m.contains(&5));
+    assert_eq!(vec![1, 2, 3, 4], m.voter_ids().collect::<Vec<_>>());
+    assert_eq!(vec![5], m.learner_ids().collect::<Vec<_>>());
+
+    Ok(())
+}
@@ -1,10 +1,13 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::fmt::Formatter;
 
 use maplit::btreemap;
 
 use crate::membership::NodeRole;
 use crate::node::Node;
+use crate::quorum::majority_of;
 use crate::quorum::AsJoint;
 use crate::quorum::FindCoherent;
 use crate::quorum::Joint;
@@ -97,6 +100,42 @@ where
     }
 }
 
+/// Display the voter ids of every config, e.g., `{1,2,3}` or `{1,2,3}=>{1,2,4}` for a joint config, followed by the
+/// learner ids if there are any, e.g., `{1,2,3} learners:{4}`.
+impl<NID, N> Display for Membership<NID, N>
+where
+    N: Node,
+    NID: NodeId,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn write_ids<NID: NodeId>(f: &mut Formatter<'_>, ids: impl Iterator<Item = NID>) -> std::fmt::Result {
+            write!(f, "{{")?;
+            for (i, id) in ids.enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", id)?;
+            }
+            write!(f, "}}")
+        }
+
+        for (i, c) in self.configs.iter().enumerate() {
+            if i > 0 {
+                write!(f, "=>")?;
+            }
+            write_ids(f, c.iter().copied())?;
+        }
+
+        let mut learners = self.learner_ids().peekable();
+        if learners.peek().is_some() {
+            write!(f, " learners:")?;
+            write_ids(f, learners)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<NID, N> MessageSummary<Membership<NID, N>> for Membership<NID, N>
 where
     N: Node,
@@ -214,6 +253,20 @@ where
 
         Self::with_nodes(configs, nodes)
     }
+
+    /// Returns a new membership without the learner `node_id`.
+    ///
+    /// A voter can not be removed by this method, it has to be turned into a learner first.
+    pub(crate) fn remove_learner(&self, node_id: &NID) -> Self {
+        debug_assert!(!self.is_voter(node_id), "can not remove voter {} as learner", node_id);
+
+        let configs = self.configs.clone();
+
+        let mut nodes = self.nodes.clone();
+        nodes.remove(node_id);
+
+        Self::with_nodes(configs, nodes)
+    }
 }
 
 /// Membership API
@@ -234,8 +287,8 @@ where
         }
     }
 
-    /// Check if the given `NodeId` exists and is a voter.
-    pub(crate) fn is_voter(&self, node_id: &NID) -> bool {
+    /// Check if the given `NodeId` exists and is a voter in any of the configs.
+    pub fn is_voter(&self, node_id: &NID) -> bool {
         for c in self.configs.iter() {
             if c.contains(node_id) {
                 return true;
@@ -244,19 +297,18 @@ where
         false
     }
 
-    /// Returns an Iterator of all voter node ids. Learners are not included.
-    pub(crate) fn voter_ids(&self) -> impl Iterator<Item = NID> {
+    /// Returns an Iterator of all voter node ids, i.e., the union of all configs. Learners are not included.
+    pub fn voter_ids(&self) -> impl Iterator<Item = NID> {
         self.configs.as_joint().ids()
     }
 
     /// Returns an Iterator of all learner node ids. Voters are not included.
-    #[allow(dead_code)]
-    pub(crate) fn learner_ids(&self) -> impl Iterator<Item = NID> + '_ {
+    pub fn learner_ids(&self) -> impl Iterator<Item = NID> + '_ {
         self.nodes.keys().filter(|x| !self.is_voter(x)).copied()
     }
 
     /// Returns if a voter or learner exists in this membership.
-    pub(crate) fn contains(&self, node_id: &NID) -> bool {
+    pub fn contains(&self, node_id: &NID) -> bool {
         self.nodes.contains_key(node_id)
     }
 
@@ -324,6 +376,42 @@ where
         Membership::with_nodes(config, nodes)
     }
 
+    /// Returns the minimal number of voters that constitute a quorum.
+    ///
+    /// For a uniform config of `n` voters it is the majority `n / 2 + 1`.
+    /// For a joint config, a quorum has to be a majority of every config, and a voter shared by several configs
+    /// counts for all of them.
+    pub fn quorum_size(&self) -> usize {
+        // Number of votes every config still requires.
+        let mut needed =
+            self.configs.iter().map(|c| if c.is_empty() { 0 } else { majority_of(c.len()) }).collect::<Vec<_>>();
+
+        let mut chosen = BTreeSet::new();
+
+        // Greedily choose the voter that contributes to the most configs that still require votes.
+        // For a joint of two configs, which is all a membership change produces, this results in the minimal size.
+        loop {
+            let gain =
+                |id: &NID| self.configs.iter().zip(needed.iter()).filter(|(c, n)| **n > 0 && c.contains(id)).count();
+
+            let best = self.voter_ids().filter(|id| !chosen.contains(id)).max_by_key(|id| gain(id));
+
+            let id = match best {
+                Some(id) if gain(&id) > 0 => id,
+                _ => break,
+            };
+
+            for (c, n) in self.configs.iter().zip(needed.iter_mut()) {
+                if *n > 0 && c.contains(&id) {
+                    *n -= 1;
+                }
+            }
+            chosen.insert(id);
+        }
+
+        chosen.len()
+    }
+
     /// Build a QuorumSet from current joint config
     pub(crate) fn to_quorum_set(&self) -> Joint<NID, Vec<NID>, Vec<Vec<NID>>> {
         let mut qs = vec![];
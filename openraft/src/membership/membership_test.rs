@@ -5,6 +5,7 @@ use std::fmt::Formatter;
 use maplit::btreemap;
 use maplit::btreeset;
 
+use crate::quorum::QuorumSet;
 use crate::Membership;
 use crate::MessageSummary;
 
@@ -151,6 +152,37 @@ fn test_membership_add_learner() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_membership_remove_learner() -> anyhow::Result<()> {
+    let node = |s: &str| TestNode {
+        addr: s.to_string(),
+        data: Default::default(),
+    };
+
+    let m_1_2_3 = Membership::<u64, TestNode>::with_nodes(
+        vec![btreeset! {1}, btreeset! {2}],
+        btreemap! {1=>node("1"), 2=>node("2"), 3=>node("3")},
+    );
+
+    // Remove a learner that does not present has no effect.
+
+    let res = m_1_2_3.remove_learner(&4);
+    assert_eq!(m_1_2_3, res);
+
+    // Success to remove a learner
+
+    let m_1_2 = m_1_2_3.remove_learner(&3);
+    assert_eq!(
+        Membership::<u64, TestNode>::with_nodes(
+            vec![btreeset! {1}, btreeset! {2}],
+            btreemap! {1=>node("1"), 2=>node("2")}
+        ),
+        m_1_2
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_membership_extend_nodes() -> anyhow::Result<()> {
     let node = |s: &str| TestNode {
@@ -263,3 +295,46 @@ fn test_membership_next_safe_with_nodes() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_membership_quorum_size() -> anyhow::Result<()> {
+    let m = |configs| Membership::<u64, ()>::new(configs, None);
+
+    assert_eq!(2, m(vec![btreeset! {1,2,3}]).quorum_size());
+    assert_eq!(3, m(vec![btreeset! {1,2,3,4}]).quorum_size());
+    assert_eq!(3, m(vec![btreeset! {1,2,3,4,5}]).quorum_size());
+
+    // Learners do not count
+    assert_eq!(
+        2,
+        Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4,5})).quorum_size()
+    );
+
+    // Joint config requires a majority of both halves
+
+    let joint = m(vec![btreeset! {1,2,3}, btreeset! {4,5,6}]);
+    assert_eq!(4, joint.quorum_size());
+    assert!(!joint.to_quorum_set().is_quorum([1, 2, 4].iter()));
+    assert!(joint.to_quorum_set().is_quorum([1, 2, 4, 5].iter()));
+
+    // A shared voter counts for both halves
+    assert_eq!(3, m(vec![btreeset! {1,2,3}, btreeset! {3,4,5}]).quorum_size());
+    assert_eq!(4, m(vec![btreeset! {1,2,3}, btreeset! {3,4,5,6,7}]).quorum_size());
+    assert_eq!(2, m(vec![btreeset! {1,2,3}, btreeset! {1,2,3}]).quorum_size());
+
+    Ok(())
+}
+
+#[test]
+fn test_membership_display() -> anyhow::Result<()> {
+    let m = Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], None);
+    assert_eq!("{1,2,3}", m.to_string());
+
+    let m = Membership::<u64, ()>::new(vec![btreeset! {1,2,3}, btreeset! {1,2,4}], None);
+    assert_eq!("{1,2,3}=>{1,2,4}", m.to_string());
+
+    let m = Membership::<u64, ()>::new(vec![btreeset! {1,2,3}], Some(btreeset! {4,5}));
+    assert_eq!("{1,2,3} learners:{4,5}", m.to_string());
+
+    Ok(())
+}
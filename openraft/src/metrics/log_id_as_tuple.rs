@@ -0,0 +1,80 @@
+//! A compact serde form of [`LogId`] for metrics: `[term, index]`.
+//!
+//! The default serde form of `LogId` is a nested object carrying the full leader id, which is more
+//! than a dashboard or a metrics collector needs. Use this module with `#[serde(with = "...")]` on
+//! a `LogId` field to emit it as a two element array instead:
+//!
+//! ```ignore
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Exported {
+//!     #[serde(with = "openraft::metrics::log_id_as_tuple")]
+//!     last_log: LogId<u64>,
+//!
+//!     #[serde(with = "openraft::metrics::log_id_as_tuple::option")]
+//!     last_applied: Option<LogId<u64>>,
+//! }
+//! ```
+//!
+//! The node id of the leader is not part of this form. When deserializing, it is set to
+//! `NID::default()`, thus a round trip does not preserve it.
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::LeaderId;
+use crate::LogId;
+use crate::NodeId;
+
+pub fn serialize<NID, S>(log_id: &LogId<NID>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    NID: NodeId,
+    S: Serializer,
+{
+    (log_id.leader_id.term, log_id.index).serialize(serializer)
+}
+
+pub fn deserialize<'de, NID, D>(deserializer: D) -> Result<LogId<NID>, D::Error>
+where
+    NID: NodeId,
+    D: Deserializer<'de>,
+{
+    let (term, index) = <(u64, u64)>::deserialize(deserializer)?;
+    Ok(LogId {
+        leader_id: LeaderId::new(term, NID::default()),
+        index,
+    })
+}
+
+/// The same `[term, index]` form for an `Option<LogId>`, where `None` is serialized as `null`.
+pub mod option {
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use crate::LeaderId;
+    use crate::LogId;
+    use crate::NodeId;
+
+    pub fn serialize<NID, S>(log_id: &Option<LogId<NID>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        NID: NodeId,
+        S: Serializer,
+    {
+        log_id.map(|x| (x.leader_id.term, x.index)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, NID, D>(deserializer: D) -> Result<Option<LogId<NID>>, D::Error>
+    where
+        NID: NodeId,
+        D: Deserializer<'de>,
+    {
+        let t = <Option<(u64, u64)>>::deserialize(deserializer)?;
+        Ok(t.map(|(term, index)| LogId {
+            leader_id: LeaderId::new(term, NID::default()),
+            index,
+        }))
+    }
+}
@@ -0,0 +1,34 @@
+use crate::LeaderId;
+use crate::LogId;
+
+#[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct Foo {
+    #[serde(with = "crate::metrics::log_id_as_tuple")]
+    log_id: LogId<u64>,
+
+    #[serde(with = "crate::metrics::log_id_as_tuple::option")]
+    opt: Option<LogId<u64>>,
+}
+
+#[test]
+fn test_log_id_as_tuple() -> anyhow::Result<()> {
+    let foo = Foo {
+        log_id: LogId::new(LeaderId::new(3, 0), 10),
+        opt: None,
+    };
+
+    let s = serde_json::to_string(&foo)?;
+    assert_eq!(r#"{"log_id":[3,10],"opt":null}"#, s);
+    assert_eq!(foo, serde_json::from_str(&s)?);
+
+    let foo = Foo {
+        log_id: LogId::new(LeaderId::new(3, 0), 10),
+        opt: Some(LogId::new(LeaderId::new(2, 0), 5)),
+    };
+
+    let s = serde_json::to_string(&foo)?;
+    assert_eq!(r#"{"log_id":[3,10],"opt":[2,5]}"#, s);
+    assert_eq!(foo, serde_json::from_str(&s)?);
+
+    Ok(())
+}
@@ -7,16 +7,19 @@
 //! Metrics are observed on a running Raft node via the `Raft::metrics()` method, which will
 //! return a stream of metrics.
 
+#[cfg(feature = "serde")] pub mod log_id_as_tuple;
 mod raft_metrics;
 mod replication_metrics;
 mod wait;
 
+#[cfg(all(test, feature = "serde"))] mod log_id_as_tuple_test;
 #[cfg(test)] mod replication_metrics_test;
 #[cfg(test)] mod wait_test;
 
 pub use raft_metrics::RaftMetrics;
 pub use replication_metrics::ReplicationMetrics;
 pub use replication_metrics::ReplicationTargetMetrics;
+pub(crate) use replication_metrics::UpdateLeaderLastLog;
 pub(crate) use replication_metrics::UpdateMatchedLogId;
 pub use wait::Wait;
 pub use wait::WaitError;
@@ -5,6 +5,8 @@ use crate::error::Fatal;
 use crate::membership::EffectiveMembership;
 use crate::metrics::ReplicationMetrics;
 use crate::node::Node;
+use crate::storage::LogStats;
+use crate::storage::SnapshotMeta;
 use crate::summary::MessageSummary;
 use crate::versioned::Versioned;
 use crate::LogId;
@@ -39,6 +41,36 @@ where
     /// If there is no snapshot, it is (0,0).
     pub snapshot: Option<LogId<NID>>,
 
+    /// The meta of the last snapshot built or installed on this node, or `None` if there is no snapshot.
+    pub snapshot_meta: Option<SnapshotMeta<NID, N>>,
+
+    /// The number of snapshots this node has sent to followers as a leader.
+    pub snapshots_sent: u64,
+
+    /// The number of snapshots this node has received from a leader.
+    pub snapshots_received: u64,
+
+    /// The number of times a replication target reported a term more than 1 greater than the term of this node.
+    ///
+    /// It usually indicates a split-brain or a misbehaving node.
+    pub term_jumps: u64,
+
+    /// The number of bytes received so far of the snapshot that is being streamed from the leader.
+    ///
+    /// It is `None` if no snapshot is being received. The total size is unknown until the last chunk is received.
+    pub snapshot_received_bytes: Option<u64>,
+
+    /// The number and size of the log entries held in storage, see `RaftStorage::log_stats`.
+    ///
+    /// It is `None` until the storage is polled for the first time.
+    pub log_stats: Option<LogStats>,
+
+    /// The number of times a leader did not advance the commit index, because the log accepted by a quorum was
+    /// proposed by a previous leader.
+    ///
+    /// It increases right after a leadership change, until a log of the new leader is accepted by a quorum.
+    pub commit_blocked_by_term: u64,
+
     // ---
     // --- cluster ---
     // ---
@@ -51,6 +83,11 @@ where
     /// The current membership config of the cluster.
     pub membership_config: Arc<EffectiveMembership<NID, N>>,
 
+    /// Whether the current membership config is a joint config, i.e., a membership change is in progress.
+    ///
+    /// A new membership change should not be started until it becomes `false`.
+    pub in_joint_consensus: bool,
+
     // ---
     // --- replication ---
     // ---
@@ -64,7 +101,7 @@ where
     N: Node,
 {
     fn summary(&self) -> String {
-        format!("Metrics{{id:{},{:?}, term:{}, last_log:{:?}, last_applied:{:?}, leader:{:?}, membership:{}, snapshot:{:?}, replication:{}",
+        format!("Metrics{{id:{},{:?}, term:{}, last_log:{:?}, last_applied:{:?}, leader:{:?}, membership:{}, joint:{}, snapshot:{:?}, snapshots_sent:{}, snapshots_received:{}, term_jumps:{}, snapshot_received_bytes:{:?}, log_stats:{:?}, replication:{}",
                 self.id,
                 self.state,
                 self.current_term,
@@ -72,7 +109,13 @@ where
                 self.last_applied,
                 self.current_leader,
                 self.membership_config.summary(),
+                self.in_joint_consensus,
                 self.snapshot,
+                self.snapshots_sent,
+                self.snapshots_received,
+                self.term_jumps,
+                self.snapshot_received_bytes,
+                self.log_stats,
                 self.replication.as_ref().map(|x| x.summary()).unwrap_or_default(),
         )
     }
@@ -93,7 +136,15 @@ where
             last_applied: None,
             current_leader: None,
             membership_config: Arc::new(EffectiveMembership::default()),
+            in_joint_consensus: false,
             snapshot: None,
+            snapshot_meta: None,
+            snapshots_sent: 0,
+            snapshots_received: 0,
+            term_jumps: 0,
+            snapshot_received_bytes: None,
+            log_stats: None,
+            commit_blocked_by_term: 0,
             replication: None,
         }
     }
@@ -2,6 +2,9 @@ use std::collections::BTreeMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use crate::versioned::Update;
 use crate::versioned::UpdateError;
@@ -16,11 +19,34 @@ use crate::NodeId;
 pub struct ReplicationMetrics<NID: NodeId> {
     /// Replication metrics of all known replication target: voters and learners
     pub replication: BTreeMap<NID, ReplicationTargetMetrics<NID>>,
+
+    /// The last log id on the leader.
+    ///
+    /// The replication lag of a target is `leader_last_log.index - matched.index`, see
+    /// [`ReplicationMetrics::lag()`].
+    ///
+    /// It is updated along with a metrics report, thus it may be behind the leader for at most
+    /// `Config::metrics_report_min_interval`.
+    pub leader_last_log: Option<LogId<NID>>,
+}
+
+impl<NID: NodeId> ReplicationMetrics<NID> {
+    /// The number of logs the leader has but the target has not yet acknowledged.
+    ///
+    /// It returns `None` if the target is not replicated by this leader.
+    pub fn lag(&self, target: &NID) -> Option<u64> {
+        let matched = self.replication.get(target)?.matched();
+        let last_index = self.leader_last_log.map(|x| x.index).unwrap_or_default();
+        Some(last_index.saturating_sub(matched.index))
+    }
 }
 
 impl<NID: NodeId> MessageSummary<ReplicationMetrics<NID>> for ReplicationMetrics<NID> {
     fn summary(&self) -> String {
         let mut res = vec!["LeaderMetrics{".to_string()];
+        if let Some(last) = &self.leader_last_log {
+            res.push(format!("last:{}, ", last));
+        }
         for (i, (k, v)) in self.replication.iter().enumerate() {
             if i > 0 {
                 res.push(", ".to_string());
@@ -37,6 +63,9 @@ impl<NID: NodeId> MessageSummary<ReplicationMetrics<NID>> for ReplicationMetrics
 pub(crate) struct UpdateMatchedLogId<NID: NodeId> {
     pub target: NID,
     pub matched: LogId<NID>,
+
+    /// When the target responded successfully.
+    pub rpc_ok_at: SystemTime,
 }
 
 impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateMatchedLogId<NID> {
@@ -46,6 +75,7 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateMatchedLogId<NID> {
 
         if target_metrics.matched_leader_id == self.matched.leader_id {
             target_metrics.matched_index.store(self.matched.index, Ordering::Relaxed);
+            target_metrics.last_rpc_ok_at_ms.store(to_millis(self.rpc_ok_at), Ordering::Relaxed);
             return Ok(());
         }
 
@@ -57,10 +87,29 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateMatchedLogId<NID> {
         to.replication.insert(self.target, ReplicationTargetMetrics {
             matched_leader_id: self.matched.leader_id,
             matched_index: AtomicU64::new(self.matched.index),
+            last_rpc_ok_at_ms: AtomicU64::new(to_millis(self.rpc_ok_at)),
         });
     }
 }
 
+/// Update the last log id of the leader in `LeaderMetrics`.
+pub(crate) struct UpdateLeaderLastLog<NID: NodeId> {
+    pub last_log_id: Option<LogId<NID>>,
+}
+
+impl<NID: NodeId> Update<ReplicationMetrics<NID>> for UpdateLeaderLastLog<NID> {
+    fn apply_in_place(&self, to: &Arc<ReplicationMetrics<NID>>) -> Result<(), UpdateError> {
+        if to.leader_last_log == self.last_log_id {
+            return Ok(());
+        }
+        Err(UpdateError::CanNotUpdateInPlace)
+    }
+
+    fn apply_mut(&self, to: &mut ReplicationMetrics<NID>) {
+        to.leader_last_log = self.last_log_id;
+    }
+}
+
 /// Remove one replication metrics in `LeaderMetrics.replication`.
 pub(crate) struct RemoveTarget<NID: NodeId> {
     pub target: NID,
@@ -82,6 +131,11 @@ impl<NID: NodeId> Update<ReplicationMetrics<NID>> for RemoveTarget<NID> {
 pub struct ReplicationTargetMetrics<NID: NodeId> {
     pub(crate) matched_leader_id: LeaderId<NID>,
     pub(crate) matched_index: AtomicU64,
+
+    /// Milliseconds since the unix epoch when the target last responded successfully; 0 means never.
+    ///
+    /// It is stored as an integer so that it can be updated in place and serialized.
+    pub(crate) last_rpc_ok_at_ms: AtomicU64,
 }
 
 impl<NID: NodeId> Clone for ReplicationTargetMetrics<NID> {
@@ -89,10 +143,13 @@ impl<NID: NodeId> Clone for ReplicationTargetMetrics<NID> {
         Self {
             matched_leader_id: self.matched_leader_id,
             matched_index: AtomicU64::new(self.matched_index.load(Ordering::Relaxed)),
+            last_rpc_ok_at_ms: AtomicU64::new(self.last_rpc_ok_at_ms.load(Ordering::Relaxed)),
         }
     }
 }
 
+/// Two metrics are equal if they have the same matched log id. The time of last successful RPC is not compared,
+/// because it changes whenever the target responds and is not useful for comparing replication progress.
 impl<NID: NodeId> PartialEq for ReplicationTargetMetrics<NID> {
     fn eq(&self, other: &Self) -> bool {
         self.matched_leader_id == other.matched_leader_id
@@ -107,6 +164,7 @@ impl<NID: NodeId> ReplicationTargetMetrics<NID> {
         Self {
             matched_leader_id: log_id.leader_id,
             matched_index: AtomicU64::new(log_id.index),
+            last_rpc_ok_at_ms: AtomicU64::new(0),
         }
     }
 
@@ -117,6 +175,24 @@ impl<NID: NodeId> ReplicationTargetMetrics<NID> {
             index,
         }
     }
+
+    /// When the target last responded successfully to the leader.
+    ///
+    /// It returns `None` if the target has not yet responded since the leader established.
+    ///
+    /// A response that does not change the matched log id, e.g., to a heartbeat, updates it in place without
+    /// triggering a metrics report.
+    pub fn last_rpc_ok_at(&self) -> Option<SystemTime> {
+        let ms = self.last_rpc_ok_at_ms.load(Ordering::Relaxed);
+        if ms == 0 {
+            return None;
+        }
+        Some(UNIX_EPOCH + Duration::from_millis(ms))
+    }
+}
+
+fn to_millis(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
 }
 
 impl<NID: NodeId> MessageSummary<ReplicationTargetMetrics<NID>> for ReplicationTargetMetrics<NID> {
@@ -1,4 +1,8 @@
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
 use crate::metrics::ReplicationMetrics;
+use crate::metrics::UpdateLeaderLastLog;
 use crate::metrics::UpdateMatchedLogId;
 use crate::versioned::Updatable;
 use crate::versioned::Versioned;
@@ -10,6 +14,7 @@ use crate::MessageSummary;
 fn test_versioned() -> anyhow::Result<()> {
     let mut a = Versioned::new(ReplicationMetrics::<u64> {
         replication: Default::default(),
+        leader_last_log: None,
     });
 
     assert_eq!("{ver:0, LeaderMetrics{}}", a.summary());
@@ -19,6 +24,7 @@ fn test_versioned() -> anyhow::Result<()> {
     a.update(UpdateMatchedLogId {
         target: 1,
         matched: LogId::new(LeaderId::new(1, 2), 3),
+        rpc_ok_at: UNIX_EPOCH,
     });
 
     assert_eq!("{ver:1, LeaderMetrics{1:1-2-3}}", a.summary());
@@ -31,6 +37,7 @@ fn test_versioned() -> anyhow::Result<()> {
     b1.update(UpdateMatchedLogId {
         target: 1,
         matched: LogId::new(LeaderId::new(1, 2), 5),
+        rpc_ok_at: UNIX_EPOCH,
     });
     assert_eq!("{ver:1, LeaderMetrics{1:1-2-5}}", a.summary());
     assert_eq!("{ver:2, LeaderMetrics{1:1-2-5}}", b1.summary());
@@ -41,6 +48,7 @@ fn test_versioned() -> anyhow::Result<()> {
     b1.update(UpdateMatchedLogId {
         target: 2,
         matched: LogId::new(LeaderId::new(1, 2), 5),
+        rpc_ok_at: UNIX_EPOCH,
     });
     assert_eq!("{ver:1, LeaderMetrics{1:1-2-5}}", a.summary());
     assert_eq!("{ver:3, LeaderMetrics{1:1-2-5, 2:1-2-5}}", b1.summary());
@@ -50,10 +58,12 @@ fn test_versioned() -> anyhow::Result<()> {
     a.update(UpdateMatchedLogId {
         target: 1,
         matched: LogId::new(LeaderId::new(1, 2), 5),
+        rpc_ok_at: UNIX_EPOCH,
     });
     a.update(UpdateMatchedLogId {
         target: 2,
         matched: LogId::new(LeaderId::new(1, 2), 5),
+        rpc_ok_at: UNIX_EPOCH,
     });
     assert_eq!("{ver:3, LeaderMetrics{1:1-2-5, 2:1-2-5}}", a.summary());
     assert_eq!("{ver:3, LeaderMetrics{1:1-2-5, 2:1-2-5}}", b1.summary());
@@ -65,6 +75,7 @@ fn test_versioned() -> anyhow::Result<()> {
     b2.update(UpdateMatchedLogId {
         target: 2,
         matched: LogId::new(LeaderId::new(1, 2), 9),
+        rpc_ok_at: UNIX_EPOCH,
     });
     assert_eq!("{ver:3, LeaderMetrics{1:1-2-5, 2:1-2-9}}", b1.summary());
     assert_eq!("{ver:4, LeaderMetrics{1:1-2-5, 2:1-2-9}}", b2.summary());
@@ -75,6 +86,7 @@ fn test_versioned() -> anyhow::Result<()> {
     b1.update(UpdateMatchedLogId {
         target: 2,
         matched: LogId::new(LeaderId::new(1, 2), 9),
+        rpc_ok_at: UNIX_EPOCH,
     });
     assert_eq!("{ver:4, LeaderMetrics{1:1-2-5, 2:1-2-9}}", b1.summary());
     assert_eq!("{ver:4, LeaderMetrics{1:1-2-5, 2:1-2-9}}", b2.summary());
@@ -87,11 +99,13 @@ fn test_versioned() -> anyhow::Result<()> {
 fn test_versioned_methods() -> anyhow::Result<()> {
     let mut a = Versioned::new(ReplicationMetrics::<u64> {
         replication: Default::default(),
+        leader_last_log: None,
     });
 
     a.update(UpdateMatchedLogId {
         target: 1,
         matched: LogId::new(LeaderId::new(1, 2), 3),
+        rpc_ok_at: UNIX_EPOCH,
     });
 
     assert_eq!("{ver:1, LeaderMetrics{1:1-2-3}}", a.summary());
@@ -101,3 +115,45 @@ fn test_versioned_methods() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_lag_and_last_rpc_ok_at() -> anyhow::Result<()> {
+    let mut a = Versioned::new(ReplicationMetrics::<u64> {
+        replication: Default::default(),
+        leader_last_log: None,
+    });
+
+    assert_eq!(None, a.data().lag(&1));
+
+    let t = UNIX_EPOCH + Duration::from_millis(1_000);
+    a.update(UpdateMatchedLogId {
+        target: 1,
+        matched: LogId::new(LeaderId::new(1, 2), 3),
+        rpc_ok_at: t,
+    });
+    a.update(UpdateLeaderLastLog {
+        last_log_id: Some(LogId::new(LeaderId::new(1, 2), 10)),
+    });
+
+    assert_eq!("{ver:2, LeaderMetrics{last:1-2-10, 1:1-2-3}}", a.summary());
+    assert_eq!(Some(7), a.data().lag(&1));
+    assert_eq!(Some(t), a.data().replication[&1].last_rpc_ok_at());
+
+    // Updating in place also updates the timestamp.
+
+    let t2 = UNIX_EPOCH + Duration::from_millis(2_000);
+    a.update(UpdateMatchedLogId {
+        target: 1,
+        matched: LogId::new(LeaderId::new(1, 2), 10),
+        rpc_ok_at: t2,
+    });
+    assert_eq!(Some(0), a.data().lag(&1));
+    assert_eq!(Some(t2), a.data().replication[&1].last_rpc_ok_at());
+
+    // A target that never responded.
+
+    let m = crate::metrics::ReplicationTargetMetrics::new(LogId::new(LeaderId::new(1, 2), 3));
+    assert_eq!(None, m.last_rpc_ok_at());
+
+    Ok(())
+}
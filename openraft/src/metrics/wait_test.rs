@@ -190,8 +190,16 @@ where
             None,
             Membership::new(vec![btreeset! {}], None),
         )),
+        in_joint_consensus: false,
 
         snapshot: None,
+        snapshot_meta: None,
+        snapshots_sent: 0,
+        snapshots_received: 0,
+        term_jumps: 0,
+        snapshot_received_bytes: None,
+        log_stats: None,
+        commit_blocked_by_term: 0,
         replication: None,
     };
     let (tx, rx) = watch::channel(init.clone());
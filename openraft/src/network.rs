@@ -3,26 +3,36 @@
 use std::error::Error;
 use std::fmt::Formatter;
 
+use anyerror::AnyError;
 use async_trait::async_trait;
 
 use crate::error::AppendEntriesError;
 use crate::error::InstallSnapshotError;
+use crate::error::NetworkError;
 use crate::error::RPCError;
 use crate::error::VoteError;
 use crate::raft::AppendEntriesRequest;
 use crate::raft::AppendEntriesResponse;
 use crate::raft::InstallSnapshotRequest;
 use crate::raft::InstallSnapshotResponse;
+use crate::raft::TimeoutNowRequest;
+use crate::raft::TimeoutNowResponse;
 use crate::raft::VoteRequest;
 use crate::raft::VoteResponse;
 use crate::RaftTypeConfig;
 
+/// The types of RPC a raft node sends.
+///
+/// More types may be added in future, thus it is not exhaustive.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[non_exhaustive]
 pub enum RPCTypes {
     Vote,
+    PreVote,
     AppendEntries,
     InstallSnapshot,
+    TimeoutNow,
 }
 
 impl std::fmt::Display for RPCTypes {
@@ -51,6 +61,31 @@ where C: RaftTypeConfig
         rpc: AppendEntriesRequest<C>,
     ) -> Result<AppendEntriesResponse<C::NodeId>, RPCError<C::NodeId, C::Node, AppendEntriesError<C::NodeId>>>;
 
+    /// Send several AppendEntries RPCs to the target Raft node in one round trip.
+    ///
+    /// The requests carry consecutive logs and must be handled by the target in order. The responses are returned in
+    /// the same order. A transport may stop at the first response that is not a success and return the responses
+    /// received so far.
+    ///
+    /// It is used instead of `send_append_entries()` when `Config::max_append_batches` is greater than 1.
+    /// The default implementation sends the requests one by one with `send_append_entries()`.
+    async fn send_append_entries_batch(
+        &mut self,
+        rpcs: Vec<AppendEntriesRequest<C>>,
+    ) -> Result<Vec<AppendEntriesResponse<C::NodeId>>, RPCError<C::NodeId, C::Node, AppendEntriesError<C::NodeId>>>
+    {
+        let mut resps = Vec::with_capacity(rpcs.len());
+        for rpc in rpcs {
+            let resp = self.send_append_entries(rpc).await?;
+            let is_success = resp.is_success();
+            resps.push(resp);
+            if !is_success {
+                break;
+            }
+        }
+        Ok(resps)
+    }
+
     /// Send an InstallSnapshot RPC to the target Raft node (§7).
     async fn send_install_snapshot(
         &mut self,
@@ -62,6 +97,30 @@ where C: RaftTypeConfig
         &mut self,
         rpc: VoteRequest<C::NodeId>,
     ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>>;
+
+    /// Send a pre-vote RPC to the target Raft node, which should be handled by `Raft::pre_vote()`.
+    ///
+    /// It is only sent if `Config::enable_pre_vote` is on.
+    /// The default implementation returns an error, i.e., pre-vote is never granted through this network.
+    async fn send_pre_vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+    ) -> Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>> {
+        let _ = rpc;
+        Err(NetworkError::new(&AnyError::error("pre-vote is not supported by this network")).into())
+    }
+
+    /// Send a timeout-now RPC to the target Raft node, which should be handled by `Raft::timeout_now()`.
+    ///
+    /// It is only sent by `Raft::step_down()`.
+    /// The default implementation returns an error, i.e., leadership can not be transferred through this network.
+    async fn send_timeout_now(
+        &mut self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>> {
+        let _ = rpc;
+        Err(NetworkError::new(&AnyError::error("timeout-now is not supported by this network")).into())
+    }
 }
 
 /// A trait defining the interface for a Raft network factory to create connections between cluster members.
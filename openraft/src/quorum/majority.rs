@@ -0,0 +1,15 @@
+/// Returns the minimum number of nodes that form a majority quorum of `n` nodes.
+///
+/// E.g., 2 of 3 nodes, 3 of 4 nodes, or the only node of a single node cluster.
+pub fn majority_of(n: usize) -> usize {
+    n / 2 + 1
+}
+
+/// Returns `true` if `present` nodes form a majority quorum of `total` nodes.
+///
+/// A quorum of an even number of nodes requires more than half of them, e.g., 3 of 4 nodes: 2 of 4 nodes are not a
+/// quorum, because the other 2 nodes could form another group of the same size.
+/// No number of nodes is a quorum of 0 nodes.
+pub fn is_quorum(present: usize, total: usize) -> bool {
+    total > 0 && present >= majority_of(total)
+}
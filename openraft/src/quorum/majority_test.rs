@@ -0,0 +1,40 @@
+use crate::quorum::is_quorum;
+use crate::quorum::majority_of;
+
+#[test]
+fn test_majority_of() -> anyhow::Result<()> {
+    assert_eq!(1, majority_of(0));
+    assert_eq!(1, majority_of(1));
+    assert_eq!(2, majority_of(2));
+    assert_eq!(2, majority_of(3));
+    assert_eq!(3, majority_of(4));
+    assert_eq!(3, majority_of(5));
+
+    Ok(())
+}
+
+#[test]
+fn test_is_quorum() -> anyhow::Result<()> {
+    // Empty set of nodes
+    assert!(!is_quorum(0, 0));
+    assert!(!is_quorum(1, 0));
+
+    // Single node
+    assert!(!is_quorum(0, 1));
+    assert!(is_quorum(1, 1));
+
+    // Odd total
+    assert!(!is_quorum(1, 3));
+    assert!(is_quorum(2, 3));
+    assert!(is_quorum(3, 3));
+    assert!(!is_quorum(2, 5));
+    assert!(is_quorum(3, 5));
+
+    // Even total: half is not a quorum
+    assert!(!is_quorum(1, 2));
+    assert!(is_quorum(2, 2));
+    assert!(!is_quorum(2, 4));
+    assert!(is_quorum(3, 4));
+
+    Ok(())
+}
@@ -1,11 +1,15 @@
 //! A quorum is a set of nodes a vote request or append-entries request has to contact to.
 //! The most common quorum is **majority**.
 //! A quorum set is a collection of quorums, e.g. the quorum set of majority of `{a,b,c}` is `{a,b}, {b,c}, {a,c}`.
+//!
+//! The majority helpers [`majority_of`] and [`is_quorum`] are public, for applications that implement their own
+//! checks about quorums, e.g., whether enough nodes are live before changing membership.
 
 mod coherent;
 mod coherent_impl;
 mod joint;
 mod joint_impl;
+mod majority;
 mod quorum_set;
 mod quorum_set_impl;
 
@@ -14,10 +18,13 @@ mod quorum_set_impl;
 mod bench;
 
 #[cfg(test)] mod coherent_test;
+#[cfg(test)] mod majority_test;
 #[cfg(test)] mod quorum_set_test;
 
 pub(crate) use coherent::Coherent;
 pub(crate) use coherent::FindCoherent;
 pub(crate) use joint::AsJoint;
 pub(crate) use joint::Joint;
+pub use majority::is_quorum;
+pub use majority::majority_of;
 pub(crate) use quorum_set::QuorumSet;
@@ -7,6 +7,10 @@ use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 
+use futures::future::BoxFuture;
+use futures::Future;
+use futures::FutureExt;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::sync::watch;
@@ -18,22 +22,32 @@ use tracing::Level;
 use crate::config::Config;
 use crate::config::RuntimeConfig;
 use crate::core::replication_lag;
+use crate::core::EventSenders;
 use crate::core::Expectation;
 use crate::core::RaftCore;
+use crate::core::ServerState;
 use crate::core::SnapshotResult;
 use crate::core::Tick;
 use crate::core::TickHandle;
 use crate::error::AddLearnerError;
 use crate::error::AppendEntriesError;
+use crate::error::ChangeMembershipError;
 use crate::error::CheckIsLeaderError;
 use crate::error::ClientWriteError;
+use crate::error::ClientWriteTimeout;
+use crate::error::ElectNowError;
 use crate::error::Fatal;
+use crate::error::ForwardToLeader;
 use crate::error::InitializeError;
 use crate::error::InstallSnapshotError;
+use crate::error::LearnerNotFound;
+use crate::error::PurgeLogError;
+use crate::error::StepDownError;
 use crate::error::VoteError;
 use crate::membership::IntoNodes;
 use crate::metrics::RaftMetrics;
 use crate::metrics::Wait;
+use crate::metrics::WaitError;
 use crate::node::Node;
 use crate::storage::Snapshot;
 use crate::AppData;
@@ -41,7 +55,10 @@ use crate::AppDataResponse;
 use crate::ChangeMembers;
 use crate::Entry;
 use crate::EntryPayload;
+use crate::LeaderChanged;
+use crate::LeaderEvent;
 use crate::LogId;
+use crate::LogIdOptionExt;
 use crate::Membership;
 use crate::MessageSummary;
 use crate::NodeId;
@@ -50,6 +67,7 @@ use crate::RaftState;
 use crate::RaftStorage;
 use crate::SnapshotMeta;
 use crate::Vote;
+use crate::VoteEvent;
 
 /// Configuration of types used by the [`Raft`] core engine.
 ///
@@ -118,6 +136,18 @@ macro_rules! declare_raft_types {
     };
 }
 
+/// The max number of buffered vote events for a receiver of [`Raft::vote_events()`].
+const VOTE_EVENT_CHANNEL_SIZE: usize = 1024;
+
+/// The max number of buffered leader events for a receiver of [`Raft::leader_events()`] or
+/// [`Raft::leader_changes()`].
+const LEADER_EVENT_CHANNEL_SIZE: usize = 1024;
+
+/// A callback that is called with every log entry applied to the state machine and the result of applying it.
+///
+/// See [`Raft::with_apply_hook()`].
+pub type ApplyHook<C> = Box<dyn FnMut(&Entry<C>, &<C as RaftTypeConfig>::R) + Send + 'static>;
+
 /// The running state of RaftCore
 enum CoreState<NID>
 where NID: NodeId
@@ -136,6 +166,10 @@ struct RaftInner<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>>
     tick_handle: TickHandle,
     tx_api: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
     rx_metrics: watch::Receiver<RaftMetrics<C::NodeId, C::Node>>,
+    rx_committed: watch::Receiver<Option<LogId<C::NodeId>>>,
+    tx_vote_events: broadcast::Sender<VoteEvent<C::NodeId>>,
+    tx_leader_events: broadcast::Sender<LeaderEvent<C::NodeId>>,
+    tx_leader_changes: broadcast::Sender<LeaderChanged<C::NodeId>>,
     // TODO(xp): it does not need to be a async mutex.
     #[allow(clippy::type_complexity)]
     tx_shutdown: Mutex<Option<oneshot::Sender<()>>>,
@@ -190,8 +224,37 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// See the docs on the `RaftStorage` trait for more details.
     #[tracing::instrument(level="debug", skip(config, network, storage), fields(cluster=%config.cluster_name))]
     pub fn new(id: C::NodeId, config: Arc<Config>, network: N, storage: S) -> Self {
+        Self::spawn(id, config, network, storage, None)
+    }
+
+    /// Create and spawn a new Raft task, with a hook that observes every applied log entry.
+    ///
+    /// `apply_hook` is called with every log entry and the result returned by
+    /// [`RaftStorage::apply_to_state_machine()`] for it, in log order, on a leader as well as on a follower or a
+    /// learner. Entries installed by a snapshot are not applied thus are not passed to the hook.
+    ///
+    /// The hook is called inside the Raft task, right after the entries are applied. It must not block: a slow hook
+    /// delays everything else this node does, including heartbeats and elections.
+    ///
+    /// The other arguments are the same as [`Raft::new()`].
+    #[tracing::instrument(level="debug", skip_all, fields(cluster=%config.cluster_name))]
+    pub fn with_apply_hook(
+        id: C::NodeId,
+        config: Arc<Config>,
+        network: N,
+        storage: S,
+        apply_hook: impl FnMut(&Entry<C>, &C::R) + Send + 'static,
+    ) -> Self {
+        Self::spawn(id, config, network, storage, Some(Box::new(apply_hook)))
+    }
+
+    fn spawn(id: C::NodeId, config: Arc<Config>, network: N, storage: S, apply_hook: Option<ApplyHook<C>>) -> Self {
         let (tx_api, rx_api) = mpsc::unbounded_channel();
         let (tx_metrics, rx_metrics) = watch::channel(RaftMetrics::new_initial(id));
+        let (tx_committed, rx_committed) = watch::channel(None);
+        let (tx_vote_events, _) = broadcast::channel(VOTE_EVENT_CHANNEL_SIZE);
+        let (tx_leader_events, _) = broadcast::channel(LEADER_EVENT_CHANNEL_SIZE);
+        let (tx_leader_changes, _) = broadcast::channel(LEADER_EVENT_CHANNEL_SIZE);
         let (tx_shutdown, rx_shutdown) = oneshot::channel();
 
         let tick_handle = Tick::spawn(
@@ -210,7 +273,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
             storage,
             tx_api.clone(),
             rx_api,
-            tx_metrics,
+            EventSenders {
+                tx_metrics,
+                tx_committed,
+                tx_vote_events: tx_vote_events.clone(),
+                tx_leader_events: tx_leader_events.clone(),
+                tx_leader_changes: tx_leader_changes.clone(),
+            },
+            apply_hook,
             rx_shutdown,
         );
 
@@ -221,6 +291,10 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
             tick_handle,
             tx_api,
             rx_metrics,
+            rx_committed,
+            tx_vote_events,
+            tx_leader_events,
+            tx_leader_changes,
             tx_shutdown: Mutex::new(Some(tx_shutdown)),
             marker_n: std::marker::PhantomData,
             marker_s: std::marker::PhantomData,
@@ -253,6 +327,20 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.send_external_command(ExternalCommand::Elect, "trigger_elect").await
     }
 
+    /// Start an election on this node at once, without waiting for the election timeout.
+    ///
+    /// Unlike [`Raft::trigger_elect`], it returns after RaftCore has started the election, i.e., this node has become
+    /// a candidate of the next term and sent vote requests. It does not wait for the election to finish.
+    /// Pre-vote is skipped even if `Config::enable_pre_vote` is on.
+    ///
+    /// It returns `ElectNowError::AlreadyLeader` if this node is the leader, or `ElectNowError::NotAVoter` if this
+    /// node is a learner.
+    /// It is not affected by `Raft::enable_elect(false)`.
+    pub async fn elect_now(&self) -> Result<(), ElectNowError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ElectNow { tx }, rx).await
+    }
+
     /// Trigger a heartbeat at once and return at once.
     ///
     /// Returns error when RaftCore has Fatal error, e.g. shut down or having storage error.
@@ -261,11 +349,33 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.send_external_command(ExternalCommand::Heartbeat, "trigger_heartbeat").await
     }
 
-    /// Trigger to build a snapshot at once and return at once.
+    /// Purge logs upto index `upto`, inclusive, that are already in the current snapshot, without building a new
+    /// snapshot.
+    ///
+    /// Logs are purged automatically after building a snapshot, in batches of `Config::purge_batch_size`. This method
+    /// purges them at once to reclaim space, e.g., after changing `Config::max_in_snapshot_log_to_keep`.
+    /// The last `Config::max_in_snapshot_log_to_keep` logs in snapshot are always kept, for replicating to lagging
+    /// followers, thus fewer logs than asked for may be purged.
+    ///
+    /// It returns the last purged log id, or `PurgeLogError::NotInSnapshot` if the log at `upto` is not included in
+    /// the current snapshot.
+    pub async fn purge_log(&self, upto: u64) -> Result<Option<LogId<C::NodeId>>, PurgeLogError<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::PurgeLog { upto, tx }, rx).await
+    }
+
+    /// Trigger to build a snapshot at once, regardless of `Config::snapshot_policy`, and wait for it to finish.
+    ///
+    /// It returns the meta of the built snapshot after it is saved to storage. If a snapshot is already being
+    /// built, it waits for that one instead of starting another.
+    ///
+    /// It returns `None` if no snapshot is built, because building is aborted, fails with a transient storage error,
+    /// or never started in favor of installing a snapshot from the leader.
     ///
     /// Returns error when RaftCore has Fatal error, e.g. shut down or having storage error.
-    pub async fn trigger_snapshot(&self) -> Result<(), Fatal<C::NodeId>> {
-        self.send_external_command(ExternalCommand::Snapshot, "trigger_snapshot").await
+    pub async fn trigger_snapshot(&self) -> Result<Option<SnapshotMeta<C::NodeId, C::Node>>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TriggerSnapshot { tx }, rx).await
     }
 
     async fn send_external_command(
@@ -308,6 +418,51 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.call_core(RaftMsg::RequestVote { rpc, tx }, rx).await
     }
 
+    /// Submit a pre-vote request RPC to this Raft node.
+    ///
+    /// These RPCs are sent by cluster peers before starting an election, if `Config::enable_pre_vote` is on.
+    /// The response tells if the vote in the request would be granted. This node's state is not changed.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn pre_vote(&self, rpc: VoteRequest<C::NodeId>) -> Result<VoteResponse<C::NodeId>, VoteError<C::NodeId>> {
+        tracing::debug!(rpc = display(rpc.summary()), "Raft::pre_vote()");
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::RequestPreVote { rpc, tx }, rx).await
+    }
+
+    /// Submit a timeout-now request RPC to this Raft node.
+    ///
+    /// These RPCs are sent by the leader to transfer its leadership, see [`Raft::step_down()`].
+    /// If the request comes from the current leader and this node has all of the leader's logs, this node starts an
+    /// election at once.
+    #[tracing::instrument(level = "debug", skip(self, rpc))]
+    pub async fn timeout_now(
+        &self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> Result<TimeoutNowResponse<C::NodeId>, VoteError<C::NodeId>> {
+        tracing::debug!(rpc = display(rpc.summary()), "Raft::timeout_now()");
+
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::TimeoutNow { rpc, tx }, rx).await
+    }
+
+    /// Ask this node, which must be the leader, to give up its leadership, e.g., before restarting it.
+    ///
+    /// The leader picks the most up-to-date voter and stops accepting writes: client writes and membership changes
+    /// are rejected with `ForwardToLeader` without a leader to forward to. Once the target has replicated the
+    /// leader's last log, the leader sends it a timeout-now request, with which it starts an election at once. The
+    /// target's vote request then turns this node into a follower. It returns the id of the node that leadership is
+    /// transferred to, once the target accepted the request.
+    ///
+    /// If the target does not catch up within `Config::election_timeout_max`, it returns
+    /// `StepDownError::NoCaughtUpFollower`, and this node stays leader and accepts writes again, rather than leaving
+    /// the cluster without a leader.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn step_down(&self) -> Result<C::NodeId, StepDownError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::StepDown { tx }, rx).await
+    }
+
     /// Submit an InstallSnapshot RPC to this Raft node.
     ///
     /// These RPCs are sent by the cluster leader in order to bring a new node or a slow node up-to-speed
@@ -333,6 +488,67 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.metrics().borrow().current_leader
     }
 
+    /// Get the vote of this Raft node, i.e., the current term and the node it voted for.
+    ///
+    /// The vote is read from the in-memory state of the Raft core, without accessing storage.
+    /// RaftCore updates the vote and then saves it to storage when handling a message, and it answers this request
+    /// only between messages. Thus the returned vote has always been saved by `RaftStorage::save_vote()`.
+    ///
+    /// It returns `Fatal::Stopped` if the Raft node is shut down.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn current_vote(&self) -> Result<Vote<C::NodeId>, Fatal<C::NodeId>> {
+        let (tx, rx) = oneshot::channel();
+        self.external_request(move |st, _, _| {
+            let _ = tx.send(st.vote);
+        });
+        rx.await.map_err(|_| Fatal::Stopped)
+    }
+
+    /// Get the leader's view of the replication to every follower and learner.
+    ///
+    /// Besides the confirmed `matched` log id, it returns the `next_index` the leader is going to send.
+    /// While searching for the matching log on a diverged follower, `next_index` moves backward,
+    /// thus it tells why a follower is not catching up.
+    ///
+    /// It returns `ForwardToLeader` error if this node is not a leader.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn replication_states(
+        &self,
+    ) -> Result<BTreeMap<C::NodeId, ReplicationState<C::NodeId>>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::GetReplicationStates { tx }, rx).await
+    }
+
+    /// List the learners in the effective membership and the leader's view of the replication to them.
+    ///
+    /// A learner is ready to join, i.e., to be added as a voter with [`Raft::change_membership`], if it lags behind
+    /// the leader by no more than `Config::replication_lag_threshold` logs.
+    ///
+    /// It returns `ForwardToLeader` error if this node is not a leader.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_learners(
+        &self,
+    ) -> Result<Vec<(C::NodeId, LearnerState<C::NodeId>)>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let (tx, rx) = oneshot::channel();
+        self.call_core(RaftMsg::ListLearners { tx }, rx).await
+    }
+
+    /// Get the minimum applied log index among the leader and all of its followers and learners.
+    ///
+    /// The applied index of a target is learned from the responses to replication RPCs, without extra messages.
+    /// It is a lower bound, thus logs up to the returned index are safe to purge on every node.
+    /// `None` means no log is known to be applied on some node.
+    ///
+    /// It returns `ForwardToLeader` error if this node is not a leader.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn min_applied_across_cluster(&self) -> Result<Option<u64>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let states = self.replication_states().await?;
+        let leader_applied = self.metrics().borrow().last_applied.index();
+
+        let min = states.values().map(|x| x.applied).fold(leader_applied, std::cmp::min);
+        Ok(min)
+    }
+
     /// Check to ensure this node is still the cluster leader, in order to guard against stale reads (§8).
     ///
     /// The actual read operation itself is up to the application, this method just ensures that
@@ -343,6 +559,78 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.call_core(RaftMsg::CheckIsLeaderRequest { tx }, rx).await
     }
 
+    /// Wait until a linearizable read can be served by the local state machine, and return the read index.
+    ///
+    /// It implements the read-index protocol (§8) without appending a log:
+    /// it takes the committed log id as the read index, confirms leadership with a quorum via
+    /// [`Raft::is_leader`], then waits until the state machine has applied up to the read index and
+    /// a log of the current term.
+    /// The last condition is required because a newly elected leader does not know the committed log id
+    /// until it commits a log of its own term (§8).
+    ///
+    /// When it returns, a read from the state machine observes every write committed before this call.
+    ///
+    /// If this node is not a leader, or is no longer a leader while waiting, a `ForwardToLeader` error is returned.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn read_index(&self) -> Result<Option<LogId<C::NodeId>>, CheckIsLeaderError<C::NodeId, C::Node>> {
+        let read_log_id = *self.inner.rx_committed.borrow();
+
+        self.is_leader().await?;
+
+        let mut rx = self.inner.rx_metrics.clone();
+        loop {
+            {
+                let m = rx.borrow();
+
+                if let Err(fatal) = &m.running_state {
+                    return Err(fatal.clone().into());
+                }
+
+                if m.state != ServerState::Leader {
+                    let leader_node = m.current_leader.and_then(|id| m.membership_config.get_node(&id).cloned());
+                    return Err(ForwardToLeader {
+                        leader_id: leader_node.as_ref().and(m.current_leader),
+                        leader_node,
+                    }
+                    .into());
+                }
+
+                if m.last_applied >= read_log_id && m.last_applied.map(|x| x.leader_id.term) == Some(m.current_term) {
+                    break;
+                }
+            }
+
+            if rx.changed().await.is_err() {
+                return Err(Fatal::Stopped.into());
+            }
+        }
+
+        tracing::debug!(
+            read_log_id = debug(&read_log_id),
+            "read_index: applied up to read index"
+        );
+
+        Ok(read_log_id)
+    }
+
+    /// Run a linearizable read `f` after this node has applied all logs committed before the read.
+    ///
+    /// It waits with [`Raft::read_index`] before calling `f`.
+    ///
+    /// `f` is called outside of `RaftCore`, thus it should read from a storage handle held by the application.
+    ///
+    /// If this node is no longer a leader while waiting, a `ForwardToLeader` error is returned.
+    #[tracing::instrument(level = "debug", skip(self, f))]
+    pub async fn consistent_read<F, Fut, T>(&self, f: F) -> Result<T, CheckIsLeaderError<C::NodeId, C::Node>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        self.read_index().await?;
+
+        Ok(f().await)
+    }
+
     /// Submit a mutating client request to Raft to update the state of the system (§5.1).
     ///
     /// It will be appended to the log, committed to the cluster, and then applied to the
@@ -376,6 +664,75 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         .await
     }
 
+    /// Submit a mutating client request to Raft without waiting for it to be applied.
+    ///
+    /// It returns a [`WriteTicket`] as soon as the entry is appended to the leader's log.
+    /// The ticket contains the log id of the entry and a future that resolves to the same result as
+    /// [`Raft::client_write`] when the entry is applied.
+    ///
+    /// This way a client is able to pipeline many writes and await the result of each of them later.
+    #[tracing::instrument(level = "debug", skip(self, app_data))]
+    pub async fn client_write_ticket(
+        &self,
+        app_data: C::D,
+    ) -> Result<WriteTicket<C>, ClientWriteError<C::NodeId, C::Node>> {
+        let (tx_log_id, rx_log_id) = oneshot::channel();
+        let (tx, rx) = oneshot::channel();
+
+        let log_id = self
+            .call_core(
+                RaftMsg::ClientWriteWithTicket {
+                    payload: EntryPayload::Normal(app_data),
+                    tx_log_id,
+                    tx,
+                },
+                rx_log_id,
+            )
+            .await?;
+
+        let raft = self.clone();
+        let future = async move {
+            match rx.await {
+                Ok(x) => x,
+                Err(_) => {
+                    let fatal = raft
+                        .get_core_stopped_error("receiving client write response from RaftCore", None::<String>)
+                        .await;
+                    Err(fatal.into())
+                }
+            }
+        }
+        .boxed();
+
+        Ok(WriteTicket { log_id, future })
+    }
+
+    /// Submit a mutating client request to Raft and wait at most `timeout` for it to be applied.
+    ///
+    /// It behaves the same as [`Raft::client_write`], except that it returns [`ClientWriteError::Timeout`] if the
+    /// entry is not applied in `timeout`, e.g., when a quorum is unreachable.
+    /// A timed out entry is not cancelled: it may still be committed and applied later.
+    ///
+    /// The timeout starts after the entry is appended to the leader's log.
+    #[tracing::instrument(level = "debug", skip(self, app_data))]
+    pub async fn client_write_with_timeout(
+        &self,
+        app_data: C::D,
+        timeout: Duration,
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>> {
+        let WriteTicket { log_id, future } = self.client_write_ticket(app_data).await?;
+
+        match tokio::time::timeout(timeout, future).await {
+            Ok(res) => res,
+            Err(_elapsed) => {
+                // The response receiver has been dropped. Let RaftCore forget the waiter.
+                let _ignore_error = self.inner.tx_api.send(RaftMsg::ClientWriteTimeout { log_id });
+
+                Err(ClientWriteTimeout { log_id, timeout }.into())
+            }
+        }
+    }
+
     /// Initialize a pristine Raft node with the given config.
     ///
     /// This command should be called on pristine nodes — where the log index is 0 and the node is
@@ -448,6 +805,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
         // The log id of the membership that contains the added learner.
         let membership_log_id = resp.membership_log_id;
+        let max_lag = self.inner.config.replication_lag_threshold;
 
         let res0 = Arc::new(std::sync::Mutex::new(resp));
         let res = res0.clone();
@@ -455,7 +813,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         let wait_res = self
             .wait(None)
             .metrics(
-                |metrics| match self.check_replication_upto_date(metrics, id, membership_log_id) {
+                |metrics| match self.check_replication_upto_date(metrics, id, membership_log_id, max_lag) {
                     Ok(matched) => {
                         res.lock().unwrap().matched = matched;
                         true
@@ -476,8 +834,150 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         Ok(r)
     }
 
+    /// Add a learner and block until the replication to it catches up to `target`.
+    ///
+    /// - Add the node as learner and setup replication to it, as [`Raft::add_learner`] does.
+    /// - Wait until the learner catches up to `target`.
+    /// - With [`CatchUpTarget::Index`], remove the learner, which terminates the replication to it. This is useful for
+    ///   one-shot data migration, where a node needs a copy of the data but never becomes a voter.
+    ///
+    /// Unlike the blocking [`Raft::add_learner`], which gives up waiting after a while, it keeps waiting until the
+    /// learner catches up.
+    ///
+    /// When finished, it returns the last log id replicated to the learner in `AddLearnerResponse::matched`.
+    /// With [`CatchUpTarget::Index`], `AddLearnerResponse::membership_log_id` is the log id of the membership that
+    /// removes the learner.
+    ///
+    /// If this node is no longer the leader before the learner catches up, it returns
+    /// `AddLearnerError::ForwardToLeader`. If the learner is removed by others before catching up, it returns
+    /// `ChangeMembershipError::LearnerNotFound`.
+    #[tracing::instrument(level = "debug", skip(self, id), fields(target=display(id)))]
+    pub async fn add_learner_until(
+        &self,
+        id: C::NodeId,
+        node: C::Node,
+        target: CatchUpTarget,
+    ) -> Result<AddLearnerResponse<C::NodeId>, AddLearnerError<C::NodeId, C::Node>> {
+        let mut resp = self.add_learner(id, node, false).await?;
+
+        if self.inner.id == id {
+            return Ok(resp);
+        }
+
+        let membership_log_id = resp.membership_log_id;
+
+        let res0 = Arc::new(std::sync::Mutex::new(None));
+        let res = res0.clone();
+
+        // Catching up may take a long time. Keep waiting until it is done or RaftCore quits.
+        loop {
+            let wait_res = self
+                .wait(None)
+                .metrics(
+                    |metrics| match self.check_catch_up(metrics, id, membership_log_id, target) {
+                        CatchUp::Waiting => false,
+                        x => {
+                            *res.lock().unwrap() = Some(x);
+                            true
+                        }
+                    },
+                    "wait learner to catch up",
+                )
+                .await;
+
+            match wait_res {
+                Ok(_) => break,
+                Err(WaitError::Timeout(_, _)) => continue,
+                Err(WaitError::ShuttingDown) => return Err(AddLearnerError::Fatal(Fatal::Stopped)),
+            }
+        }
+
+        // Safe unwrap(): the waiting above returns Ok only when the result is set.
+        let matched = match res0.lock().unwrap().take().unwrap() {
+            CatchUp::Reached(matched) => matched,
+            CatchUp::Failed(e) => return Err(e),
+            CatchUp::Waiting => unreachable!("waiting is not a result"),
+        };
+
+        if let CatchUpTarget::Lag(_) = target {
+            resp.matched = Some(matched);
+            return Ok(resp);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let removed = self.call_core(RaftMsg::RemoveLearner { id, tx }, rx).await.map_err(|e| match e {
+            ClientWriteError::ForwardToLeader(e) => AddLearnerError::ForwardToLeader(e),
+            ClientWriteError::ChangeMembershipError(e) => AddLearnerError::ChangeMembershipError(e),
+            ClientWriteError::Fatal(e) => AddLearnerError::Fatal(e),
+            e @ (ClientWriteError::EntryTooLarge(_) | ClientWriteError::Timeout(_)) => {
+                AddLearnerError::RemoveLearner(e)
+            }
+        })?;
+
+        Ok(AddLearnerResponse {
+            membership_log_id: Some(removed.log_id),
+            matched: Some(matched),
+        })
+    }
+
+    /// Checks if the replication to the learner reaches `target`.
+    ///
+    /// For [`CatchUpTarget::Index`], the membership that adds the learner has to be applied too, so that it is allowed
+    /// to remove it.
+    fn check_catch_up(
+        &self,
+        metrics: &RaftMetrics<C::NodeId, C::Node>,
+        node_id: C::NodeId,
+        membership_log_id: Option<LogId<C::NodeId>>,
+        target: CatchUpTarget,
+    ) -> CatchUp<C::NodeId, C::Node> {
+        let repl = match &metrics.replication {
+            None => {
+                // This node is no longer a leader.
+                let leader_node = metrics.current_leader.and_then(|x| metrics.membership_config.get_node(&x).cloned());
+                let leader_id = leader_node.as_ref().and(metrics.current_leader);
+
+                return CatchUp::Failed(ForwardToLeader { leader_id, leader_node }.into());
+            }
+            Some(x) => x,
+        };
+
+        if metrics.membership_config.log_id < membership_log_id {
+            // Waiting for the latest metrics to report.
+            return CatchUp::Waiting;
+        }
+
+        if !metrics.membership_config.membership.contains(&node_id) {
+            // This learner has been removed.
+            let err = ChangeMembershipError::LearnerNotFound(LearnerNotFound { node_id });
+            return CatchUp::Failed(err.into());
+        }
+
+        let matched = match repl.data().replication.get(&node_id) {
+            None => {
+                // Maybe replication is not reported yet. Keep waiting.
+                return CatchUp::Waiting;
+            }
+            Some(x) => x.matched(),
+        };
+
+        let reached = match target {
+            CatchUpTarget::Index(index) => {
+                matched.index >= index && metrics.last_applied.index() >= membership_log_id.index()
+            }
+            CatchUpTarget::Lag(max_lag) => replication_lag(&Some(matched.index), &metrics.last_log_index) <= max_lag,
+        };
+
+        if reached {
+            return CatchUp::Reached(matched);
+        }
+
+        // Not caught up yet, keep waiting.
+        CatchUp::Waiting
+    }
+
     /// Returns Ok() with the latest known matched log id if it should quit waiting: leader change, node removed, or
-    /// replication becomes upto date.
+    /// replication lags no more than `max_lag` logs.
     ///
     /// Returns Err() if it should keep waiting.
     fn check_replication_upto_date(
@@ -485,6 +985,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         metrics: &RaftMetrics<C::NodeId, C::Node>,
         node_id: C::NodeId,
         membership_log_id: Option<LogId<C::NodeId>>,
+        max_lag: u64,
     ) -> Result<Option<LogId<C::NodeId>>, ()> {
         if metrics.membership_config.log_id < membership_log_id {
             // Waiting for the latest metrics to report.
@@ -517,7 +1018,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
 
         let distance = replication_lag(&Some(matched.index), &metrics.last_log_index);
 
-        if distance <= self.inner.config.replication_lag_threshold {
+        if distance <= max_lag {
             // replication became up to date.
             return Ok(Some(matched));
         }
@@ -537,6 +1038,8 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
     /// If `allow_lagging` is true, it will always propose the new membership and wait until committed.
     /// Otherwise it returns error `ChangeMembershipError::LearnerIsLagging` if there is a lagging learner.
     ///
+    /// Use [`Raft::change_membership_checked()`] to reject a membership whose live voters do not form a quorum.
+    ///
     /// If `turn_to_learner` is true, then all the members which not exists in the new membership,
     /// will be turned into learners, otherwise will be removed.
     ///
@@ -556,12 +1059,37 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         allow_lagging: bool,
         turn_to_learner: bool,
     ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>> {
-        let changes: ChangeMembers<C::NodeId> = members.into();
+        self.do_change_membership(members.into(), allow_lagging, turn_to_learner, false).await
+    }
 
+    /// Propose a cluster configuration change, the same as [`Raft::change_membership()`], except that the new
+    /// membership is checked to be safe to commit first.
+    ///
+    /// It returns error `ChangeMembershipError::QuorumNotLive` if the live voters of the new membership do not form a
+    /// quorum, which may make the cluster unavailable. A voter is considered live if it has responded to the leader
+    /// within `Config::election_timeout_max`. To apply such a membership anyway, use [`Raft::change_membership()`].
+    #[tracing::instrument(level = "info", skip_all)]
+    pub async fn change_membership_checked(
+        &self,
+        members: impl Into<ChangeMembers<C::NodeId>>,
+        allow_lagging: bool,
+        turn_to_learner: bool,
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>> {
+        self.do_change_membership(members.into(), allow_lagging, turn_to_learner, true).await
+    }
+
+    async fn do_change_membership(
+        &self,
+        changes: ChangeMembers<C::NodeId>,
+        allow_lagging: bool,
+        turn_to_learner: bool,
+        check_quorum_live: bool,
+    ) -> Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>> {
         tracing::info!(
             changes = debug(&changes),
             allow_lagging = display(allow_lagging),
             turn_to_learner = display(turn_to_learner),
+            check_quorum_live = display(check_quorum_live),
             "change_membership: start to commit joint config"
         );
 
@@ -583,6 +1111,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
                 RaftMsg::ChangeMembership {
                     changes: changes.clone(),
                     when: when.clone(),
+                    check_quorum_live,
                     turn_to_learner,
                     tx,
                 },
@@ -607,6 +1136,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
                 RaftMsg::ChangeMembership {
                     changes,
                     when,
+                    check_quorum_live,
                     turn_to_learner,
                     tx,
                 },
@@ -730,6 +1260,77 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         self.inner.rx_metrics.clone()
     }
 
+    /// Get the latest metrics, without subscribing to the metrics channel.
+    ///
+    /// It does not wait: the returned value is the last one reported by RaftCore.
+    pub fn metrics_snapshot(&self) -> RaftMetrics<C::NodeId, C::Node> {
+        self.inner.rx_metrics.borrow().clone()
+    }
+
+    /// Get a handle to the committed log id channel.
+    ///
+    /// The value is updated every time the committed log id advances, **before** the newly committed logs are applied
+    /// to the state machine. Thus an application is able to react to a commit without waiting for it to be applied.
+    ///
+    /// A slow receiver may miss some intermediate values, but the observed log ids are always monotonically
+    /// increasing.
+    pub fn committed(&self) -> watch::Receiver<Option<LogId<C::NodeId>>> {
+        self.inner.rx_committed.clone()
+    }
+
+    /// Returns whether the log at `index` is committed, as known by this node.
+    ///
+    /// On a leader it is the log that is accepted by a quorum. On a follower or learner it is the committed log id
+    /// the leader sent with the last replication, which may lag behind the leader.
+    pub fn is_committed(&self, index: u64) -> bool {
+        self.inner.rx_committed.borrow().index() >= Some(index)
+    }
+
+    /// Wait until the log at `index` is committed, as known by this node, and returns the committed log id.
+    ///
+    /// It returns `Fatal::Stopped` if the Raft node is shut down before the log is committed.
+    pub async fn wait_committed(&self, index: u64) -> Result<LogId<C::NodeId>, Fatal<C::NodeId>> {
+        let mut rx = self.inner.rx_committed.clone();
+        loop {
+            if let Some(log_id) = *rx.borrow() {
+                if log_id.index >= index {
+                    return Ok(log_id);
+                }
+            }
+
+            rx.changed().await.map_err(|_| Fatal::Stopped)?;
+        }
+    }
+
+    /// Subscribe to the vote requests this node receives and the grant or reject decision on each of them.
+    ///
+    /// Only events sent after subscribing are received.
+    /// A receiver that falls behind by more than 1024 events gets a `RecvError::Lagged` and misses the oldest ones.
+    pub fn vote_events(&self) -> broadcast::Receiver<VoteEvent<C::NodeId>> {
+        self.inner.tx_vote_events.subscribe()
+    }
+
+    /// Subscribe to the changes of the believed leader caused by AppendEntries requests from a new leader.
+    ///
+    /// It helps to detect a leader change this node did not take part in, e.g., a follower learns the
+    /// new leader only when the new leader starts replicating to it.
+    /// Only events sent after subscribing are received.
+    /// A receiver that falls behind by more than 1024 events gets a `RecvError::Lagged` and misses the oldest ones.
+    pub fn leader_events(&self) -> broadcast::Receiver<LeaderEvent<C::NodeId>> {
+        self.inner.tx_leader_events.subscribe()
+    }
+
+    /// Subscribe to every change of the leader this node believes in, no matter what causes it.
+    ///
+    /// Unlike [`Raft::leader_events()`], it includes this node becoming the leader, stepping down, and losing the
+    /// leader when an election starts, in which case the leader in the event is `None`.
+    /// Each event carries the term of this node, so that a consumer can ignore stale ones.
+    /// Only events sent after subscribing are received.
+    /// A receiver that falls behind by more than 1024 events gets a `RecvError::Lagged` and misses the oldest ones.
+    pub fn leader_changes(&self) -> broadcast::Receiver<LeaderChanged<C::NodeId>> {
+        self.inner.tx_leader_changes.subscribe()
+    }
+
     /// Get a handle to wait for the metrics to satisfy some condition.
     ///
     /// ```ignore
@@ -758,7 +1359,43 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Raft<C, N,
         }
     }
 
+    /// Wait until this node becomes the leader, or until `timeout`.
+    ///
+    /// It is backed by the metrics channel and resolves as soon as the metrics report this node as the leader.
+    /// `timeout` defaults to 500 ms, the same as [`Raft::wait`].
+    pub async fn wait_for_leadership(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<RaftMetrics<C::NodeId, C::Node>, WaitError> {
+        let id = self.inner.id;
+        self.wait(timeout)
+            .metrics(
+                |x| x.state == ServerState::Leader && x.current_leader == Some(id),
+                "wait_for_leadership",
+            )
+            .await
+    }
+
+    /// Wait until this node is no longer the leader, or until `timeout`.
+    ///
+    /// It resolves at once if this node is not a leader.
+    /// `timeout` defaults to 500 ms, the same as [`Raft::wait`].
+    pub async fn wait_for_non_leadership(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<RaftMetrics<C::NodeId, C::Node>, WaitError> {
+        self.wait(timeout).metrics(|x| x.state != ServerState::Leader, "wait_for_non_leadership").await
+    }
+
     /// Shutdown this Raft node.
+    ///
+    /// The shutdown signal is only checked between two messages: the message being handled is finished first.
+    /// RaftCore applies logs to the state machine and saves the vote while handling the message that commits the logs
+    /// or changes the vote. Thus when this method returns, every committed log has been applied and the vote has been
+    /// saved. It may take as long as applying the logs committed by the last message.
+    ///
+    /// Messages that are queued but not yet handled, e.g., client writes that are not yet appended, are dropped and
+    /// their callers receive an error.
     pub async fn shutdown(&self) -> Result<(), JoinError> {
         if let Some(tx) = self.inner.tx_shutdown.lock().await.take() {
             // A failure to send means the RaftCore is already shutdown. Continue to check the task return value.
@@ -795,6 +1432,56 @@ pub struct AddLearnerResponse<NID: NodeId> {
     pub matched: Option<LogId<NID>>,
 }
 
+/// The state of replication to a target node, seen by the leader. See [`Raft::replication_states`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct ReplicationState<NID: NodeId> {
+    /// The last log id confirmed to match the leader log.
+    pub matched: Option<LogId<NID>>,
+
+    /// The index of the first log the leader sends in the next AppendEntries RPC.
+    pub next_index: u64,
+
+    /// The index of the last log known to be applied on the target, or `None` if nothing is known.
+    ///
+    /// It is a lower bound: the target may have applied more logs than the leader has learned about.
+    pub applied: Option<u64>,
+}
+
+/// The state of a learner, seen by the leader. See [`Raft::list_learners`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct LearnerState<NID: NodeId> {
+    /// The replication to the learner.
+    pub replication: ReplicationState<NID>,
+
+    /// Whether the learner is close enough to the leader log to be added as a voter.
+    pub ready_to_join: bool,
+}
+
+/// What a learner has to catch up to, see [`Raft::add_learner_until`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum CatchUpTarget {
+    /// The learner is removed once the log at this index is replicated to it.
+    Index(u64),
+
+    /// The learner is kept once it lags at most this number of logs behind the last log of the leader.
+    Lag(u64),
+}
+
+/// The state of a learner being waited for by [`Raft::add_learner_until`].
+enum CatchUp<NID: NodeId, N: Node> {
+    /// Keep waiting.
+    Waiting,
+
+    /// The learner caught up, with the last log id replicated to it.
+    Reached(LogId<NID>),
+
+    /// Stop waiting: this node is no longer the leader, or the learner is removed.
+    Failed(AddLearnerError<NID, N>),
+}
+
 /// TX for Add Learner Respose
 pub(crate) type RaftAddLearnerTx<NID, N> = RaftRespTx<AddLearnerResponse<NID>, AddLearnerError<NID, N>>;
 
@@ -804,12 +1491,21 @@ pub(crate) type InstallSnapshotTx<NID> = RaftRespTx<InstallSnapshotResponse<NID>
 /// TX for Vote Response
 pub(crate) type VoteTx<NID> = RaftRespTx<VoteResponse<NID>, VoteError<NID>>;
 
+/// TX for Timeout Now Response
+pub(crate) type TimeoutNowTx<NID> = RaftRespTx<TimeoutNowResponse<NID>, VoteError<NID>>;
+
+/// TX for Step Down Response
+pub(crate) type StepDownTx<NID, N> = RaftRespTx<NID, StepDownError<NID, N>>;
+
 /// TX for Append Entries Response
 pub(crate) type AppendEntriesTx<NID> = RaftRespTx<AppendEntriesResponse<NID>, AppendEntriesError<NID>>;
 
 /// TX for Client Write Response
 pub(crate) type ClientWriteTx<C, NID, N> = RaftRespTx<ClientWriteResponse<C>, ClientWriteError<NID, N>>;
 
+/// TX for Trigger Snapshot Response
+pub(crate) type TriggerSnapshotTx<NID, N> = RaftRespTx<Option<SnapshotMeta<NID, N>>, Fatal<NID>>;
+
 /// A message coming from the Raft API.
 pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> {
     AppendEntries {
@@ -827,6 +1523,31 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         /// Which ServerState sent this message. It is also the requested vote.
         vote: Vote<C::NodeId>,
     },
+    RequestPreVote {
+        rpc: VoteRequest<C::NodeId>,
+        tx: VoteTx<C::NodeId>,
+    },
+    PreVoteResponse {
+        target: C::NodeId,
+        resp: VoteResponse<C::NodeId>,
+
+        /// The vote asked for in the pre-vote request.
+        vote: Vote<C::NodeId>,
+    },
+    TimeoutNow {
+        rpc: TimeoutNowRequest<C::NodeId>,
+        tx: TimeoutNowTx<C::NodeId>,
+    },
+    StepDown {
+        tx: StepDownTx<C::NodeId, C::Node>,
+    },
+    ElectNow {
+        tx: RaftRespTx<(), ElectNowError<C::NodeId>>,
+    },
+    PurgeLog {
+        upto: u64,
+        tx: RaftRespTx<Option<LogId<C::NodeId>>, PurgeLogError<C::NodeId>>,
+    },
     InstallSnapshot {
         rpc: InstallSnapshotRequest<C>,
         tx: InstallSnapshotTx<C::NodeId>,
@@ -842,10 +1563,33 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         payload: EntryPayload<C>,
         tx: ClientWriteTx<C, C::NodeId, C::Node>,
     },
+
+    /// Append a client write and send back its log id via `tx_log_id` before it is applied.
+    /// The result of applying it is sent via `tx`.
+    ClientWriteWithTicket {
+        payload: EntryPayload<C>,
+        tx_log_id: RaftRespTx<LogId<C::NodeId>, ClientWriteError<C::NodeId, C::Node>>,
+        tx: ClientWriteTx<C, C::NodeId, C::Node>,
+    },
+    /// The waiter of a client write has given up.
+    /// Remove its response channel, if the receiver has been dropped.
+    ClientWriteTimeout {
+        log_id: LogId<C::NodeId>,
+    },
     CheckIsLeaderRequest {
         tx: RaftRespTx<(), CheckIsLeaderError<C::NodeId, C::Node>>,
     },
 
+    GetReplicationStates {
+        #[allow(clippy::type_complexity)]
+        tx: RaftRespTx<BTreeMap<C::NodeId, ReplicationState<C::NodeId>>, CheckIsLeaderError<C::NodeId, C::Node>>,
+    },
+
+    ListLearners {
+        #[allow(clippy::type_complexity)]
+        tx: RaftRespTx<Vec<(C::NodeId, LearnerState<C::NodeId>)>, CheckIsLeaderError<C::NodeId, C::Node>>,
+    },
+
     Initialize {
         members: BTreeMap<C::NodeId, C::Node>,
         tx: RaftRespTx<(), InitializeError<C::NodeId, C::Node>>,
@@ -859,6 +1603,12 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         /// Send the log id when the replication becomes line-rate.
         tx: RaftAddLearnerTx<C::NodeId, C::Node>,
     },
+    /// Request raft core to remove a learner from the cluster.
+    RemoveLearner {
+        id: C::NodeId,
+
+        tx: RaftRespTx<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>>,
+    },
     ChangeMembership {
         changes: ChangeMembers<C::NodeId>,
 
@@ -866,6 +1616,9 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         /// If expectation is not satisfied, a corresponding error will return.
         when: Option<Expectation>,
 
+        /// Reject the change if the live voters of the new membership do not form a quorum.
+        check_quorum_live: bool,
+
         /// If `turn_to_learner` is `true`, then all the members which do not exist in the new membership
         /// will be turned into learners, otherwise they will be removed.
         turn_to_learner: bool,
@@ -882,6 +1635,11 @@ pub(crate) enum RaftMsg<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStor
         cmd: ExternalCommand,
     },
 
+    /// Build a snapshot at once and respond when it is done.
+    TriggerSnapshot {
+        tx: TriggerSnapshotTx<C::NodeId, C::Node>,
+    },
+
     /// A tick event to wake up RaftCore to check timeout etc.
     Tick {
         /// ith tick
@@ -955,6 +1713,17 @@ where
             RaftMsg::VoteResponse { target, resp, vote } => {
                 format!("VoteResponse: from: {}: {}, res-vote: {}", target, resp.summary(), vote)
             }
+            RaftMsg::RequestPreVote { rpc, .. } => {
+                format!("RequestPreVote: {}", rpc.summary())
+            }
+            RaftMsg::PreVoteResponse { target, resp, vote } => {
+                format!(
+                    "PreVoteResponse: from: {}: {}, pre-vote: {}",
+                    target,
+                    resp.summary(),
+                    vote
+                )
+            }
             RaftMsg::InstallSnapshot { rpc, .. } => {
                 format!("InstallSnapshot: {}", rpc.summary())
             }
@@ -964,28 +1733,47 @@ where
             RaftMsg::ClientWriteRequest { payload: rpc, .. } => {
                 format!("ClientWriteRequest: {}", rpc.summary())
             }
+            RaftMsg::ClientWriteWithTicket { payload, .. } => {
+                format!("ClientWriteWithTicket: {}", payload.summary())
+            }
+            RaftMsg::ClientWriteTimeout { log_id } => {
+                format!("ClientWriteTimeout: {}", log_id)
+            }
             RaftMsg::CheckIsLeaderRequest { .. } => "CheckIsLeaderRequest".to_string(),
+            RaftMsg::TimeoutNow { rpc, .. } => {
+                format!("TimeoutNow: {}", rpc.summary())
+            }
+            RaftMsg::StepDown { .. } => "StepDown".to_string(),
+            RaftMsg::ElectNow { .. } => "ElectNow".to_string(),
+            RaftMsg::PurgeLog { upto, .. } => format!("PurgeLog: upto: {}", upto),
+            RaftMsg::GetReplicationStates { .. } => "GetReplicationStates".to_string(),
+            RaftMsg::ListLearners { .. } => "ListLearners".to_string(),
             RaftMsg::Initialize { members, .. } => {
                 format!("Initialize: {:?}", members)
             }
             RaftMsg::AddLearner { id, node, .. } => {
                 format!("AddLearner: id: {}, node: {:?}", id, node)
             }
+            RaftMsg::RemoveLearner { id, .. } => {
+                format!("RemoveLearner: id: {}", id)
+            }
             RaftMsg::ChangeMembership {
                 changes: members,
                 when,
+                check_quorum_live,
                 turn_to_learner,
                 ..
             } => {
                 format!(
-                    "ChangeMembership: members: {:?}, when: {:?}, turn_to_learner: {}",
-                    members, when, turn_to_learner,
+                    "ChangeMembership: members: {:?}, when: {:?}, check_quorum_live: {}, turn_to_learner: {}",
+                    members, when, check_quorum_live, turn_to_learner,
                 )
             }
             RaftMsg::ExternalRequest { .. } => "External Request".to_string(),
             RaftMsg::ExternalCommand { cmd } => {
                 format!("ExternalCommand: {:?}", cmd)
             }
+            RaftMsg::TriggerSnapshot { .. } => "TriggerSnapshot".to_string(),
             RaftMsg::Tick { i } => {
                 format!("Tick {}", i)
             }
@@ -1030,8 +1818,6 @@ pub(crate) enum ExternalCommand {
     Elect,
     /// Emit a heartbeat message, only if the node is leader.
     Heartbeat,
-    /// Trigger to build a snapshot
-    Snapshot,
 }
 
 /// An RPC sent by a cluster leader to replicate log entries (§5.3), and as a heartbeat (§5.2).
@@ -1123,17 +1909,36 @@ impl<NID: NodeId> MessageSummary<AppendEntriesResponse<NID>> for AppendEntriesRe
 pub struct VoteRequest<NID: NodeId> {
     pub vote: Vote<NID>,
     pub last_log_id: Option<LogId<NID>>,
+
+    /// The election priority of the candidate, see [`Config::election_priority`](`crate::Config::election_priority`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub priority: u32,
 }
 
 impl<NID: NodeId> MessageSummary<VoteRequest<NID>> for VoteRequest<NID> {
     fn summary(&self) -> String {
-        format!("{}, last_log:{:?}", self.vote, self.last_log_id.map(|x| x.to_string()))
+        format!(
+            "{}, last_log:{:?}, priority:{}",
+            self.vote,
+            self.last_log_id.map(|x| x.to_string()),
+            self.priority
+        )
     }
 }
 
 impl<NID: NodeId> VoteRequest<NID> {
     pub fn new(vote: Vote<NID>, last_log_id: Option<LogId<NID>>) -> Self {
-        Self { vote, last_log_id }
+        Self {
+            vote,
+            last_log_id,
+            priority: 0,
+        }
+    }
+
+    /// Set the election priority of the candidate.
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
     }
 }
 
@@ -1163,6 +1968,48 @@ impl<NID: NodeId> MessageSummary<VoteResponse<NID>> for VoteResponse<NID> {
     }
 }
 
+/// An RPC sent by a leader to a caught up follower to transfer leadership to it, see [`Raft::step_down()`].
+///
+/// The follower starts an election at once, without waiting for its election timeout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TimeoutNowRequest<NID: NodeId> {
+    /// The vote of the leader.
+    pub vote: Vote<NID>,
+
+    /// The last log id on the leader, which the follower must have.
+    pub last_log_id: Option<LogId<NID>>,
+}
+
+impl<NID: NodeId> MessageSummary<TimeoutNowRequest<NID>> for TimeoutNowRequest<NID> {
+    fn summary(&self) -> String {
+        format!("{}, last_log:{:?}", self.vote, self.last_log_id.map(|x| x.to_string()))
+    }
+}
+
+impl<NID: NodeId> TimeoutNowRequest<NID> {
+    pub fn new(vote: Vote<NID>, last_log_id: Option<LogId<NID>>) -> Self {
+        Self { vote, last_log_id }
+    }
+}
+
+/// The response to a `TimeoutNowRequest`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct TimeoutNowResponse<NID: NodeId> {
+    /// The vote of the follower after handling the request.
+    pub vote: Vote<NID>,
+
+    /// Will be true if the follower started an election.
+    pub accepted: bool,
+}
+
+impl<NID: NodeId> MessageSummary<TimeoutNowResponse<NID>> for TimeoutNowResponse<NID> {
+    fn summary(&self) -> String {
+        format!("{{accepted:{}, {}}}", self.accepted, self.vote)
+    }
+}
+
 /// An RPC sent by the Raft leader to send chunks of a snapshot to a follower (§7).
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
@@ -1218,6 +2065,21 @@ pub struct ClientWriteResponse<C: RaftTypeConfig> {
     pub membership: Option<Membership<C::NodeId, C::Node>>,
 }
 
+/// A ticket of a client write that is appended but not yet applied, returned by [`Raft::client_write_ticket`].
+pub struct WriteTicket<C: RaftTypeConfig> {
+    /// The id of the log that is appended.
+    pub log_id: LogId<C::NodeId>,
+
+    /// Resolves to the response when the log is applied.
+    pub future: BoxFuture<'static, Result<ClientWriteResponse<C>, ClientWriteError<C::NodeId, C::Node>>>,
+}
+
+impl<C: RaftTypeConfig> Debug for WriteTicket<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteTicket").field("log_id", &self.log_id).finish()
+    }
+}
+
 impl<C: RaftTypeConfig> Debug for ClientWriteResponse<C>
 where C::R: Debug
 {
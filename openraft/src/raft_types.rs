@@ -1,19 +1,48 @@
+use std::cmp::Ordering;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::str::FromStr;
 
+use crate::error::InvalidLogId;
+use crate::error::InvalidSnapshotSegmentId;
 use crate::LeaderId;
 use crate::MessageSummary;
 use crate::NodeId;
 
 /// The identity of a raft log.
 /// A term, node_id and an index identifies an log globally.
-#[derive(Debug, Default, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
+///
+/// Log ids are ordered by the leader id first, i.e., by `term`, then by `index`. See the `Ord` impl.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct LogId<NID: NodeId> {
     pub leader_id: LeaderId<NID>,
     pub index: u64,
 }
 
+/// Log ids are ordered as raft orders logs: the one with a greater `term` is greater; if the terms are equal,
+/// the one with a greater `index` is greater.
+///
+/// The `term` is compared as part of the `leader_id`. Since there is at most one leader in a term, two valid
+/// log ids with the same term always have the same `leader_id`.
+///
+/// E.g., `(2,1) > (1,9)`: a log of a later term is greater no matter what its index is.
+///
+/// This ordering is what comparing two log ids relies on, e.g., in `StorageHelper::get_initial_state()` to detect
+/// if `last_log_id < last_applied`. It is implemented explicitly so that it does not change if the fields are
+/// reordered.
+impl<NID: NodeId> Ord for LogId<NID> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.leader_id.cmp(&other.leader_id).then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+impl<NID: NodeId> PartialOrd for LogId<NID> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 pub trait RaftLogId<NID: NodeId> {
     fn get_log_id(&self) -> &LogId<NID>;
 
@@ -48,33 +77,49 @@ impl<NID: NodeId> MessageSummary<LogId<NID>> for Option<LogId<NID>> {
 }
 
 impl<NID: NodeId> LogId<NID> {
+    /// Create a log id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is an invalid log id, see [`LogId::try_new`].
     pub fn new(leader_id: LeaderId<NID>, index: u64) -> Self {
-        if leader_id.term == 0 || index == 0 {
-            assert_eq!(
-                leader_id.term, 0,
-                "zero-th log entry must be (0,0,0), but {} {}",
-                leader_id, index
-            );
-            assert_eq!(
-                leader_id.node_id,
-                NID::default(),
-                "zero-th log entry must be (0,0,0), but {} {}",
-                leader_id,
-                index
-            );
-            assert_eq!(
-                index, 0,
-                "zero-th log entry must be (0,0,0), but {} {}",
-                leader_id, index
-            );
+        Self::try_new(leader_id, index).expect("invalid log id")
+    }
+
+    /// Create a log id, or return an error if it is invalid.
+    ///
+    /// The zero-th log entry must be `(0,0,0)`: if either the term or the index is 0, all of the term, the
+    /// node id and the index must be 0.
+    ///
+    /// It is meant to validate a log id from untrusted input, such as logs read from a possibly corrupt
+    /// storage.
+    pub fn try_new(leader_id: LeaderId<NID>, index: u64) -> Result<Self, InvalidLogId<NID>> {
+        if (leader_id.term == 0 || index == 0)
+            && (leader_id.term != 0 || leader_id.node_id != NID::default() || index != 0)
+        {
+            return Err(InvalidLogId { leader_id, index });
         }
-        LogId { leader_id, index }
+
+        Ok(LogId { leader_id, index })
     }
 }
 
 pub trait LogIdOptionExt {
     fn index(&self) -> Option<u64>;
     fn next_index(&self) -> u64;
+
+    /// Returns the greater one of two log ids.
+    ///
+    /// Log ids are compared in the order of `LogId`: the leader id(term first), then the index.
+    /// `None` is smaller than any log id.
+    fn max_log_id(self, other: Self) -> Self
+    where Self: Sized;
+
+    /// Compares only the index of two log ids, ignoring the leader id.
+    ///
+    /// `None` is smaller than any index.
+    fn cmp_index(self, other: Self) -> Ordering
+    where Self: Sized;
 }
 
 impl<NID: NodeId> LogIdOptionExt for Option<LogId<NID>> {
@@ -88,6 +133,14 @@ impl<NID: NodeId> LogIdOptionExt for Option<LogId<NID>> {
             Some(log_id) => log_id.index + 1,
         }
     }
+
+    fn max_log_id(self, other: Self) -> Self {
+        std::cmp::max(self, other)
+    }
+
+    fn cmp_index(self, other: Self) -> Ordering {
+        self.index().cmp(&other.index())
+    }
 }
 
 pub trait LogIndexOptionExt {
@@ -150,6 +203,28 @@ impl Display for SnapshotSegmentId {
     }
 }
 
+/// Parse a `SnapshotSegmentId` from its `Display` output, i.e., `<id>+<offset>`.
+///
+/// The `offset` is the part after the last `+`, thus an `id` containing `+` is parsed correctly.
+impl FromStr for SnapshotSegmentId {
+    type Err = InvalidSnapshotSegmentId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| InvalidSnapshotSegmentId {
+            input: s.to_string(),
+            reason,
+        };
+
+        let (id, offset) = s.rsplit_once('+').ok_or_else(|| invalid("missing '+'".to_string()))?;
+        let offset = offset.parse::<u64>().map_err(|e| invalid(format!("invalid offset: {e}")))?;
+
+        Ok(SnapshotSegmentId {
+            id: id.to_string(),
+            offset,
+        })
+    }
+}
+
 // An update action with option to update with some value or just leave it as is.
 #[derive(Debug, Clone, PartialOrd, PartialEq, Eq)]
 pub enum Update<T> {
@@ -157,6 +232,32 @@ pub enum Update<T> {
     AsIs,
 }
 
+impl<T> Update<T> {
+    /// Maps an `Update<T>` to `Update<U>` by applying `f` to the value to update with.
+    pub fn map<U, F>(self, f: F) -> Update<U>
+    where F: FnOnce(T) -> U {
+        match self {
+            Update::Update(v) => Update::Update(f(v)),
+            Update::AsIs => Update::AsIs,
+        }
+    }
+
+    /// Returns the value to update with, or `default` if it is `AsIs`.
+    pub fn unwrap_or(self, default: T) -> T {
+        match self {
+            Update::Update(v) => v,
+            Update::AsIs => default,
+        }
+    }
+
+    /// Overwrites `target` with the value to update with, or leaves it as is.
+    pub fn apply_to(self, target: &mut T) {
+        if let Update::Update(v) = self {
+            *target = v;
+        }
+    }
+}
+
 /// Describes the need to update some aspect of the metrics.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub(crate) struct MetricsChangeFlags {
@@ -0,0 +1,168 @@
+use std::cmp::Ordering;
+
+use crate::error::InvalidLogId;
+use crate::LeaderId;
+use crate::LogId;
+use crate::LogIdOptionExt;
+use crate::SnapshotSegmentId;
+use crate::Update;
+
+#[test]
+fn test_log_id_try_new() -> anyhow::Result<()> {
+    let lid = |term, node_id| LeaderId::<u64> { term, node_id };
+
+    assert_eq!(
+        LogId {
+            leader_id: lid(0, 0),
+            index: 0
+        },
+        LogId::try_new(lid(0, 0), 0)?
+    );
+    assert_eq!(
+        LogId {
+            leader_id: lid(1, 2),
+            index: 3
+        },
+        LogId::try_new(lid(1, 2), 3)?
+    );
+
+    assert_eq!(
+        Err(InvalidLogId {
+            leader_id: lid(1, 0),
+            index: 0
+        }),
+        LogId::try_new(lid(1, 0), 0)
+    );
+    assert_eq!(
+        Err(InvalidLogId {
+            leader_id: lid(0, 0),
+            index: 1
+        }),
+        LogId::try_new(lid(0, 0), 1)
+    );
+    assert_eq!(
+        Err(InvalidLogId {
+            leader_id: lid(0, 2),
+            index: 0
+        }),
+        LogId::try_new(lid(0, 2), 0)
+    );
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "invalid log id")]
+fn test_log_id_new_panics_on_invalid() {
+    let _ = LogId::<u64>::new(LeaderId::new(0, 0), 1);
+}
+
+#[test]
+fn test_log_id_ord() -> anyhow::Result<()> {
+    let log_id = |term, index| LogId::<u64>::new(LeaderId::new(term, 0), index);
+
+    // A greater term wins, no matter what the index is.
+    assert!(log_id(2, 1) > log_id(1, 9));
+    assert!(log_id(1, 9) < log_id(2, 1));
+
+    // With the same term, a greater index wins.
+    assert!(log_id(1, 2) > log_id(1, 1));
+    assert_eq!(std::cmp::Ordering::Equal, log_id(1, 2).cmp(&log_id(1, 2)));
+
+    assert_eq!(log_id(2, 1), std::cmp::max(log_id(2, 1), log_id(1, 9)));
+    assert_eq!(log_id(1, 9), std::cmp::min(log_id(2, 1), log_id(1, 9)));
+
+    // None is less than any log id.
+    assert!(None < Some(log_id(0, 0)));
+    assert!(Some(log_id(1, 9)) < Some(log_id(2, 1)));
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_segment_id_from_str() -> anyhow::Result<()> {
+    for s in [
+        "a+0",
+        "1-2-3+5",
+        "snapshot-10-20-30+1024",
+        "0+18446744073709551615",
+        "a+b+3",
+        "+7",
+    ] {
+        assert_eq!(s, s.parse::<SnapshotSegmentId>()?.to_string());
+    }
+
+    assert_eq!(
+        SnapshotSegmentId {
+            id: "1-2+3".to_string(),
+            offset: 4
+        },
+        "1-2+3+4".parse::<SnapshotSegmentId>()?
+    );
+
+    assert!("abc".parse::<SnapshotSegmentId>().is_err());
+    assert!("abc+".parse::<SnapshotSegmentId>().is_err());
+    assert!("abc+x".parse::<SnapshotSegmentId>().is_err());
+    assert!("abc+-1".parse::<SnapshotSegmentId>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_update_map() -> anyhow::Result<()> {
+    assert_eq!(Update::Update(2), Update::Update(1).map(|x| x * 2));
+    assert_eq!(Update::<u64>::AsIs, Update::<u64>::AsIs.map(|x| x * 2));
+
+    Ok(())
+}
+
+#[test]
+fn test_update_unwrap_or() -> anyhow::Result<()> {
+    assert_eq!(1, Update::Update(1).unwrap_or(5));
+    assert_eq!(5, Update::AsIs.unwrap_or(5));
+
+    Ok(())
+}
+
+#[test]
+fn test_update_apply_to() -> anyhow::Result<()> {
+    let mut v = 5;
+
+    Update::AsIs.apply_to(&mut v);
+    assert_eq!(5, v);
+
+    Update::Update(1).apply_to(&mut v);
+    assert_eq!(1, v);
+
+    Ok(())
+}
+
+#[test]
+fn test_log_id_option_max_log_id() -> anyhow::Result<()> {
+    let log_id = |t, n, i| Some(LogId::<u64>::new(LeaderId::new(t, n), i));
+
+    assert_eq!(None, None::<LogId<u64>>.max_log_id(None));
+    assert_eq!(log_id(1, 0, 0), None.max_log_id(log_id(1, 0, 0)));
+    assert_eq!(log_id(1, 0, 0), log_id(1, 0, 0).max_log_id(None));
+
+    // Term-major: a greater term wins over a greater index.
+    assert_eq!(log_id(2, 0, 1), log_id(1, 0, 5).max_log_id(log_id(2, 0, 1)));
+    assert_eq!(log_id(1, 0, 5), log_id(1, 0, 5).max_log_id(log_id(1, 0, 3)));
+
+    Ok(())
+}
+
+#[test]
+fn test_log_id_option_cmp_index() -> anyhow::Result<()> {
+    let log_id = |t, n, i| Some(LogId::<u64>::new(LeaderId::new(t, n), i));
+
+    assert_eq!(Ordering::Equal, None::<LogId<u64>>.cmp_index(None));
+    assert_eq!(Ordering::Less, None.cmp_index(log_id(1, 0, 0)));
+    assert_eq!(Ordering::Greater, log_id(1, 0, 0).cmp_index(None));
+
+    // Only the index is compared.
+    assert_eq!(Ordering::Equal, log_id(1, 0, 3).cmp_index(log_id(2, 1, 3)));
+    assert_eq!(Ordering::Greater, log_id(1, 0, 5).cmp_index(log_id(2, 0, 1)));
+
+    Ok(())
+}
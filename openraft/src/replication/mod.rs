@@ -1,7 +1,10 @@
 //! Replication stream.
 
 use std::io::SeekFrom;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use futures::future::FutureExt;
 use tokio::io::AsyncReadExt;
@@ -16,6 +19,7 @@ use tracing_futures::Instrument;
 
 use crate::config::Config;
 use crate::config::SnapshotPolicy;
+use crate::core::AppliedLogSizes;
 use crate::error::AppendEntriesError;
 use crate::error::CommittedAdvanceTooMany;
 use crate::error::HigherVote;
@@ -52,6 +56,12 @@ pub(crate) struct ReplicationStream<NID: NodeId> {
 
     /// The channel used for communicating with the replication task.
     pub repl_tx: mpsc::UnboundedSender<Replicate<NID>>,
+
+    /// The index of the first log the replication task sends in the next AppendEntries RPC.
+    pub next_index: Arc<AtomicU64>,
+
+    /// The next index of the last log known to be applied on the target. `0` means nothing is known to be applied.
+    pub applied_next_index: Arc<AtomicU64>,
 }
 
 /// A task responsible for sending replication events to a target follower in the Raft cluster.
@@ -121,6 +131,24 @@ pub(crate) struct ReplicationCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S
     /// The last possible matching entry on a follower.
     max_possible_matched_index: Option<u64>,
 
+    /// The index of the first log to send, shared with [`ReplicationStream`].
+    ///
+    /// It moves backward while searching for the matching log on the follower, and moves forward when logs are
+    /// replicated.
+    next_index: Arc<AtomicU64>,
+
+    /// The next index of the last log known to be applied on the target, shared with [`ReplicationStream`].
+    ///
+    /// A follower applies committed logs before responding to AppendEntries, thus a successful response means the
+    /// logs up to `min(leader_commit, matched)` are applied.
+    applied_next_index: Arc<AtomicU64>,
+
+    /// The number of snapshots sent by all replication streams of the leader, shared with `RaftCore`.
+    snapshots_sent: Arc<AtomicU64>,
+
+    /// The sizes of the applied logs not in a snapshot, shared with `RaftCore`.
+    applied_log_sizes: Arc<Mutex<AppliedLogSizes>>,
+
     /// The timeout for sending snapshot segment.
     install_snapshot_timeout: Duration,
 
@@ -130,7 +158,10 @@ pub(crate) struct ReplicationCore<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S
 
 impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> ReplicationCore<C, N, S> {
     /// Spawn a new replication task for the target node.
-    #[tracing::instrument(level = "trace", skip(config, network, log_reader, raft_core_tx))]
+    #[tracing::instrument(
+        level = "trace",
+        skip(config, network, log_reader, raft_core_tx, snapshots_sent, applied_log_sizes)
+    )]
     #[allow(clippy::type_complexity)]
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn spawn(
@@ -143,11 +174,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
         network: N::Network,
         log_reader: S::LogReader,
         raft_core_tx: mpsc::UnboundedSender<RaftMsg<C, N, S>>,
+        snapshots_sent: Arc<AtomicU64>,
+        applied_log_sizes: Arc<Mutex<AppliedLogSizes>>,
         span: tracing::Span,
     ) -> ReplicationStream<C::NodeId> {
         // other component to ReplicationStream
         let (repl_tx, repl_rx) = mpsc::unbounded_channel();
         let install_snapshot_timeout = Duration::from_millis(config.install_snapshot_timeout);
+        let next_index = Arc::new(AtomicU64::new(progress_entry.matching.next_index()));
+        let applied_next_index = Arc::new(AtomicU64::new(0));
 
         let this = Self {
             target,
@@ -160,15 +195,24 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             committed,
             matched: progress_entry.matching,
             max_possible_matched_index: progress_entry.max_possible_matching(),
+            next_index: next_index.clone(),
+            applied_next_index: applied_next_index.clone(),
             raft_core_tx,
             repl_rx,
+            snapshots_sent,
+            applied_log_sizes,
             install_snapshot_timeout,
             need_to_replicate: true,
         };
 
         let handle = tokio::spawn(this.main().instrument(span));
 
-        ReplicationStream { handle, repl_tx }
+        ReplicationStream {
+            handle,
+            repl_tx,
+            next_index,
+            applied_next_index,
+        }
     }
 
     #[tracing::instrument(level="debug", skip(self), fields(vote=%self.vote, target=display(self.target), cluster=%self.config.cluster_name))]
@@ -237,6 +281,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
     /// Send an AppendEntries RPC to the target.
     ///
+    /// If the target has all of the logs, it is a heartbeat that contains no entries, and no log entry is read from
+    /// storage to build it.
+    ///
     /// This request will timeout if no response is received within the
     /// configured heartbeat interval.
     #[tracing::instrument(level = "debug", skip(self))]
@@ -247,7 +294,7 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
         let mut prev_index = self.matched.index().add(offset);
 
-        let (prev_log_id, logs, has_more_logs) = loop {
+        let (prev_log_id, logs, has_more_logs, last_log_index) = loop {
             // TODO(xp): test heartbeat when all logs are removed.
 
             let log_state = self.log_reader.get_log_state().await?;
@@ -277,6 +324,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
             let prev_log_id = if prev_index == last_purged.index() {
                 last_purged
+            } else if prev_index == self.matched.index() {
+                // The matched log is never removed by the leader, unless it is purged.
+                self.matched
             } else if let Some(prev_i) = prev_index {
                 let first = self.log_reader.try_get_log_entry(prev_i).await?;
                 match first {
@@ -293,7 +343,14 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             let logs = if start == end {
                 vec![]
             } else {
-                let logs = self.log_reader.try_get_log_entries(start..end).await?;
+                let logs = self
+                    .log_reader
+                    .get_log_entries_limited(
+                        start..end,
+                        self.config.max_payload_entries,
+                        self.config.max_payload_bytes,
+                    )
+                    .await?;
                 if !logs.is_empty() && logs[0].log_id.index > prev_log_id.next_index() {
                     // There is still chance the first log is removed.
                     // log entry is just deleted after fetching first_log_id.
@@ -304,35 +361,92 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 logs
             };
 
-            break (prev_log_id, logs, end < last_log_index);
-        };
+            // Fewer logs than requested may be loaded due to `max_payload_bytes`.
+            let end = logs.last().map(|x| x.log_id.index + 1).unwrap_or(end);
 
-        let conflict = prev_log_id;
-        let matched = if logs.is_empty() {
-            prev_log_id
-        } else {
-            Some(logs[logs.len() - 1].log_id)
+            break (prev_log_id, logs, end < last_log_index, last_log_index);
         };
 
-        // Build the heartbeat frame to be sent to the follower.
-        let payload = AppendEntriesRequest {
-            vote: self.vote,
-            prev_log_id,
-            leader_commit: self.committed,
-            entries: logs,
-        };
+        self.next_index.store(prev_log_id.next_index(), Ordering::Relaxed);
 
-        // Send the payload.
-        tracing::debug!(
-            payload=%payload.summary(),
-            "start sending append_entries, timeout: {:?}",
-            self.config.heartbeat_interval
-        );
+        let mut batches = vec![(prev_log_id, logs)];
+        let mut has_more_logs = has_more_logs;
+
+        // Load more consecutive payloads to send in one round trip.
+        while has_more_logs && (batches.len() as u64) < self.config.max_append_batches {
+            let prev_log_id = match batches.last().and_then(|(_, logs)| logs.last()) {
+                Some(last) => last.log_id,
+                None => break,
+            };
+
+            let start = prev_log_id.index + 1;
+            let end = std::cmp::min(start + self.config.max_payload_entries, last_log_index);
+
+            let logs = self
+                .log_reader
+                .get_log_entries_limited(
+                    start..end,
+                    self.config.max_payload_entries,
+                    self.config.max_payload_bytes,
+                )
+                .await?;
+
+            // Logs may be purged since the first payload is loaded.
+            let last = match (logs.first(), logs.last()) {
+                (Some(first), Some(last)) if first.log_id.index == start => last.log_id,
+                _ => break,
+            };
+
+            has_more_logs = last.index + 1 < last_log_index;
+            batches.push((Some(prev_log_id), logs));
+        }
+
+        let leader_commit = self.committed;
+
+        // For every payload: the prev log id that may conflict and the last log id that will be matched.
+        let mut expected = Vec::with_capacity(batches.len());
+        let mut payloads = Vec::with_capacity(batches.len());
+
+        for (prev_log_id, logs) in batches {
+            let matched = logs.last().map(|x| Some(x.log_id)).unwrap_or(prev_log_id);
+            expected.push((prev_log_id, matched));
+
+            // Build the heartbeat frame to be sent to the follower.
+            payloads.push(AppendEntriesRequest {
+                vote: self.vote,
+                prev_log_id,
+                leader_commit,
+                entries: logs,
+            });
+        }
+
+        let n_payloads = payloads.len();
+        let the_timeout = Duration::from_millis(self.config.heartbeat_interval * n_payloads as u64);
+
+        // Send the payloads.
+        let res = if n_payloads == 1 {
+            let payload = payloads.pop().unwrap();
+
+            tracing::debug!(
+                payload=%payload.summary(),
+                "start sending append_entries, timeout: {:?}",
+                the_timeout
+            );
+
+            timeout(the_timeout, self.network.send_append_entries(payload))
+                .await
+                .map(|res| res.map(|x| vec![x]))
+        } else {
+            tracing::debug!(
+                payloads = n_payloads,
+                "start sending append_entries in batch, timeout: {:?}",
+                the_timeout
+            );
 
-        let the_timeout = Duration::from_millis(self.config.heartbeat_interval);
-        let res = timeout(the_timeout, self.network.send_append_entries(payload)).await;
+            timeout(the_timeout, self.network.send_append_entries_batch(payloads)).await
+        };
 
-        let append_resp = match res {
+        let append_resps = match res {
             Ok(append_res) => match append_res {
                 Ok(res) => res,
                 Err(err) => {
@@ -382,40 +496,47 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
             }
         };
 
-        tracing::debug!("append_entries resp: {:?}", append_resp);
+        tracing::debug!("append_entries resps: {:?}", append_resps);
 
-        match append_resp {
-            AppendEntriesResponse::Success => {
-                self.update_matched(matched);
+        let n_resps = append_resps.len();
 
-                // Set the need_to_replicate flag if there is more log to send.
-                // Otherwise leave it as is.
-                self.need_to_replicate = has_more_logs;
-                Ok(())
-            }
-            AppendEntriesResponse::HigherVote(vote) => {
-                assert!(vote > self.vote, "higher vote should be greater than leader's vote");
-                tracing::debug!(%vote, "append entries failed. converting to follower");
+        for (append_resp, (conflict, matched)) in append_resps.into_iter().zip(expected) {
+            match append_resp {
+                AppendEntriesResponse::Success => {
+                    let applied = std::cmp::min(leader_commit, matched);
+                    self.applied_next_index.fetch_max(applied.next_index(), Ordering::Relaxed);
 
-                Err(ReplicationError::HigherVote(HigherVote {
-                    higher: vote,
-                    mine: self.vote,
-                }))
-            }
-            AppendEntriesResponse::Conflict => {
-                debug_assert!(conflict.is_some(), "prev_log_id=None never conflict");
-                let conflict = conflict.unwrap();
-
-                // Continue to find the matching log id on follower.
-                self.max_possible_matched_index = if conflict.index == 0 {
-                    None
-                } else {
-                    Some(conflict.index - 1)
-                };
-
-                Ok(())
+                    self.update_matched(matched);
+                }
+                AppendEntriesResponse::HigherVote(vote) => {
+                    assert!(vote > self.vote, "higher vote should be greater than leader's vote");
+                    tracing::debug!(%vote, "append entries failed. converting to follower");
+
+                    return Err(ReplicationError::HigherVote(HigherVote {
+                        higher: vote,
+                        mine: self.vote,
+                    }));
+                }
+                AppendEntriesResponse::Conflict => {
+                    debug_assert!(conflict.is_some(), "prev_log_id=None never conflict");
+                    let conflict = conflict.unwrap();
+
+                    // Continue to find the matching log id on follower.
+                    self.max_possible_matched_index = if conflict.index == 0 {
+                        None
+                    } else {
+                        Some(conflict.index - 1)
+                    };
+
+                    return Ok(());
+                }
             }
         }
+
+        // Set the need_to_replicate flag if there is more log to send, or some payloads are not acknowledged.
+        // Otherwise leave it as is.
+        self.need_to_replicate = has_more_logs || n_resps < n_payloads;
+        Ok(())
     }
 
     /// max_possible_matched_index is the least index for `prev_log_id` to form a consecutive log sequence
@@ -442,6 +563,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
     /// Update the `matched` and `max_possible_matched_index`, which both are for tracking
     /// follower replication(the left and right cursor in a bsearch).
     /// And also report the matched log id to RaftCore to commit an entry etc.
+    ///
+    /// It is called for every successful response. The matched log id is reported even if it did not change, e.g.,
+    /// for a heartbeat, so that RaftCore knows when the target last responded.
     #[tracing::instrument(level = "trace", skip(self))]
     fn update_matched(&mut self, new_matched: Option<LogId<C::NodeId>>) {
         tracing::debug!(
@@ -455,14 +579,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
 
         if self.matched < new_matched {
             self.matched = new_matched;
+            self.next_index.store(self.matched.next_index(), Ordering::Relaxed);
 
             tracing::debug!(target=%self.target, matched=?self.matched, "matched updated");
+        }
 
+        if let Some(matched) = self.matched {
             let _ = self.raft_core_tx.send(RaftMsg::UpdateReplicationMatched {
                 target: self.target,
-                // `self.matched < new_matched` implies new_matched can not be None.
-                // Thus unwrap is safe.
-                result: Ok(self.matched.unwrap()),
+                result: Ok(matched),
                 vote: self.vote,
                 membership_log_id: self.membership_log_id,
             });
@@ -483,6 +608,15 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                 tracing::trace!("snapshot needed: {}", needs_snap);
                 needs_snap
             }
+            SnapshotPolicy::SizeSinceLast(threshold) => {
+                let bytes = self.applied_log_sizes.lock().unwrap().bytes_after(self.matched.index());
+
+                let needs_snap = bytes >= *threshold;
+
+                tracing::trace!("snapshot needed: {}, lacking {} bytes", needs_snap, bytes);
+                needs_snap
+            }
+            SnapshotPolicy::Never => false,
         }
     }
 
@@ -790,6 +924,9 @@ impl<C: RaftTypeConfig, N: RaftNetworkFactory<C>, S: RaftStorage<C>> Replication
                     self.matched,
                 );
 
+                // Count it before reporting the matched log id, which triggers a metrics update.
+                self.snapshots_sent.fetch_add(1, Ordering::Relaxed);
+                self.applied_next_index.fetch_max(snapshot.meta.last_log_id.next_index(), Ordering::Relaxed);
                 self.update_matched(snapshot.meta.last_log_id);
 
                 return Ok(());
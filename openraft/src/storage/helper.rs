@@ -1,9 +1,16 @@
+use std::cmp::Ordering;
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::sync::Arc;
 
+use futures::stream;
+use futures::Stream;
+use futures::TryStreamExt;
+
 use crate::engine::LogIdList;
 use crate::internal_server_state::InternalServerState;
 use crate::EffectiveMembership;
+use crate::Entry;
 use crate::EntryPayload;
 use crate::LogId;
 use crate::LogIdOptionExt;
@@ -70,6 +77,35 @@ where
         })
     }
 
+    /// Returns a stream of the log entries in `range`, which are read from storage in batches of at most
+    /// `batch_size` entries.
+    ///
+    /// Unlike [`RaftLogReader::get_log_entries`](`crate::RaftLogReader::get_log_entries`), only one batch is held in
+    /// memory at a time, thus it is suitable for scanning a large range of logs. The stream ends after the first
+    /// error.
+    pub fn log_entries_stream(
+        &mut self,
+        range: Range<u64>,
+        batch_size: u64,
+    ) -> impl Stream<Item = Result<Entry<C>, StorageError<C::NodeId>>> + '_ {
+        let batch_size = std::cmp::max(batch_size, 1);
+        let end = range.end;
+
+        let batches = stream::try_unfold((&mut *self.sto, range.start), move |(sto, start)| async move {
+            if start >= end {
+                return Ok(None);
+            }
+
+            let batch_end = std::cmp::min(start.saturating_add(batch_size), end);
+            let entries = sto.get_log_entries(start..batch_end).await?;
+
+            let batch = stream::iter(entries.into_iter().map(Ok));
+            Ok::<_, StorageError<C::NodeId>>(Some((batch, (sto, batch_end))))
+        });
+
+        batches.try_flatten()
+    }
+
     /// Get the log id of the entry at `index`.
     pub async fn get_log_id(&mut self, log_index: u64) -> Result<LogId<C::NodeId>, StorageError<C::NodeId>> {
         let st = self.sto.get_log_state().await?;
@@ -99,15 +135,20 @@ where
     /// a follower only need to revert at most one membership log.
     ///
     /// Thus a raft node will only need to store at most two recent membership logs.
+    ///
+    /// The returned `committed` membership is never an uncommitted one:
+    /// it is either the one in state machine, or the former one of the last two membership logs.
+    /// The `effective` membership is the last one seen, no matter it is committed or not.
     pub async fn get_membership(&mut self) -> Result<MembershipState<C::NodeId, C::Node>, StorageError<C::NodeId>> {
-        let (_, sm_mem) = self.sto.last_applied_state().await?;
-
-        let sm_mem_next_index = sm_mem.log_id.next_index();
+        let (last_applied, sm_mem) = self.sto.last_applied_state().await?;
 
-        let log_mem = self.last_membership_in_log(sm_mem_next_index).await?;
+        // Membership logs upto `last_applied` are applied, the last of them is the one in state machine.
+        // Only the tail of the log that is not applied has to be scanned.
+        let log_mem = self.last_membership_in_log(last_applied.next_index()).await?;
         tracing::debug!(membership_in_sm=?sm_mem, membership_in_log=?log_mem, "RaftStorage::get_membership");
 
         // There 2 membership configs in logs.
+        // The former must have been committed, otherwise the latter could not be proposed.
         if log_mem.len() == 2 {
             return Ok(MembershipState {
                 committed: Arc::new(log_mem[0].clone()),
@@ -115,10 +156,11 @@ where
             });
         }
 
-        let effective = if log_mem.is_empty() {
-            sm_mem.clone()
-        } else {
-            log_mem[0].clone()
+        // A membership log is only loaded if it is after the one in state machine.
+        // Compare them anyway, in case a log reader returns more than it is asked for.
+        let effective = match log_mem.first() {
+            Some(log_em) if log_em.log_id.cmp_index(sm_mem.log_id) == Ordering::Greater => log_em.clone(),
+            _ => sm_mem.clone(),
         };
 
         let res = MembershipState {
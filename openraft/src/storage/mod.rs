@@ -3,6 +3,7 @@
 mod helper;
 mod snapshot_signature;
 use std::fmt::Debug;
+use std::ops::Bound;
 use std::ops::RangeBounds;
 
 use async_trait::async_trait;
@@ -17,7 +18,9 @@ use crate::membership::EffectiveMembership;
 use crate::node::Node;
 use crate::raft_types::SnapshotId;
 use crate::Entry;
+use crate::EntryPayload;
 use crate::LogId;
+use crate::LogIdOptionExt;
 use crate::MessageSummary;
 use crate::NodeId;
 use crate::RaftTypeConfig;
@@ -99,6 +102,17 @@ pub struct LogState<C: RaftTypeConfig> {
     pub last_log_id: Option<LogId<C::NodeId>>,
 }
 
+/// The amount of log held in storage, for capacity planning.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LogStats {
+    /// The number of log entries present in storage, excluding the purged ones.
+    pub entries: u64,
+
+    /// The approximate size in bytes of the present log entries, or `None` if the storage does not know it.
+    pub bytes: Option<u64>,
+}
+
 /// A trait defining the interface for a Raft log subsystem.
 ///
 /// This interface is accessed read-only from replica streams.
@@ -133,6 +147,55 @@ where C: RaftTypeConfig
         Ok(res.pop())
     }
 
+    /// Try to get the term of the log entry at `log_index`.
+    ///
+    /// It does not return an error if the log entry at `log_index` is not found.
+    ///
+    /// The default implementation loads the entire entry with `try_get_log_entry()`.
+    /// An implementation should override it if it is able to read the log id without reading or cloning the payload.
+    async fn get_log_term(&mut self, log_index: u64) -> Result<Option<u64>, StorageError<C::NodeId>> {
+        let ent = self.try_get_log_entry(log_index).await?;
+        Ok(ent.map(|x| x.log_id.leader_id.term))
+    }
+
+    /// Get a series of log entries from storage, reading at most `max_entries` entries or `max_bytes` bytes.
+    ///
+    /// Entries in `range` are read in order, and reading stops before either limit is exceeded. The first entry is
+    /// always returned even if it is larger than `max_bytes`, so that a caller always makes progress. The caller
+    /// continues reading from the index following the last returned entry.
+    ///
+    /// Entry that is not found is allowed, as with `try_get_log_entries()`.
+    ///
+    /// The default implementation only respects `max_entries`, because the size of an entry is unknown to a log
+    /// reader. An implementation should override it if it is able to tell the size of an entry.
+    async fn get_log_entries_limited<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+        max_entries: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        let _ = max_bytes;
+
+        let start = match range.start_bound() {
+            Bound::Included(i) => *i,
+            Bound::Excluded(i) => i.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(i) => i.saturating_add(1),
+            Bound::Excluded(i) => *i,
+            Bound::Unbounded => u64::MAX,
+        };
+
+        let end = std::cmp::min(end, start.saturating_add(max_entries));
+        if start >= end {
+            return Ok(vec![]);
+        }
+
+        self.try_get_log_entries(start..end).await
+    }
+
     /// Returns the last deleted log id and the last log id.
     ///
     /// The impl should not consider the applied log id in state machine.
@@ -201,6 +264,11 @@ where C: RaftTypeConfig
     ///
     /// See the [storage chapter of the guide](https://datafuselabs.github.io/openraft/getting-started.html#implement-raftstorage)
     /// for details on where and how this is used.
+    ///
+    /// Raft only accesses snapshot data through `AsyncRead`, `AsyncWrite` and `AsyncSeek`: it reads a snapshot in
+    /// chunks to send it to other nodes, and writes received chunks at their offsets. It never requires the whole
+    /// snapshot in memory, thus it does not have to be an in-memory buffer such as `Cursor<Vec<u8>>`. E.g., it can be
+    /// a file handle, so that a large snapshot is streamed from and to disk.
     type SnapshotData: AsyncRead + AsyncWrite + AsyncSeek + Send + Sync + Unpin + 'static;
 
     /// Log reader type.
@@ -223,6 +291,29 @@ where C: RaftTypeConfig
     /// sync primitives to serialize access to the common internal object, if needed.
     async fn get_log_reader(&mut self) -> Self::LogReader;
 
+    /// Returns the size in bytes of `payload` when it is serialized into the log.
+    ///
+    /// It is used to enforce `Config::max_entry_bytes` on client writes.
+    /// The default implementation returns `None`, i.e., the size is unknown and the limit is not enforced.
+    fn serialized_size(&self, payload: &EntryPayload<C>) -> Option<u64> {
+        let _ = payload;
+        None
+    }
+
+    /// Returns the number and the approximate total size of the log entries in storage.
+    ///
+    /// It is polled by `RaftCore` on every tick and reported in `RaftMetrics::log_stats`.
+    /// The default implementation computes the number of entries from [`RaftLogReader::get_log_state`] and
+    /// leaves the size unknown.
+    async fn log_stats(&mut self) -> Result<LogStats, StorageError<C::NodeId>> {
+        let st = self.get_log_state().await?;
+
+        Ok(LogStats {
+            entries: st.last_log_id.next_index() - st.last_purged_log_id.next_index(),
+            bytes: None,
+        })
+    }
+
     /// Append a payload of entries to the log.
     ///
     /// Though the entries will always be presented in order, each entry's index should be used to
@@ -308,6 +399,18 @@ where C: RaftTypeConfig
     async fn get_current_snapshot(
         &mut self,
     ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>>;
+
+    /// Returns the snapshot with the given `snapshot_id`, or `None` if the storage no longer keeps it.
+    ///
+    /// It is meant for debugging, or for resuming an interrupted transfer of a snapshot that is no longer the
+    /// current one. The default implementation only looks at the current snapshot.
+    async fn get_snapshot_by_id(
+        &mut self,
+        snapshot_id: &str,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>> {
+        let snapshot = self.get_current_snapshot().await?;
+        Ok(snapshot.filter(|s| s.meta.snapshot_id == snapshot_id))
+    }
 }
 
 /// APIs for debugging a store.
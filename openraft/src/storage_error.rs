@@ -162,6 +162,12 @@ pub enum Violation<NID: NodeId> {
         last_applied: Option<LogId<NID>>,
         purge_upto: LogId<NID>,
     },
+
+    #[error("snapshot can not revert state machine, last_applied: {last_applied:?}, snapshot last_log_id: {snapshot_last_log_id:?}")]
+    SnapshotNotAscending {
+        last_applied: Option<LogId<NID>>,
+        snapshot_last_log_id: Option<LogId<NID>>,
+    },
 }
 
 /// A storage error could be either a defensive check error or an error occurred when doing the actual io operation.
@@ -220,6 +226,9 @@ where NID: NodeId
     verb: ErrorVerb,
     source: AnyError,
     backtrace: Option<String>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    transient: bool,
 }
 
 impl<NID> std::fmt::Display for StorageIOError<NID>
@@ -239,6 +248,38 @@ where NID: NodeId
             verb,
             source,
             backtrace: anyerror::backtrace_str(),
+            transient: false,
         }
     }
+
+    /// Mark this error as transient, e.g., a temporarily full disk or a timeout waiting for a lock.
+    ///
+    /// `RaftCore` retries `RaftStorage::append_to_log()` and `RaftStorage::apply_to_state_machine()` a few times
+    /// when they fail with a transient error, before shutting down the node, see `Config::storage_retry_count`.
+    ///
+    /// A storage must mark an error as transient only if the failed call took no effect at all. E.g., an
+    /// `apply_to_state_machine()` that applied some of the entries before failing must not return a transient error,
+    /// otherwise the retry applies these entries twice.
+    ///
+    /// A received snapshot that can not be decoded is likely corrupted during transfer. `install_snapshot()` should
+    /// return a transient error for it, so that the caller knows re-sending the snapshot may fix it.
+    pub fn transient(mut self) -> Self {
+        self.transient = true;
+        self
+    }
+
+    /// Returns `true` if this error is marked as transient.
+    pub fn is_transient(&self) -> bool {
+        self.transient
+    }
+
+    /// The subject on which the error occurs.
+    pub fn subject(&self) -> &ErrorSubject<NID> {
+        &self.subject
+    }
+
+    /// What it is doing when the error occurs.
+    pub fn verb(&self) -> &ErrorVerb {
+        &self.verb
+    }
 }
@@ -10,12 +10,14 @@ use crate::async_trait::async_trait;
 use crate::defensive::DefensiveCheckBase;
 use crate::membership::EffectiveMembership;
 use crate::storage::LogState;
+use crate::storage::LogStats;
 use crate::storage::RaftLogReader;
 use crate::storage::RaftSnapshotBuilder;
 use crate::storage::Snapshot;
 use crate::summary::MessageSummary;
 use crate::DefensiveCheck;
 use crate::Entry;
+use crate::EntryPayload;
 use crate::LogId;
 use crate::RaftStorage;
 use crate::RaftStorageDebug;
@@ -124,6 +126,15 @@ where
         self.inner().save_vote(vote).await
     }
 
+    fn serialized_size(&self, payload: &EntryPayload<C>) -> Option<u64> {
+        self.inner.serialized_size(payload)
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn log_stats(&mut self) -> Result<LogStats, StorageError<C::NodeId>> {
+        self.inner().log_stats().await
+    }
+
     #[tracing::instrument(level = "trace", skip(self))]
     async fn read_vote(&mut self) -> Result<Option<Vote<C::NodeId>>, StorageError<C::NodeId>> {
         self.inner().read_vote().await
@@ -178,6 +189,7 @@ where
         meta: &SnapshotMeta<C::NodeId, C::Node>,
         snapshot: Box<Self::SnapshotData>,
     ) -> Result<(), StorageError<C::NodeId>> {
+        self.defensive_install_snapshot_ge_last_applied(meta).await?;
         self.inner().install_snapshot(meta, snapshot).await
     }
 
@@ -188,6 +200,14 @@ where
         self.inner().get_current_snapshot().await
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn get_snapshot_by_id(
+        &mut self,
+        snapshot_id: &str,
+    ) -> Result<Option<Snapshot<C::NodeId, C::Node, Self::SnapshotData>>, StorageError<C::NodeId>> {
+        self.inner().get_snapshot_by_id(snapshot_id).await
+    }
+
     async fn get_log_reader(&mut self) -> Self::LogReader {
         LogReaderExt {
             defensive: self.defensive.clone(),
@@ -213,6 +233,22 @@ impl<C: RaftTypeConfig, T: RaftStorage<C>> RaftLogReader<C> for StoreExt<C, T> {
         self.inner().try_get_log_entries(range).await
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn get_log_term(&mut self, log_index: u64) -> Result<Option<u64>, StorageError<C::NodeId>> {
+        self.inner().get_log_term(log_index).await
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn get_log_entries_limited<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+        max_entries: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.defensive_nonempty_range(range.clone())?;
+        self.inner().get_log_entries_limited(range, max_entries, max_bytes).await
+    }
+
     async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
         self.defensive_no_dirty_log().await?;
         self.inner().get_log_state().await
@@ -255,6 +291,22 @@ impl<C: RaftTypeConfig, T: RaftStorage<C>> RaftLogReader<C> for LogReaderExt<C,
         self.inner.try_get_log_entries(range).await
     }
 
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn get_log_term(&mut self, log_index: u64) -> Result<Option<u64>, StorageError<C::NodeId>> {
+        self.inner.get_log_term(log_index).await
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn get_log_entries_limited<RB: RangeBounds<u64> + Clone + Debug + Send + Sync>(
+        &mut self,
+        range: RB,
+        max_entries: u64,
+        max_bytes: u64,
+    ) -> Result<Vec<Entry<C>>, StorageError<C::NodeId>> {
+        self.defensive_nonempty_range(range.clone())?;
+        self.inner.get_log_entries_limited(range, max_entries, max_bytes).await
+    }
+
     async fn get_log_state(&mut self) -> Result<LogState<C>, StorageError<C::NodeId>> {
         // TODO self.defensive_no_dirty_log().await?;
         // Log state via LogReader is requested exactly at one place in the replication loop.
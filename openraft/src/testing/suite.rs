@@ -3,6 +3,7 @@ use std::future::Future;
 use std::marker::PhantomData;
 use std::option::Option::None;
 
+use futures::TryStreamExt;
 use maplit::btreeset;
 
 use crate::membership::EffectiveMembership;
@@ -24,6 +25,7 @@ use crate::NodeId;
 use crate::RaftSnapshotBuilder;
 use crate::RaftStorage;
 use crate::RaftTypeConfig;
+use crate::SnapshotMeta;
 use crate::StorageError;
 use crate::Violation;
 use crate::Vote;
@@ -86,6 +88,8 @@ where
         run_fut(builder.run_test(Self::get_membership_initial))?;
         run_fut(builder.run_test(Self::get_membership_from_log_and_empty_sm))?;
         run_fut(builder.run_test(Self::get_membership_from_log_and_sm))?;
+        run_fut(builder.run_test(Self::get_membership_committed_and_uncommitted_in_log))?;
+        run_fut(builder.run_test(Self::get_membership_in_log_le_last_applied))?;
         run_fut(builder.run_test(Self::get_initial_state_without_init))?;
         run_fut(builder.run_test(Self::get_initial_state_membership_from_log_and_sm))?;
         run_fut(builder.run_test(Self::get_initial_state_with_state))?;
@@ -95,6 +99,10 @@ where
         run_fut(builder.run_test(Self::save_vote))?;
         run_fut(builder.run_test(Self::get_log_entries))?;
         run_fut(builder.run_test(Self::try_get_log_entry))?;
+        run_fut(builder.run_test(Self::get_log_term))?;
+        run_fut(builder.run_test(Self::get_log_entries_limited))?;
+        run_fut(builder.run_test(Self::log_stats))?;
+        run_fut(builder.run_test(Self::log_entries_stream))?;
         run_fut(builder.run_test(Self::initial_logs))?;
         run_fut(builder.run_test(Self::get_log_state))?;
         run_fut(builder.run_test(Self::get_log_id))?;
@@ -404,6 +412,97 @@ where
         Ok(())
     }
 
+    pub async fn get_membership_committed_and_uncommitted_in_log(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let mem = |index: u64, members| Entry {
+            log_id: log_id(1, index),
+            payload: EntryPayload::Membership(Membership::new(vec![members], None)),
+        };
+
+        let mut logs = vec![blank::<C>(0, 0)];
+        for i in 1..=12 {
+            logs.push(match i {
+                10 => mem(10, btreeset! {1,2,3}),
+                12 => mem(12, btreeset! {4,5,6}),
+                _ => blank(1, i),
+            });
+        }
+
+        store.append_to_log(&logs.iter().collect::<Vec<_>>()).await?;
+
+        tracing::info!("--- membership at 10 is committed but not applied, membership at 12 is not committed");
+        {
+            store.apply_to_state_machine(&logs[0..=9].iter().collect::<Vec<_>>()).await?;
+
+            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+
+            assert_eq!(Some(log_id(1, 10)), mem_state.committed.log_id);
+            assert_eq!(
+                Membership::new(vec![btreeset! {1,2,3}], None),
+                mem_state.committed.membership,
+            );
+
+            // A membership takes effect at once it is appended, no matter it is committed or not.
+            assert_eq!(Some(log_id(1, 12)), mem_state.effective.log_id);
+        }
+
+        tracing::info!("--- membership at 10 is applied, membership at 12 is not committed");
+        {
+            store.apply_to_state_machine(&[&logs[10]]).await?;
+
+            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+
+            assert_eq!(Some(log_id(1, 10)), mem_state.committed.log_id);
+            assert_eq!(
+                Membership::new(vec![btreeset! {1,2,3}], None),
+                mem_state.committed.membership,
+            );
+            assert_eq!(Some(log_id(1, 12)), mem_state.effective.log_id);
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_membership_in_log_le_last_applied(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let mem = |index: u64, members| Entry {
+            log_id: log_id(1, index),
+            payload: EntryPayload::Membership(Membership::new(vec![members], None)),
+        };
+
+        tracing::info!("--- the only membership log is at or below last_applied, read from state machine");
+        {
+            // The membership log in the log store differs from the applied one, to tell where it is read from.
+            store.append_to_log(&[&blank(0, 0), &blank(1, 1), &blank(1, 2), &mem(3, btreeset! {1,2,3})]).await?;
+            store
+                .apply_to_state_machine(&[&blank(0, 0), &mem(1, btreeset! {3,4,5}), &blank(1, 2), &blank(1, 3)])
+                .await?;
+
+            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+
+            assert_eq!(Some(log_id(1, 1)), mem_state.committed.log_id);
+            assert_eq!(Some(log_id(1, 1)), mem_state.effective.log_id);
+            assert_eq!(
+                Membership::new(vec![btreeset! {3,4,5}], None),
+                mem_state.effective.membership,
+            );
+        }
+
+        tracing::info!("--- a membership log above last_applied is effective");
+        {
+            store.append_to_log(&[&blank(1, 4), &mem(5, btreeset! {7,8,9})]).await?;
+
+            let mem_state = StorageHelper::new(&mut store).get_membership().await?;
+
+            assert_eq!(Some(log_id(1, 1)), mem_state.committed.log_id);
+            assert_eq!(Some(log_id(1, 5)), mem_state.effective.log_id);
+            assert_eq!(
+                Membership::new(vec![btreeset! {7,8,9}], None),
+                mem_state.effective.membership,
+            );
+        }
+
+        Ok(())
+    }
+
     pub async fn get_initial_state_without_init(mut store: S) -> Result<(), StorageError<C::NodeId>> {
         let initial = StorageHelper::new(&mut store).get_initial_state().await?;
         assert_eq!(RaftState::default(), initial, "uninitialized state");
@@ -752,6 +851,77 @@ where
         Ok(())
     }
 
+    pub async fn get_log_term(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        store.purge_logs_upto(LogId::new(LeaderId::new(0, C::NodeId::default()), 0)).await?;
+
+        assert_eq!(Some(1), store.get_log_term(3).await?);
+        assert_eq!(Some(1), store.get_log_term(10).await?);
+
+        // Purged or absent.
+        assert_eq!(None, store.get_log_term(0).await?);
+        assert_eq!(None, store.get_log_term(11).await?);
+
+        Ok(())
+    }
+
+    pub async fn get_log_entries_limited(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        let ents = store.get_log_entries_limited(3..8, 2, u64::MAX).await?;
+        assert_eq!(vec![3, 4], ents.iter().map(|x| x.log_id.index).collect::<Vec<_>>());
+
+        let ents = store.get_log_entries_limited(3..8, 100, u64::MAX).await?;
+        assert_eq!(
+            vec![3, 4, 5, 6, 7],
+            ents.iter().map(|x| x.log_id.index).collect::<Vec<_>>()
+        );
+
+        // At least one entry is returned, no matter how small `max_bytes` is.
+        let ents = store.get_log_entries_limited(3..8, 100, 0).await?;
+        assert_eq!(Some(3), ents.first().map(|x| x.log_id.index));
+
+        Ok(())
+    }
+
+    pub async fn log_stats(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        let stats = store.log_stats().await?;
+        assert_eq!(0, stats.entries);
+
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        let stats = store.log_stats().await?;
+        assert_eq!(11, stats.entries);
+
+        store.purge_logs_upto(LogId::new(LeaderId::new(1, NODE_ID.into()), 3)).await?;
+
+        let stats = store.log_stats().await?;
+        assert_eq!(7, stats.entries);
+
+        store.delete_conflict_logs_since(LogId::new(LeaderId::new(1, NODE_ID.into()), 8)).await?;
+
+        let stats = store.log_stats().await?;
+        assert_eq!(4, stats.entries);
+
+        Ok(())
+    }
+
+    pub async fn log_entries_stream(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        Self::feed_10_logs_vote_self(&mut store).await?;
+
+        let ents: Vec<_> = StorageHelper::new(&mut store).log_entries_stream(2..9, 3).try_collect().await?;
+        assert_eq!(
+            vec![2, 3, 4, 5, 6, 7, 8],
+            ents.iter().map(|x| x.log_id.index).collect::<Vec<_>>()
+        );
+
+        let ents: Vec<_> = StorageHelper::new(&mut store).log_entries_stream(5..5, 3).try_collect().await?;
+        assert!(ents.is_empty());
+
+        Ok(())
+    }
+
     pub async fn initial_logs(mut store: S) -> Result<(), StorageError<C::NodeId>> {
         let ent = store.try_get_log_entry(0).await?;
         assert!(ent.is_none(), "store initialized");
@@ -1259,6 +1429,7 @@ where
         run_fut(builder.run_test(Self::df_apply_gt_last_applied_id))?;
         run_fut(builder.run_test(Self::df_purge_applied_le_last_applied))?;
         run_fut(builder.run_test(Self::df_delete_conflict_gt_last_applied))?;
+        run_fut(builder.run_test(Self::df_install_snapshot_ge_last_applied))?;
 
         Ok(())
     }
@@ -1786,6 +1957,35 @@ where
 
         Ok(())
     }
+
+    pub async fn df_install_snapshot_ge_last_applied(mut store: S) -> Result<(), StorageError<C::NodeId>> {
+        store.apply_to_state_machine(&[&blank(0, 0), &blank(3, 1), &blank(3, 2)]).await?;
+
+        tracing::info!("--- install a snapshot that reverts state machine");
+        {
+            let meta = SnapshotMeta {
+                last_log_id: Some(log_id(3, 1)),
+                last_membership: EffectiveMembership::default(),
+                snapshot_id: "3-1-0".to_string(),
+            };
+
+            let data = store.begin_receiving_snapshot().await?;
+            let res = store.install_snapshot(&meta, data).await;
+
+            // A defensive error is never transient, thus it is not retried.
+            let e = res.unwrap_err().into_defensive().unwrap();
+            assert_eq!(ErrorSubject::Snapshot(meta.signature()), e.subject);
+            assert_eq!(
+                Violation::SnapshotNotAscending {
+                    last_applied: Some(log_id(3, 2)),
+                    snapshot_last_log_id: Some(log_id(3, 1)),
+                },
+                e.violation
+            );
+        }
+
+        Ok(())
+    }
 }
 
 fn log_id<NID: NodeId>(term: u64, index: u64) -> LogId<NID>
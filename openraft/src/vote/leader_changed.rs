@@ -0,0 +1,16 @@
+use crate::NodeId;
+
+/// A change of the leader this node believes in, including this node becoming the leader or stepping down.
+///
+/// Subscribe to them with [`Raft::leader_changes()`](`crate::Raft::leader_changes`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct LeaderChanged<NID: NodeId> {
+    /// The term of this node when the change is seen.
+    ///
+    /// A consumer can ignore an event with a term smaller than one it has already seen.
+    pub term: u64,
+
+    /// The new leader, or `None` if this node does not know a leader, e.g., during an election.
+    pub leader: Option<NID>,
+}
@@ -0,0 +1,18 @@
+use crate::NodeId;
+
+/// A change of the leader this node believes in, caused by an AppendEntries request from the new leader.
+///
+/// Leader changes caused by an election are not included.
+/// Subscribe to them with [`Raft::leader_events()`](`crate::Raft::leader_events`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct LeaderEvent<NID: NodeId> {
+    /// The leader before receiving the AppendEntries request, if any.
+    pub prev_leader: Option<NID>,
+
+    /// The leader that sent the AppendEntries request.
+    pub leader: NID,
+
+    /// The term of the new leader.
+    pub term: u64,
+}
@@ -1,7 +1,14 @@
+mod leader_changed;
+mod leader_event;
 mod leader_id;
 #[allow(clippy::module_inception)] mod vote;
+mod vote_event;
 
+pub use leader_changed::LeaderChanged;
+pub use leader_event::LeaderEvent;
 pub use leader_id::LeaderId;
 pub use vote::Vote;
+pub use vote_event::VoteEvent;
+pub use vote_event::VoteReason;
 
 #[cfg(test)] mod leader_id_test;
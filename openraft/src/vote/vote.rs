@@ -5,6 +5,10 @@ use crate::MessageSummary;
 use crate::NodeId;
 
 /// `Vote` represent the privilege of a node.
+///
+/// Votes are totally ordered by `(term, node_id, committed)`.
+/// When two candidates with equally fresh logs compete in the same term, a voter grants the one with the greater
+/// `node_id` even if it has granted the other one, thus the tie is broken deterministically.
 #[derive(Debug, Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
 pub struct Vote<NID: NodeId> {
@@ -0,0 +1,37 @@
+use crate::LogId;
+use crate::NodeId;
+use crate::Vote;
+
+/// Why a vote request is granted or rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub enum VoteReason<NID: NodeId> {
+    /// The vote is granted.
+    Granted,
+
+    /// The term of the candidate is lower than the term of this node.
+    TermTooLow { my_vote: Vote<NID> },
+
+    /// This node has already voted for another candidate, or has seen a leader, in the same term.
+    AlreadyVoted { my_vote: Vote<NID> },
+
+    /// The last log id of the candidate is not as up-to-date as the one of this node.
+    LogNotFreshEnough { my_last_log_id: Option<LogId<NID>> },
+}
+
+/// A vote request received by this node and the decision on it.
+///
+/// Subscribe to them with [`Raft::vote_events()`](`crate::Raft::vote_events`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize), serde(bound = ""))]
+pub struct VoteEvent<NID: NodeId> {
+    /// The candidate that sent the vote request.
+    pub from: NID,
+
+    /// The term the candidate requested a vote for.
+    pub term: u64,
+
+    pub granted: bool,
+
+    pub reason: VoteReason<NID>,
+}
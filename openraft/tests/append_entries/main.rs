@@ -12,9 +12,13 @@ mod t10_conflict_with_empty_entries;
 mod t10_see_higher_vote;
 mod t20_append_conflicts;
 mod t30_append_inconsistent_log;
+mod t35_replication_next_index;
+mod t36_min_applied_across_cluster;
+mod t37_leader_change_by_append_entries;
 mod t40_append_updates_membership;
 mod t50_append_entries_with_bigger_term;
 mod t50_replication_1_voter_to_isolated_learner;
 mod t60_enable_heartbeat;
 mod t60_large_heartbeat;
+mod t70_append_entries_batch;
 mod t90_issue_216_stale_last_log_id;
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::ServerState;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::replication_states()` exposes the `next_index` the leader sends to a follower.
+///
+/// What does this test do?
+///
+/// - Fake a cluster of node 0 and 1, both have 100 uncommitted logs that diverge after the initial logs: node 0 has
+///   logs of term 3 and node 1 has logs of term 2.
+/// - Elect node 0, and keep collecting the `next_index` to node 1 while the leader searches for the matching log.
+/// - Assert `next_index` moves backward during searching, and then advances to the end of the leader log.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn replication_next_index() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+
+    tracing::info!("--- remove all nodes and fake diverged logs");
+    {
+        let (r0, mut sto0) = router.remove_node(0).unwrap();
+        let (r1, mut sto1) = router.remove_node(1).unwrap();
+
+        r0.shutdown().await?;
+        r1.shutdown().await?;
+
+        for i in log_index + 1..=100 {
+            sto0.append_to_log(&[&Entry {
+                log_id: LogId::new(LeaderId::new(3, 0), i),
+                payload: EntryPayload::Blank,
+            }])
+            .await?;
+
+            sto1.append_to_log(&[&Entry {
+                log_id: LogId::new(LeaderId::new(2, 0), i),
+                payload: EntryPayload::Blank,
+            }])
+            .await?;
+        }
+
+        sto0.save_vote(&Vote::new(3, 0)).await?;
+        sto1.save_vote(&Vote::new(2, 0)).await?;
+
+        router.network_send_delay(20);
+
+        router.new_raft_node_with_sto(0, sto0);
+        router.new_raft_node_with_sto(1, sto1);
+    }
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- collect next_index to node 1 while electing node 0");
+    let observed = {
+        let n = n0.clone();
+        let h = tokio::spawn(async move {
+            let mut observed: Vec<u64> = vec![];
+            loop {
+                // Node 1 is not reported until the replication to it starts.
+                if let Some(st) = n.replication_states().await.ok().and_then(|states| states.get(&1).cloned()) {
+                    if observed.last() != Some(&st.next_index) {
+                        observed.push(st.next_index);
+                    }
+
+                    // The leader appends a blank log after being elected.
+                    if st.matched.map(|x| x.index) == Some(101) {
+                        return observed;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        });
+
+        n0.trigger_elect().await?;
+        router.wait(&0, timeout()).state(ServerState::Leader, "node 0 becomes leader").await?;
+
+        tokio::time::timeout(Duration::from_millis(5_000), h).await??
+    };
+
+    tracing::info!("--- observed next_index: {:?}", observed);
+    {
+        let decreased = observed.windows(2).any(|w| w[1] < w[0]);
+        assert!(decreased, "next_index moves backward while searching: {:?}", observed);
+
+        assert_eq!(
+            Some(&102),
+            observed.last(),
+            "next_index advances to the end: {:?}",
+            observed
+        );
+
+        let states = n0.replication_states().await?;
+        assert_eq!(102, states.get(&1).unwrap().next_index);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
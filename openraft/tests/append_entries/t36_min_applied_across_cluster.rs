@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LogIdOptionExt;
+use tokio::time::sleep;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::MemRaft;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::min_applied_across_cluster()` returns the applied index of the most lagging node.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes, wait for the leader to learn every node has applied all logs.
+/// - Isolate node 2 and write more logs.
+/// - Assert the minimum equals the applied index of node 2, which is lagging.
+/// - Restore node 2 and write one more log, assert the minimum catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn min_applied_across_cluster() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- every node applied all logs");
+    {
+        wait_min_applied(&n0, Some(log_index)).await?;
+    }
+
+    tracing::info!("--- isolate node 2 and write logs");
+    let lagging_applied = {
+        router.isolate_node(2);
+
+        router.client_request_many(0, "foo", 10).await?;
+        log_index += 10;
+        router.wait_for_log(&btreeset! {0,1}, Some(log_index), None, "write logs").await?;
+
+        let applied = router.get_metrics(&2)?.last_applied.index();
+        assert!(applied < Some(log_index));
+
+        let min = n0.min_applied_across_cluster().await?;
+        assert_eq!(applied, min, "the minimum is the applied index of the lagging node-2");
+
+        let states = n0.replication_states().await?;
+        assert!(states[&1].applied >= applied);
+        assert_eq!(applied, states[&2].applied);
+
+        applied
+    };
+
+    tracing::info!("--- restore node 2, the minimum catches up");
+    {
+        router.restore_node(2);
+
+        router.client_request_many(0, "foo", 1).await?;
+        log_index += 1;
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), None, "node-2 catches up").await?;
+
+        // node-2 learns the latest committed log id with the next replication RPC.
+        wait_min_applied(&n0, Some(log_index - 1)).await?;
+        assert!(n0.min_applied_across_cluster().await? > lagging_applied);
+    }
+
+    Ok(())
+}
+
+/// Wait until `min_applied_across_cluster()` reaches at least `want`.
+async fn wait_min_applied(raft: &MemRaft, want: Option<u64>) -> Result<()> {
+    let deadline = Instant::now() + Duration::from_millis(3_000);
+    loop {
+        let min = raft.min_applied_across_cluster().await?;
+        if min >= want {
+            return Ok(());
+        }
+        if Instant::now() > deadline {
+            panic!("timeout waiting for min applied >= {:?}, got: {:?}", want, min);
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+}
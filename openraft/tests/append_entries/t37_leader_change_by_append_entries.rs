@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::AppendEntriesRequest;
+use openraft::Config;
+use openraft::LeaderEvent;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftNetwork;
+use openraft::RaftNetworkFactory;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A follower learns a new leader from an AppendEntries request, without taking part in the election.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes with node 0 as the leader.
+/// - Send an AppendEntries request from node 1 with a greater committed vote to node 2.
+/// - Assert node 2 believes node 1 is the leader and a `LeaderEvent` is sent.
+/// - Assert another AppendEntries from the same leader does not send an event.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leader_change_by_append_entries() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n2 = router.get_raft_handle(&2)?;
+    assert_eq!(Some(0), n2.current_leader().await);
+
+    let mut leader_events = n2.leader_events();
+
+    let last_log_id = Some(LogId::new(LeaderId::new(1, 0), log_index));
+    let req = AppendEntriesRequest {
+        vote: Vote::new_committed(2, 1),
+        prev_log_id: last_log_id,
+        entries: vec![],
+        leader_commit: last_log_id,
+    };
+
+    tracing::info!("--- node 2 receives AppendEntries from node 1 at term 2");
+    {
+        let resp = router.new_client(2, &()).await?.send_append_entries(req.clone()).await?;
+        assert!(resp.is_success());
+
+        n2.wait(timeout()).current_leader(1, "node 2 follows node 1").await?;
+        assert_eq!(Some(1), n2.current_leader().await);
+
+        let ev = tokio::time::timeout(timeout().unwrap(), leader_events.recv()).await??;
+        assert_eq!(
+            LeaderEvent {
+                prev_leader: Some(0),
+                leader: 1,
+                term: 2,
+            },
+            ev
+        );
+    }
+
+    tracing::info!("--- another AppendEntries from the same leader sends no event");
+    {
+        let resp = router.new_client(2, &()).await?.send_append_entries(req).await?;
+        assert!(resp.is_success());
+
+        assert!(leader_events.try_recv().is_err());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A lagging follower catches up with several AppendEntries payloads sent in one batch.
+///
+/// What does this test do?
+///
+/// - Bring up a leader and a learner, with small payloads and up to 4 payloads in a batch.
+/// - Isolate the learner and write logs on the leader.
+/// - Restore the learner, it receives all of the logs.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn append_entries_batch() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            max_payload_entries: 2,
+            max_append_batches: 4,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {1}).await?;
+
+    tracing::info!("--- isolate the learner and write logs");
+    {
+        router.isolate_node(1);
+
+        router.client_request_many(0, "foo", 30).await?;
+        log_index += 30;
+
+        router.wait(&0, timeout()).log(Some(log_index), "leader applied logs").await?;
+    }
+
+    tracing::info!("--- restore the learner, it catches up");
+    {
+        router.restore_node(1);
+
+        router.wait(&1, timeout()).log(Some(log_index), "learner caught up").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
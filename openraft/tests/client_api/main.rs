@@ -9,5 +9,11 @@ mod fixtures;
 // The later tests may depend on the earlier ones.
 
 mod t10_client_writes;
+mod t11_client_write_ticket;
+mod t12_max_entry_bytes;
+mod t13_client_write_timeout;
+mod t14_apply_in_batches;
 mod t20_client_reads;
+mod t21_consistent_read;
+mod t22_read_index;
 mod t50_lagging_network_write;
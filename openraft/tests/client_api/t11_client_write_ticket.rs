@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Pipeline client writes with `Raft::client_write_ticket()`.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes.
+/// - Submit 50 writes without waiting for them to be applied, collect the tickets.
+/// - Await all of the tickets and assert every one resolves to the response of its own write.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_ticket() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- submit writes and collect tickets");
+    let mut tickets = vec![];
+    for i in 0..50 {
+        let ticket = n0
+            .client_write_ticket(ClientRequest {
+                client: "foo".to_string(),
+                serial: i,
+                status: format!("request-{}", i),
+            })
+            .await?;
+
+        log_index += 1;
+        assert_eq!(
+            log_index, ticket.log_id.index,
+            "ticket has the log id of the appended entry"
+        );
+
+        tickets.push(ticket);
+    }
+
+    tracing::info!("--- await all tickets");
+    for (i, ticket) in tickets.into_iter().enumerate() {
+        let log_id = ticket.log_id;
+        let resp = ticket.future.await?;
+
+        assert_eq!(log_id, resp.log_id);
+        // The response is the previous status of the client.
+        let want = if i == 0 {
+            "ClientResponse(None)".to_string()
+        } else {
+            format!("ClientResponse(Some(\"request-{}\"))", i - 1)
+        };
+        assert_eq!(want, format!("{:?}", resp.data));
+    }
+
+    router
+        .wait_for_log(
+            &btreeset! {0,1,2},
+            Some(log_index),
+            None,
+            "all written logs are replicated",
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::error::EntryTooLarge;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::RaftStorage;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A client write entry larger than `Config::max_entry_bytes` is rejected before it enters the log.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes, with `max_entry_bytes` set to the size of an entry with a 100 bytes status.
+/// - Write an entry that is 1 byte larger, assert it is rejected and no log is appended.
+/// - Write an entry that is exactly at the limit, assert it succeeds.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn max_entry_bytes() -> Result<()> {
+    let req = |status_len: usize| ClientRequest {
+        client: "foo".to_string(),
+        serial: 1,
+        status: "x".repeat(status_len),
+    };
+
+    let sto = memstore::MemStore::new_async().await;
+    let max = sto.serialized_size(&EntryPayload::Normal(req(100))).unwrap();
+
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            max_entry_bytes: max,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- write an entry exceeding max_entry_bytes");
+    {
+        let res = n0.client_write(req(101)).await;
+        let err = res.unwrap_err();
+        assert_eq!(
+            ClientWriteError::EntryTooLarge(EntryTooLarge { size: max + 1, max }),
+            err
+        );
+
+        let res = n0.client_write_ticket(req(101)).await;
+        let err = res.unwrap_err();
+        assert_eq!(
+            ClientWriteError::EntryTooLarge(EntryTooLarge { size: max + 1, max }),
+            err
+        );
+
+        let metrics = n0.metrics().borrow().clone();
+        assert_eq!(Some(log_index), metrics.last_log_index, "no log is appended");
+    }
+
+    tracing::info!("--- write an entry at max_entry_bytes");
+    {
+        let resp = n0.client_write(req(100)).await?;
+        log_index += 1;
+
+        assert_eq!(log_index, resp.log_id.index);
+        router
+            .wait_for_log(
+                &btreeset! {0,1,2},
+                Some(log_index),
+                None,
+                "at-limit entry is replicated",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::client_write_with_timeout()` returns `Timeout` if the entry can not be committed in time, and the entry
+/// is still committed later when a quorum is back.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes and isolate both followers, so that the leader can not reach a quorum.
+/// - Write with a short timeout, assert it returns `Timeout` while the followers are still isolated.
+/// - Restore the followers and write again, assert both entries are committed on every node.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn client_write_timeout() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    let req = |serial: u64| ClientRequest {
+        client: "foo".to_string(),
+        serial,
+        status: format!("request-{}", serial),
+    };
+
+    tracing::info!("--- isolate followers, write with timeout");
+    {
+        router.isolate_node(1);
+        router.isolate_node(2);
+
+        let timeout = Duration::from_millis(200);
+        let res = n0.client_write_with_timeout(req(1), timeout).await;
+        log_index += 1;
+
+        match res {
+            Err(ClientWriteError::Timeout(t)) => {
+                assert_eq!(log_index, t.log_id.index);
+                assert_eq!(timeout, t.timeout);
+            }
+            _ => unreachable!("expect Timeout, got: {:?}", res),
+        }
+
+        let m = n0.metrics().borrow().clone();
+        assert_eq!(Some(log_index), m.last_log_index);
+        assert_eq!(Some(log_index - 1), m.last_applied.map(|x| x.index));
+    }
+
+    tracing::info!("--- restore followers, the timed out entry is committed with the next one");
+    {
+        router.restore_node(1);
+        router.restore_node(2);
+
+        let resp = n0.client_write_with_timeout(req(2), Duration::from_millis(3_000)).await?;
+        log_index += 1;
+        assert_eq!(log_index, resp.log_id.index);
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), None, "both entries are committed").await?;
+    }
+
+    Ok(())
+}
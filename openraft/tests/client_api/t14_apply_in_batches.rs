@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Logs committed at once are applied in batches of at most `max_apply_entries`.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes with election disabled, applying at most 3 logs in one call.
+/// - Isolate both followers so that no write is committed, and submit writes.
+/// - Restore the followers: all of the writes are committed at once.
+/// - Assert every write gets the response of its own log, in order.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn apply_in_batches() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            max_apply_entries: 3,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- isolate followers and submit writes");
+    router.isolate_node(1);
+    router.isolate_node(2);
+
+    let mut tickets = vec![];
+    for i in 0..20 {
+        let ticket = n0
+            .client_write_ticket(ClientRequest {
+                client: "foo".to_string(),
+                serial: i,
+                status: format!("request-{}", i),
+            })
+            .await?;
+
+        log_index += 1;
+        tickets.push(ticket);
+    }
+
+    tracing::info!("--- restore followers, writes are committed and applied in batches");
+    router.restore_node(1);
+    router.restore_node(2);
+
+    for (i, ticket) in tickets.into_iter().enumerate() {
+        let log_id = ticket.log_id;
+        let resp = ticket.future.await?;
+
+        assert_eq!(log_id, resp.log_id);
+        // The response is the previous status of the client.
+        let want = if i == 0 {
+            "ClientResponse(None)".to_string()
+        } else {
+            format!("ClientResponse(Some(\"request-{}\"))", i - 1)
+        };
+        assert_eq!(want, format!("{:?}", resp.data));
+    }
+
+    router
+        .wait_for_log(
+            &btreeset! {0,1,2},
+            Some(log_index),
+            None,
+            "all written logs are applied",
+        )
+        .await?;
+
+    Ok(())
+}
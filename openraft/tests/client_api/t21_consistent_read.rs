@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::error::CheckIsLeaderError;
+use openraft::Config;
+use openraft::RaftStorageDebug;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A consistent read on the leader observes a preceding write; on a follower it is rejected.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write a value through the leader.
+/// - read it back with `consistent_read()` on the leader and assert it reflects the write.
+/// - call `consistent_read()` on a follower and assert it returns `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn consistent_read() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write a value through the leader");
+    {
+        router
+            .send_client_request(0, ClientRequest {
+                client: "foo".to_string(),
+                serial: 1,
+                status: "bar".to_string(),
+            })
+            .await?;
+    }
+
+    tracing::info!("--- consistent_read on the leader reflects the write");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let mut sto0 = router.get_storage_handle(&0)?;
+
+        let got = n0
+            .consistent_read(|| async move { sto0.get_state_machine().await.data.client_status.get("foo").cloned() })
+            .await?;
+
+        assert_eq!(Some("bar".to_string()), got);
+    }
+
+    tracing::info!("--- consistent_read on a follower is rejected");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+
+        let res = n1.consistent_read(|| async { unreachable!("must not read on a follower") }).await;
+
+        match res {
+            Err(CheckIsLeaderError::ForwardToLeader(e)) => {
+                assert_eq!(Some(0), e.leader_id);
+            }
+            Err(e) => panic!("expect ForwardToLeader, got: {:?}", e),
+            Ok(()) => panic!("expect ForwardToLeader, got Ok"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::error::CheckIsLeaderError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `read_index()` returns the committed log id on the leader once it is applied; on a follower it is rejected.
+///
+/// What does this test do?
+///
+/// - create a stable 3-node cluster.
+/// - write a value through the leader.
+/// - call `read_index()` on the leader and assert it returns the log id of the write, which is applied.
+/// - call `read_index()` on a follower and assert it returns `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn read_index() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- write a value through the leader");
+    {
+        router
+            .send_client_request(0, ClientRequest {
+                client: "foo".to_string(),
+                serial: 1,
+                status: "bar".to_string(),
+            })
+            .await?;
+        log_index += 1;
+    }
+
+    tracing::info!("--- read_index on the leader returns the last write");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+
+        let read_log_id = n0.read_index().await?;
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), log_index)), read_log_id);
+
+        let applied = n0.metrics().borrow().last_applied;
+        assert!(applied >= read_log_id);
+    }
+
+    tracing::info!("--- read_index on a follower is rejected");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+
+        let res = n1.read_index().await;
+
+        match res {
+            Err(CheckIsLeaderError::ForwardToLeader(e)) => {
+                assert_eq!(Some(0), e.leader_id);
+            }
+            Err(e) => panic!("expect ForwardToLeader, got: {:?}", e),
+            Ok(x) => panic!("expect ForwardToLeader, got Ok({:?})", x),
+        }
+    }
+
+    Ok(())
+}
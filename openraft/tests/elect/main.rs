@@ -9,3 +9,11 @@ mod fixtures;
 // The later tests may depend on the earlier ones.
 
 mod t10_elect_compare_last_log;
+mod t20_vote_events;
+mod t30_elect_tie_break;
+mod t40_pre_vote;
+mod t50_step_down;
+mod t60_elect_now;
+mod t70_election_priority;
+mod t80_leader_changes;
+mod t90_election_timeout_seed;
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::Vote;
+use openraft::VoteEvent;
+use openraft::VoteReason;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Every received vote request is reported by `Raft::vote_events()` along with the decision and reason.
+///
+/// What does this test do?
+///
+/// - Bring up a single node cluster and write several logs.
+/// - Send a vote request with a higher term but a stale last log id, assert it is rejected with `LogNotFreshEnough`.
+/// - Send a vote request with an up-to-date last log id but a lower term, assert it is rejected with `TermTooLow`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn vote_events() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    log_index += router.client_request_many(0, "foo", 5).await?;
+    router.wait(&0, None).log(Some(log_index), "write logs").await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let mut rx = n0.vote_events();
+
+    let term = n0.metrics().borrow().current_term;
+    let my_vote = Vote::new_committed(term, 0);
+    let my_last_log_id = Some(LogId::new(LeaderId::new(my_vote.term, 0), log_index));
+
+    tracing::info!("--- vote request with a stale last log id is rejected");
+    {
+        let stale = Some(LogId::new(LeaderId::new(my_vote.term, 0), log_index - 1));
+        let resp = n0.vote(VoteRequest::new(Vote::new(my_vote.term + 10, 1), stale)).await?;
+        assert!(!resp.vote_granted);
+
+        let ev = rx.recv().await?;
+        assert_eq!(
+            VoteEvent {
+                from: 1,
+                term: my_vote.term + 10,
+                granted: false,
+                reason: VoteReason::LogNotFreshEnough { my_last_log_id },
+            },
+            ev
+        );
+    }
+
+    tracing::info!("--- vote request with a lower term is rejected");
+    {
+        let resp = n0.vote(VoteRequest::new(Vote::new(0, 1), my_last_log_id)).await?;
+        assert!(!resp.vote_granted);
+
+        let ev = rx.recv().await?;
+        assert_eq!(
+            VoteEvent {
+                from: 1,
+                term: 0,
+                granted: false,
+                reason: VoteReason::TermTooLow { my_vote },
+            },
+            ev
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftNetwork;
+use openraft::RaftNetworkFactory;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Two candidates with equally fresh logs in the same term: the one with the greater node id wins the vote.
+///
+/// `Vote` is totally ordered by `(term, node_id)`, thus a voter that has granted a candidate still grants another
+/// candidate of the same term with a greater node id, but never the other way around.
+/// The last-log-id freshness check is applied before comparing votes and is never overridden.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes, with election timeout disabled.
+/// - Send vote requests of the same term from node 1 and node 2 to node 0, in both orders.
+/// - Assert node 0 always ends up voting for node 2, no matter which request arrives first.
+/// - Assert a candidate with a greater node id but a staler log is rejected.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_tie_break() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let last_log_id = Some(LogId::new(LeaderId::new(1, 0), log_index));
+
+    tracing::info!("--- term 5: node 1 then node 2, both are granted");
+    {
+        let resp = router.new_client(0, &()).await?.send_vote(VoteRequest::new(Vote::new(5, 1), last_log_id)).await?;
+        assert!(resp.vote_granted);
+
+        let resp = router.new_client(0, &()).await?.send_vote(VoteRequest::new(Vote::new(5, 2), last_log_id)).await?;
+        assert!(resp.vote_granted);
+        assert_eq!(Vote::new(5, 2), resp.vote);
+    }
+
+    tracing::info!("--- term 6: node 2 then node 1, node 1 is rejected");
+    {
+        let resp = router.new_client(0, &()).await?.send_vote(VoteRequest::new(Vote::new(6, 2), last_log_id)).await?;
+        assert!(resp.vote_granted);
+
+        let resp = router.new_client(0, &()).await?.send_vote(VoteRequest::new(Vote::new(6, 1), last_log_id)).await?;
+        assert!(!resp.vote_granted);
+        assert_eq!(Vote::new(6, 2), resp.vote);
+    }
+
+    tracing::info!("--- term 7: node 1 with a fresh log, then node 2 with a stale log, node 2 is rejected");
+    {
+        let resp = router.new_client(0, &()).await?.send_vote(VoteRequest::new(Vote::new(7, 1), last_log_id)).await?;
+        assert!(resp.vote_granted);
+
+        let stale = Some(LogId::new(LeaderId::new(1, 0), log_index - 1));
+        let resp = router.new_client(0, &()).await?.send_vote(VoteRequest::new(Vote::new(7, 2), stale)).await?;
+        assert!(!resp.vote_granted);
+        assert_eq!(Vote::new(7, 1), resp.vote);
+    }
+
+    Ok(())
+}
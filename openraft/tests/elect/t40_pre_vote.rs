@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::VoteRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftNetwork;
+use openraft::RaftNetworkFactory;
+use openraft::ServerState;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With pre-vote enabled, a node that is partitioned away does not increase its term, and does not disrupt the
+/// leader when it reconnects.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes with pre-vote enabled.
+/// - Assert a pre-vote request is rejected by a follower that hears from the leader, and it does not change the vote.
+/// - Isolate node 2 for several election timeouts, assert its term does not increase.
+/// - Restore node 2, assert node 0 is still the leader of term 1.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn pre_vote() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_pre_vote: true,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- a follower that hears from the leader rejects pre-vote");
+    {
+        let last_log_id = Some(LogId::new(LeaderId::new(1, 0), log_index));
+        let resp = router
+            .new_client(1, &())
+            .await?
+            .send_pre_vote(VoteRequest::new(Vote::new(2, 2), last_log_id))
+            .await?;
+
+        assert!(!resp.vote_granted);
+        assert_eq!(Vote::new_committed(1, 0), resp.vote);
+
+        let n1 = router.get_raft_handle(&1)?;
+        assert_eq!(1, n1.metrics().borrow().current_term);
+    }
+
+    tracing::info!("--- isolate node 2, its term does not increase");
+    {
+        router.isolate_node(2);
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max * 5)).await;
+
+        let n2 = router.get_raft_handle(&2)?;
+        assert_eq!(1, n2.metrics().borrow().current_term);
+    }
+
+    tracing::info!("--- restore node 2, node 0 is still the leader");
+    {
+        router.restore_node(2);
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max * 2)).await;
+
+        let n0 = router.get_raft_handle(&0)?;
+        let m = n0.metrics().borrow().clone();
+        assert_eq!(ServerState::Leader, m.state);
+        assert_eq!(1, m.current_term);
+
+        let n2 = router.get_raft_handle(&2)?;
+        assert_eq!(1, n2.metrics().borrow().current_term);
+    }
+
+    Ok(())
+}
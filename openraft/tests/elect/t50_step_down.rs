@@ -0,0 +1,140 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use memstore::IntoMemClientRequest;
+use openraft::error::ClientWriteError;
+use openraft::error::StepDownError;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A leader transfers its leadership to a caught up voter with `Raft::step_down()`.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes, node 0 is the leader.
+/// - Call `step_down()` on a follower, expect a ForwardToLeader error.
+/// - Call `step_down()` on node 0, expect the returned target becomes the leader and node 0 becomes a follower.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn step_down() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- step_down() on a follower is rejected");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.step_down().await;
+
+        match res {
+            Err(StepDownError::ForwardToLeader(fwd)) => {
+                assert_eq!(Some(0), fwd.leader_id);
+            }
+            _ => {
+                unreachable!("expect ForwardToLeader, got: {:?}", res);
+            }
+        }
+    }
+
+    tracing::info!("--- step_down() on the leader transfers leadership");
+    {
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "all nodes catch up").await?;
+
+        let n0 = router.get_raft_handle(&0)?;
+        let target = n0.step_down().await?;
+        assert!(target == 1 || target == 2, "target must be a follower, got: {}", target);
+
+        router.wait(&target, timeout()).state(ServerState::Leader, "target becomes leader").await?;
+        router.wait(&0, timeout()).state(ServerState::Follower, "node 0 becomes follower").await?;
+        router.wait(&0, timeout()).current_leader(target, "node 0 follows the new leader").await?;
+    }
+
+    Ok(())
+}
+
+/// While the leader waits for the transfer target to catch up, it does not accept writes.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes, node 0 is the leader.
+/// - Isolate node 1 and 2 and write a log on node 0, which can not be replicated.
+/// - Call `step_down()` on node 0, expect a write on node 0 to be rejected with ForwardToLeader.
+/// - Expect `step_down()` to return NoCaughtUpFollower, since no voter catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn step_down_rejects_writes_until_target_catches_up() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+    router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "all nodes catch up").await?;
+
+    tracing::info!("--- isolate node 1,2 and write a log that can not be replicated");
+    {
+        router.isolate_node(1);
+        router.isolate_node(2);
+
+        let n0 = router.get_raft_handle(&0)?;
+        tokio::spawn(async move {
+            let _ = n0.client_write(ClientRequest::make_request("foo", 1)).await;
+        });
+
+        router
+            .wait(&0, timeout())
+            .metrics(|x| x.last_log_index == Some(log_index + 1), "node 0 appends a log")
+            .await?;
+    }
+
+    tracing::info!("--- step_down() on node 0 rejects writes while waiting");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let step_down = tokio::spawn(async move { n0.step_down().await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let res = router.send_client_request(0, ClientRequest::make_request("foo", 2)).await;
+        match res {
+            Err(ClientWriteError::ForwardToLeader(fwd)) => {
+                assert!(fwd.leader_id.is_none());
+            }
+            _ => {
+                unreachable!("expect ForwardToLeader, got: {:?}", res);
+            }
+        }
+
+        let res = step_down.await?;
+        match res {
+            Err(StepDownError::NoCaughtUpFollower(e)) => {
+                assert_eq!(Some(log_index + 1), e.last_log_id.map(|x| x.index));
+            }
+            _ => {
+                unreachable!("expect NoCaughtUpFollower, got: {:?}", res);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::ElectNowError;
+use openraft::Config;
+use openraft::RaftStorage;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::elect_now()` starts an election on a follower at once.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 voters and 1 learner, with election timeout disabled.
+/// - `elect_now()` on the leader and on the learner are rejected.
+/// - `elect_now()` on a follower makes it the leader.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn elect_now() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    tracing::info!("--- elect_now() on the leader is rejected");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.elect_now().await;
+        assert!(matches!(res, Err(ElectNowError::AlreadyLeader(_))), "got: {:?}", res);
+    }
+
+    tracing::info!("--- elect_now() on a learner is rejected");
+    {
+        let n3 = router.get_raft_handle(&3)?;
+        let res = n3.elect_now().await;
+        assert!(matches!(res, Err(ElectNowError::NotAVoter(_))), "got: {:?}", res);
+    }
+
+    tracing::info!("--- elect_now() on a follower makes it the leader");
+    {
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "all voters catch up").await?;
+
+        let n1 = router.get_raft_handle(&1)?;
+        n1.elect_now().await?;
+
+        router.wait(&1, timeout()).state(ServerState::Leader, "node 1 becomes leader").await?;
+        router.wait(&0, timeout()).current_leader(1, "node 0 follows node 1").await?;
+    }
+
+    tracing::info!("--- current_vote() returns the saved vote");
+    {
+        let vote = router.get_raft_handle(&1)?.current_vote().await?;
+        assert_eq!(1, vote.node_id);
+        assert!(vote.term > 1, "node 1 is elected in a new term");
+
+        let mut sto1 = router.get_storage_handle(&1)?;
+        assert_eq!(Some(vote), sto1.read_vote().await?);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A node with a lower `election_priority` is still able to become the leader when the nodes with higher priority
+/// are down.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 voters, then restart node 2 with a higher election priority.
+/// - Node 2 starts an election, so that the other nodes see its priority in the vote request.
+/// - Isolate node 2: one of node 0 and node 1 still becomes the leader, after a bounded delay.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn election_priority() -> Result<()> {
+    let config = Arc::new(Config::default().validate()?);
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- restart node 2 with a higher priority");
+    {
+        let (n2, sto2) = router.remove_node(2).unwrap();
+        n2.shutdown().await?;
+
+        let config2 = Arc::new(
+            Config {
+                election_priority: 10,
+                ..Default::default()
+            }
+            .validate()?,
+        );
+        router.new_raft_node_with_config(2, sto2, config2);
+
+        router.wait(&2, timeout()).log(Some(log_index), "node 2 restarted").await?;
+    }
+
+    tracing::info!("--- node 2 starts an election and becomes the leader");
+    {
+        let n2 = router.get_raft_handle(&2)?;
+        n2.elect_now().await?;
+
+        router.wait(&2, timeout()).state(ServerState::Leader, "node 2 becomes leader").await?;
+        router.wait(&0, timeout()).current_leader(2, "node 0 follows node 2").await?;
+        router.wait(&1, timeout()).current_leader(2, "node 1 follows node 2").await?;
+    }
+
+    tracing::info!("--- isolate node 2, node 0 or node 1 becomes the leader");
+    {
+        router.isolate_node(2);
+
+        router
+            .wait(&0, timeout())
+            .metrics(
+                |m| m.current_leader == Some(0) || m.current_leader == Some(1),
+                "node 0 or node 1 becomes leader",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderChanged;
+use openraft::ServerState;
+use tokio::sync::broadcast;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::leader_changes()` sends every change of the believed leader, on the nodes stepping down and becoming the
+/// leader.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 voters with node 0 as the leader.
+/// - Node 1 starts an election and becomes the leader.
+/// - Assert node 0 and node 1 both see the leader lost, then node 1 as the leader, in the new term.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leader_changes() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n1 = router.get_raft_handle(&1)?;
+
+    let mut changes0 = n0.leader_changes();
+    let mut changes1 = n1.leader_changes();
+
+    tracing::info!("--- node 1 becomes the leader");
+    {
+        n1.elect_now().await?;
+        router.wait(&1, timeout()).state(ServerState::Leader, "node 1 becomes leader").await?;
+        router.wait(&0, timeout()).current_leader(1, "node 0 follows node 1").await?;
+
+        let want = vec![LeaderChanged { term: 2, leader: None }, LeaderChanged {
+            term: 2,
+            leader: Some(1),
+        }];
+
+        assert_eq!(want, recv_until_leader(&mut changes0, 1).await?, "node 0 steps down");
+        assert_eq!(
+            want,
+            recv_until_leader(&mut changes1, 1).await?,
+            "node 1 becomes leader"
+        );
+    }
+
+    Ok(())
+}
+
+/// Every leader change is sent, even if several of them happen when handling one message.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 2 voters with node 0 as the leader.
+/// - Remove node 0, node 1 still believes in node 0 as the leader.
+/// - Node 1 starts an election, which it wins at once as the only voter.
+/// - Assert node 1 sees the leader changing from node 0 to none and then to node 1.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn leader_changes_in_one_message() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+
+    tracing::info!("--- remove node 0, node 1 still believes in node 0");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.change_membership(btreeset! {1}, true, false).await?;
+        log_index += 2;
+
+        router
+            .wait(&1, timeout())
+            .metrics(
+                |x| x.last_log_index == Some(log_index),
+                "node 1 receives the membership logs",
+            )
+            .await?;
+        router.wait(&1, timeout()).current_leader(0, "node 1 follows node 0").await?;
+    }
+
+    tracing::info!("--- node 1 elects itself at once");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let mut changes1 = n1.leader_changes();
+
+        n1.elect_now().await?;
+        router.wait(&1, timeout()).state(ServerState::Leader, "node 1 becomes leader").await?;
+
+        let want = vec![LeaderChanged { term: 2, leader: None }, LeaderChanged {
+            term: 2,
+            leader: Some(1),
+        }];
+        assert_eq!(want, recv_until_leader(&mut changes1, 1).await?);
+    }
+
+    Ok(())
+}
+
+/// Receive leader changes until `leader` is seen.
+async fn recv_until_leader(
+    rx: &mut broadcast::Receiver<LeaderChanged<u64>>,
+    leader: u64,
+) -> Result<Vec<LeaderChanged<u64>>> {
+    let mut res = vec![];
+    loop {
+        let ev = tokio::time::timeout(timeout().unwrap(), rx.recv()).await??;
+        let done = ev.leader == Some(leader);
+        res.push(ev);
+        if done {
+            return Ok(res);
+        }
+    }
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
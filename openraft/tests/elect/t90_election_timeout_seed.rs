@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `Config::election_timeout_seed`, elections are reproducible: nodes sharing a seed sample different
+/// timeouts, and the node with the shortest one is elected on every run.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes with the same seed, then restart all of them at once.
+/// - Wait for a leader to be elected by election timeout.
+/// - Repeat it several times and assert the same leader is elected on every run.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn election_timeout_seed() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            // With seed 10, node 1 samples the shortest election timeouts.
+            election_timeout_seed: Some(10),
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut leaders = vec![];
+
+    for i in 0..3 {
+        tracing::info!("--- run {}: bring up a cluster of 3 nodes", i);
+
+        let mut router = RaftRouter::new(config.clone());
+        let log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+        tracing::info!("--- run {}: restart all nodes", i);
+
+        let mut stores = vec![];
+        for id in 0..3 {
+            let (node, sto) = router.remove_node(id).unwrap();
+            node.shutdown().await?;
+            stores.push((id, sto));
+        }
+
+        for (id, sto) in stores {
+            router.new_raft_node_with_sto(id, sto);
+        }
+
+        tracing::info!("--- run {}: wait for a leader to be elected", i);
+
+        let metrics = router
+            .wait(&0, timeout())
+            .metrics(
+                |x| x.current_term >= 2 && x.current_leader.is_some(),
+                "a leader is elected after restart",
+            )
+            .await?;
+        let leader = metrics.current_leader.unwrap();
+
+        router.wait(&leader, timeout()).state(ServerState::Leader, "leader is elected").await?;
+        router
+            .wait(&leader, timeout())
+            .log_at_least(Some(log_index + 1), "leader commits a blank log")
+            .await?;
+
+        leaders.push(leader);
+    }
+
+    assert_eq!(vec![1, 1, 1], leaders);
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -42,6 +42,8 @@ use openraft::raft::AppendEntriesRequest;
 use openraft::raft::AppendEntriesResponse;
 use openraft::raft::InstallSnapshotRequest;
 use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::TimeoutNowRequest;
+use openraft::raft::TimeoutNowResponse;
 use openraft::raft::VoteRequest;
 use openraft::raft::VoteResponse;
 use openraft::storage::RaftLogReader;
@@ -359,7 +361,26 @@ where
 
     #[tracing::instrument(level = "debug", skip(self, sto))]
     pub fn new_raft_node_with_sto(&mut self, id: C::NodeId, sto: StoreWithDefensive<C, S>) {
-        let node = Raft::new(id, self.config.clone(), self.clone(), sto.clone());
+        let config = self.config.clone();
+        self.new_raft_node_with_config(id, sto, config)
+    }
+
+    /// Create and register a new Raft node with its own config, instead of the config of the router.
+    #[tracing::instrument(level = "debug", skip(self, sto, config))]
+    pub fn new_raft_node_with_config(&mut self, id: C::NodeId, sto: StoreWithDefensive<C, S>, config: Arc<Config>) {
+        let node = Raft::new(id, config, self.clone(), sto.clone());
+        let mut rt = self.routing_table.lock().unwrap();
+        rt.insert(id, (node, sto));
+    }
+
+    /// Create and register a new Raft node with a hook that observes every applied log entry.
+    pub fn new_raft_node_with_apply_hook(
+        &mut self,
+        id: C::NodeId,
+        sto: StoreWithDefensive<C, S>,
+        apply_hook: impl FnMut(&Entry<C>, &C::R) + Send + 'static,
+    ) {
+        let node = Raft::with_apply_hook(id, self.config.clone(), self.clone(), sto.clone(), apply_hook);
         let mut rt = self.routing_table.lock().unwrap();
         rt.insert(id, (node, sto));
     }
@@ -418,7 +439,7 @@ where
 
     pub fn get_metrics(&self, node_id: &C::NodeId) -> Result<RaftMetrics<C::NodeId, C::Node>> {
         let node = self.get_raft_handle(node_id)?;
-        let metrics = node.metrics().borrow().clone();
+        let metrics = node.metrics_snapshot();
         Ok(metrics)
     }
 
@@ -1039,6 +1060,36 @@ where
         let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
         Ok(resp)
     }
+
+    /// Send a pre-vote RPC to the target Raft node.
+    async fn send_pre_vote(
+        &mut self,
+        rpc: VoteRequest<C::NodeId>,
+    ) -> std::result::Result<VoteResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>> {
+        self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.pre_vote(rpc).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+        Ok(resp)
+    }
+
+    /// Send a timeout-now RPC to the target Raft node.
+    async fn send_timeout_now(
+        &mut self,
+        rpc: TimeoutNowRequest<C::NodeId>,
+    ) -> std::result::Result<TimeoutNowResponse<C::NodeId>, RPCError<C::NodeId, C::Node, VoteError<C::NodeId>>> {
+        self.owner.check_reachable(rpc.vote.node_id, self.target)?;
+        self.owner.rand_send_delay().await;
+
+        let node = self.owner.get_raft_handle(&self.target)?;
+
+        let resp = node.timeout_now(rpc).await;
+        let resp = resp.map_err(|e| RemoteError::new(self.target, e))?;
+        Ok(resp)
+    }
 }
 
 pub enum ValueTest<T> {
@@ -6,3 +6,7 @@
 mod fixtures;
 
 mod t10_compaction;
+mod t20_retain_log_tail;
+mod t30_compaction_by_size;
+mod t31_compaction_never;
+mod t40_purge_log;
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftLogReader;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A tail of `max_in_snapshot_log_to_keep` logs is retained after compaction,
+/// so that a follower slightly behind catches up by replicating logs instead of installing a snapshot.
+///
+/// What does this test do?
+///
+/// - Bring up a leader and a learner, write 10 logs.
+/// - Isolate the learner, write 3 more logs and build a snapshot on the leader.
+/// - Assert the last 5 logs survive and older ones are purged.
+/// - Restore the learner, which lags 3 logs behind, assert it catches up without receiving a snapshot.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn retain_log_tail() -> Result<()> {
+    let max_keep = 5;
+
+    let config = Arc::new(
+        Config {
+            max_in_snapshot_log_to_keep: max_keep,
+            purge_batch_size: 1,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {1}).await?;
+
+    let leader = router.get_raft_handle(&0)?;
+    let learner = router.get_raft_handle(&1)?;
+
+    tracing::info!("--- write logs to leader and learner");
+    {
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait(&1, timeout()).log(Some(log_index), "learner receives 10 logs").await?;
+    }
+
+    tracing::info!("--- isolate learner, write logs and build snapshot on leader");
+    {
+        router.isolate_node(1);
+
+        log_index += router.client_request_many(0, "0", 3).await?;
+        router.wait(&0, timeout()).log(Some(log_index), "write 3 logs").await?;
+
+        leader.trigger_snapshot().await?;
+        leader
+            .wait(timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot")
+            .await?;
+    }
+
+    tracing::info!("--- check only the log tail is retained on leader");
+    {
+        let mut sto0 = router.get_storage_handle(&0)?;
+
+        let logs = sto0.try_get_log_entries(..).await?;
+        let indexes = logs.iter().map(|x| x.log_id.index).collect::<Vec<_>>();
+        assert_eq!(((log_index + 1 - max_keep)..=log_index).collect::<Vec<_>>(), indexes);
+
+        let log_state = sto0.get_log_state().await?;
+        assert_eq!(
+            Some(LogId::new(LeaderId::new(1, 0), log_index - max_keep)),
+            log_state.last_purged_log_id
+        );
+    }
+
+    tracing::info!("--- restore learner, it catches up with logs in the tail");
+    {
+        router.restore_node(1);
+
+        log_index += router.client_request_many(0, "0", 1).await?;
+        learner.wait(timeout()).log(Some(log_index), "learner catches up").await?;
+
+        let snapshot = learner.metrics().borrow().snapshot;
+        assert_eq!(None, snapshot, "learner does not install snapshot");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,227 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::raft::AppendEntriesRequest;
+use openraft::Config;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftStorage;
+use openraft::ServerState;
+use openraft::SnapshotPolicy;
+use openraft::Vote;
+
+use crate::fixtures::blank;
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `SnapshotPolicy::SizeSinceLast`, a snapshot is built once the serialized size of the applied logs
+/// not in the last snapshot reaches the threshold, no matter how many logs there are.
+///
+/// What does this test do?
+///
+/// - Bring up a single node cluster, with the threshold set to the size of 5 entries with a 1000 bytes status.
+/// - Write 4 such entries, assert no snapshot is built.
+/// - Write the 5th entry, assert a snapshot is built at it.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn compaction_by_size() -> Result<()> {
+    let sto = memstore::MemStore::new_async().await;
+    let entry_size = sto.serialized_size(&EntryPayload::Normal(req(1))).unwrap();
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::SizeSinceLast(entry_size * 5),
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- write 4 entries, below the threshold");
+    {
+        for serial in 1..=4 {
+            n0.client_write(req(serial)).await?;
+            log_index += 1;
+        }
+
+        let metrics = n0.wait(timeout()).log(Some(log_index), "write 4 entries").await?;
+        assert_eq!(None, metrics.snapshot);
+    }
+
+    tracing::info!("--- write the 5th entry, reach the threshold");
+    {
+        n0.client_write(req(5)).await?;
+        log_index += 1;
+
+        n0.wait(timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot by size")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Logs deleted by a conflict are never applied thus are not counted by `SnapshotPolicy::SizeSinceLast`.
+///
+/// What does this test do?
+///
+/// - Bring up a learner, with the threshold set to the size of 5 entries with a 1000 bytes status.
+/// - Append 5 such entries to it without committing them.
+/// - Replace them with a blank log from a new leader, and commit it.
+/// - Assert no snapshot is built.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn compaction_by_size_ignores_conflicting_logs() -> Result<()> {
+    let sto = memstore::MemStore::new_async().await;
+    let entry_size = sto.serialized_size(&EntryPayload::Normal(req(1))).unwrap();
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::SizeSinceLast(entry_size * 5),
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    router.new_raft_node(0);
+
+    let n0 = router.get_raft_handle(&0)?;
+    n0.wait(timeout()).state(ServerState::Learner, "empty").await?;
+
+    tracing::info!("--- append 5 uncommitted entries");
+    {
+        let mut entries = vec![blank(0, 0)];
+        entries.extend((1..=5).map(|i| Entry {
+            log_id: LogId::new(LeaderId::new(1, 0), i),
+            payload: EntryPayload::Normal(req(i)),
+        }));
+
+        let resp = n0
+            .append_entries(AppendEntriesRequest {
+                vote: Vote::new_committed(1, 0),
+                prev_log_id: None,
+                entries,
+                leader_commit: None,
+            })
+            .await?;
+        assert!(resp.is_success());
+
+        n0.wait(timeout()).metrics(|x| x.last_log_index == Some(5), "append 5 entries").await?;
+    }
+
+    tracing::info!("--- replace them with a blank log and commit it");
+    {
+        let resp = n0
+            .append_entries(AppendEntriesRequest {
+                vote: Vote::new_committed(2, 0),
+                prev_log_id: Some(LogId::new(LeaderId::new(0, 0), 0)),
+                entries: vec![blank(2, 1)],
+                leader_commit: Some(LogId::new(LeaderId::new(2, 0), 1)),
+            })
+            .await?;
+        assert!(resp.is_success());
+
+        n0.wait(timeout()).log(Some(1), "conflicting logs are deleted").await?;
+    }
+
+    tracing::info!("--- no snapshot is built");
+    {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(None, n0.metrics().borrow().snapshot);
+    }
+
+    Ok(())
+}
+
+/// The applied logs not in the last snapshot are counted again when a node restarts.
+///
+/// What does this test do?
+///
+/// - Bring up a single node cluster, with the threshold set to the size of 5 entries with a 1000 bytes status.
+/// - Write 3 such entries, assert no snapshot is built.
+/// - Restart the node and write 2 more entries, assert a snapshot is built at the last one.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn compaction_by_size_after_restart() -> Result<()> {
+    let sto = memstore::MemStore::new_async().await;
+    let entry_size = sto.serialized_size(&EntryPayload::Normal(req(1))).unwrap();
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::SizeSinceLast(entry_size * 5),
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write 3 entries, below the threshold");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        for serial in 1..=3 {
+            n0.client_write(req(serial)).await?;
+            log_index += 1;
+        }
+
+        let metrics = n0.wait(timeout()).log(Some(log_index), "write 3 entries").await?;
+        assert_eq!(None, metrics.snapshot);
+    }
+
+    tracing::info!("--- restart node-0");
+    {
+        let (n0, sto0) = router.remove_node(0).unwrap();
+        n0.shutdown().await?;
+
+        router.new_raft_node_with_sto(0, sto0);
+
+        let n0 = router.get_raft_handle(&0)?;
+        n0.trigger_elect().await?;
+        n0.wait(timeout()).state(ServerState::Leader, "leader after restart").await?;
+    }
+
+    tracing::info!("--- write 2 more entries, reach the threshold");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+
+        let resp = n0.client_write(req(4)).await?;
+        let metrics = n0.wait(timeout()).log_at_least(Some(resp.log_id.index), "write the 4th entry").await?;
+        assert_eq!(None, metrics.snapshot);
+
+        let resp = n0.client_write(req(5)).await?;
+
+        n0.wait(timeout())
+            .metrics(
+                |x| x.snapshot == Some(resp.log_id),
+                "build snapshot by size after restart",
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn req(serial: u64) -> ClientRequest {
+    ClientRequest {
+        client: "foo".to_string(),
+        serial,
+        status: "x".repeat(1000),
+    }
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftLogReader;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// With `SnapshotPolicy::Never`, no snapshot is built automatically, but one built on demand is
+/// still sent to a follower that lacks the purged logs.
+///
+/// What does this test do?
+///
+/// - Bring up a single node cluster with `SnapshotPolicy::Never`, write 20 logs.
+/// - Assert no snapshot is built and no log is purged.
+/// - Trigger a snapshot, add a learner, assert the learner receives this snapshot.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn compaction_never() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::Never,
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- write logs, no snapshot is built");
+    {
+        log_index += router.client_request_many(0, "0", 20).await?;
+
+        let metrics = n0.wait(timeout()).log(Some(log_index), "write 20 logs").await?;
+        assert_eq!(None, metrics.snapshot);
+
+        let mut sto0 = router.get_storage_handle(&0)?;
+        let logs = sto0.try_get_log_entries(..).await?;
+        assert_eq!(log_index + 1, logs.len() as u64, "no log is purged");
+    }
+
+    tracing::info!("--- trigger a snapshot, a new learner receives it");
+    {
+        n0.trigger_snapshot().await?;
+        n0.wait(timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot on demand")
+            .await?;
+
+        router.new_raft_node(1);
+        router.add_learner(0, 1).await?;
+
+        router
+            .wait(&1, timeout())
+            .snapshot(LogId::new(LeaderId::new(1, 0), log_index), "learner installs snapshot")
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::NotInSnapshot;
+use openraft::error::PurgeLogError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftLogReader;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Purge logs that are in snapshot with `Raft::purge_log()`, without building a new snapshot.
+///
+/// What does this test do?
+///
+/// - Bring up a single node cluster, write 10 logs and build a snapshot, with a batch size that purges nothing.
+/// - Purging logs not in snapshot is refused.
+/// - Purge logs upto an index in snapshot, then upto the last log in snapshot.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn purge_log() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1000,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let mut sto0 = router.get_storage_handle(&0)?;
+
+    tracing::info!("--- write logs and build snapshot");
+    {
+        log_index += router.client_request_many(0, "0", 10).await?;
+        router.wait(&0, timeout()).log(Some(log_index), "write 10 logs").await?;
+
+        n0.trigger_snapshot().await?;
+        n0.wait(timeout()).snapshot(LogId::new(LeaderId::new(1, 0), log_index), "build snapshot").await?;
+
+        let log_state = sto0.get_log_state().await?;
+        assert_eq!(None, log_state.last_purged_log_id, "batch size is not reached");
+    }
+
+    tracing::info!("--- purging logs not in snapshot is refused");
+    {
+        let err = n0.purge_log(log_index + 1).await.unwrap_err();
+        match err {
+            PurgeLogError::NotInSnapshot(e) => assert_eq!(
+                NotInSnapshot {
+                    index: log_index + 1,
+                    snapshot_last_log_id: Some(LogId::new(LeaderId::new(1, 0), log_index)),
+                },
+                e
+            ),
+            _ => panic!("expect NotInSnapshot, got: {:?}", err),
+        }
+    }
+
+    tracing::info!("--- purge logs upto 5");
+    {
+        let purged = n0.purge_log(5).await?;
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), 5)), purged);
+
+        let logs = sto0.try_get_log_entries(..).await?;
+        assert_eq!(6, logs[0].log_id.index);
+    }
+
+    tracing::info!("--- purge all logs in snapshot");
+    {
+        let purged = n0.purge_log(log_index).await?;
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), log_index)), purged);
+
+        let logs = sto0.try_get_log_entries(..).await?;
+        assert!(logs.is_empty());
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
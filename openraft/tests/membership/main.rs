@@ -11,9 +11,13 @@ mod fixtures;
 mod t00_learner_restart;
 mod t01_single_node;
 mod t10_add_learner;
+mod t11_add_learner_until;
 mod t12_concurrent_write_and_add_learner;
+mod t13_list_learners;
 mod t15_add_remove_follower;
 mod t16_change_membership_cases;
+mod t17_change_membership_quorum_not_live;
+mod t18_new_leader_replicates_to_learner;
 mod t20_change_membership;
 mod t25_elect_with_new_config;
 mod t30_commit_joint_config;
@@ -3,6 +3,8 @@ use std::time::Duration;
 
 use anyhow::Result;
 use maplit::btreeset;
+use openraft::error::AddLearnerError;
+use openraft::raft::CatchUpTarget;
 use openraft::Config;
 use openraft::LeaderId;
 use openraft::LogId;
@@ -117,6 +119,91 @@ async fn add_learner_non_blocking() -> Result<()> {
     Ok(())
 }
 
+/// `add_learner_until()` with a lag target blocks until the replication to the learner lags at most the given number of
+/// logs, no matter what `replication_lag_threshold` is.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_learner_until_lag() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            // With this threshold a blocking `add_learner()` returns at once.
+            replication_lag_threshold: 5000,
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write up to 1000 logs");
+    {
+        router.client_request_many(0, "learner_add", 1000 - log_index as usize).await?;
+        log_index = 1000;
+
+        router.wait(&0, timeout()).log(Some(log_index), "received 1000 logs").await?;
+    }
+
+    tracing::info!("--- add node-1 and wait for it to be fully caught up");
+    {
+        router.new_raft_node(1);
+        let raft = router.get_raft_handle(&0)?;
+        let res = raft.add_learner_until(1, (), CatchUpTarget::Lag(0)).await?;
+        log_index += 1;
+
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), log_index)), res.matched);
+    }
+
+    Ok(())
+}
+
+/// `add_learner_until()` with a lag target returns ForwardToLeader if this node is no longer the leader before the
+/// learner catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_learner_until_lag_leader_lost() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+    router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "node 1 catches up").await?;
+
+    tracing::info!("--- add an isolated node-2, which never catches up");
+    let adding = {
+        router.new_raft_node(2);
+        router.isolate_node(2);
+
+        let n0 = router.get_raft_handle(&0)?;
+        tokio::spawn(async move { n0.add_learner_until(2, (), CatchUpTarget::Lag(0)).await })
+    };
+
+    router
+        .wait(&0, timeout())
+        .metrics(|x| x.membership_config.membership.contains(&2), "node-2 is added")
+        .await?;
+
+    tracing::info!("--- node-0 steps down, add_learner_until() returns ForwardToLeader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.step_down().await?;
+
+        let res = adding.await?;
+        match res {
+            Err(AddLearnerError::ForwardToLeader(_)) => {}
+            _ => {
+                unreachable!("expect ForwardToLeader, got: {:?}", res);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// add a learner, then shutdown the leader to make leader transferred,
 /// check after new leader come, the learner can receive new log.
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
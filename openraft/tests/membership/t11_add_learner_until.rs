@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::AddLearnerError;
+use openraft::error::ChangeMembershipError;
+use openraft::raft::CatchUpTarget;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Add a learner that is removed automatically once it catches up to a target log index.
+///
+/// What does this test do?
+///
+/// - Bring up a single node cluster and write several logs.
+/// - Add node-1 with a catch-up target.
+/// - Assert node-1 received logs upto the target, and is removed from the membership and the replication.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_learner_until() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_tick: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write up to 50 logs");
+    {
+        router.client_request_many(0, "foo", 50 - log_index as usize).await?;
+        log_index = 50;
+
+        router.wait_for_log(&btreeset! {0}, Some(log_index), timeout(), "write 50 logs").await?;
+    }
+
+    tracing::info!("--- add node-1 as a learner until it catches up to log 30");
+    {
+        router.new_raft_node(1);
+
+        let n0 = router.get_raft_handle(&0)?;
+        let res = n0.add_learner_until(1, (), CatchUpTarget::Index(30)).await?;
+        log_index += 2; // add learner, remove learner
+
+        tracing::info!("add_learner_until res: {:?}", res);
+
+        assert!(res.matched.unwrap().index >= 30, "learner should have caught up");
+        assert_eq!(Some(log_index), res.membership_log_id.map(|x| x.index));
+    }
+
+    tracing::info!("--- node-1 is removed from membership and replication");
+    {
+        router.wait_for_log(&btreeset! {0}, Some(log_index), timeout(), "remove learner").await?;
+
+        let metrics = router.get_metrics(&0)?;
+        assert!(!metrics.membership_config.membership.nodes().any(|(id, _)| *id == 1));
+
+        let repl = metrics.replication.unwrap();
+        assert!(!repl.data().replication.contains_key(&1));
+    }
+
+    Ok(())
+}
+
+/// `add_learner_until()` returns ForwardToLeader if this node is no longer the leader before the learner catches up.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_learner_until_leader_lost() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+    router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "node 1 catches up").await?;
+
+    tracing::info!("--- add an isolated node-2, which never catches up");
+    let adding = {
+        router.new_raft_node(2);
+        router.isolate_node(2);
+
+        let n0 = router.get_raft_handle(&0)?;
+        tokio::spawn(async move { n0.add_learner_until(2, (), CatchUpTarget::Index(100)).await })
+    };
+
+    router
+        .wait(&0, timeout())
+        .metrics(|x| x.membership_config.membership.contains(&2), "node-2 is added")
+        .await?;
+
+    tracing::info!("--- node-0 steps down, add_learner_until() returns ForwardToLeader");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.step_down().await?;
+
+        let res = adding.await?;
+        match res {
+            Err(AddLearnerError::ForwardToLeader(_)) => {}
+            _ => {
+                unreachable!("expect ForwardToLeader, got: {:?}", res);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `add_learner_until()` returns LearnerNotFound if the learner is removed by others before it catches up.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of node-0 and node-1.
+/// - Add an isolated node-2 with a catch-up target it never reaches.
+/// - Make node-2 a voter then remove it, with two membership changes.
+/// - Assert `add_learner_until()` returns LearnerNotFound.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn add_learner_until_learner_removed() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {}).await?;
+    router.wait_for_log(&btreeset! {0,1}, Some(log_index), timeout(), "node 1 catches up").await?;
+
+    tracing::info!("--- add an isolated node-2, which never catches up");
+    let adding = {
+        router.new_raft_node(2);
+        router.isolate_node(2);
+
+        let n0 = router.get_raft_handle(&0)?;
+        tokio::spawn(async move { n0.add_learner_until(2, (), CatchUpTarget::Index(100)).await })
+    };
+
+    router
+        .wait(&0, timeout())
+        .metrics(|x| x.membership_config.membership.contains(&2), "node-2 is added")
+        .await?;
+
+    tracing::info!("--- remove node-2, add_learner_until() returns LearnerNotFound");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        n0.change_membership(btreeset! {0,1,2}, true, false).await?;
+        n0.change_membership(btreeset! {0,1}, true, false).await?;
+
+        let res = adding.await?;
+        match res {
+            Err(AddLearnerError::ChangeMembershipError(ChangeMembershipError::LearnerNotFound(e))) => {
+                assert_eq!(2, e.node_id);
+            }
+            _ => {
+                unreachable!("expect LearnerNotFound, got: {:?}", res);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
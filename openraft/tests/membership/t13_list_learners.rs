@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::error::CheckIsLeaderError;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::list_learners()` returns the replication state of every learner and whether it is ready to join.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 2 voters and 2 learners.
+/// - Isolate learner 3 and write more logs than `replication_lag_threshold`.
+/// - Assert learner 2 is ready to join while learner 3 is not.
+/// - Assert a follower returns `ForwardToLeader`.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn list_learners() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            replication_lag_threshold: 10,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1}, btreeset! {2,3}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- all learners are up to date");
+    {
+        let learners = n0.list_learners().await?;
+        let ids = learners.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(vec![2, 3], ids);
+
+        for (id, st) in learners {
+            assert_eq!(
+                Some(LogId::new(LeaderId::new(1, 0), log_index)),
+                st.replication.matched,
+                "learner {} matched",
+                id
+            );
+            assert!(st.ready_to_join, "learner {} is ready to join", id);
+        }
+    }
+
+    tracing::info!("--- isolate learner 3 and write logs");
+    {
+        router.isolate_node(3);
+
+        router.client_request_many(0, "foo", 20).await?;
+        log_index += 20;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write 20 logs").await?;
+
+        let learners = n0.list_learners().await?;
+        assert_eq!(2, learners.len());
+
+        let (id, st) = &learners[0];
+        assert_eq!(2, *id);
+        assert_eq!(Some(log_index), st.replication.matched.map(|x| x.index));
+        assert!(st.ready_to_join, "learner 2 catches up");
+
+        let (id, st) = &learners[1];
+        assert_eq!(3, *id);
+        assert!(!st.ready_to_join, "learner 3 lags behind");
+    }
+
+    tracing::info!("--- a follower forwards to the leader");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        let res = n1.list_learners().await;
+
+        match res {
+            Err(CheckIsLeaderError::ForwardToLeader(f)) => {
+                assert_eq!(Some(0), f.leader_id);
+            }
+            other => panic!("expect ForwardToLeader, got: {:?}", other),
+        }
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
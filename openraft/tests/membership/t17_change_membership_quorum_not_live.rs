@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use maplit::btreeset;
+use openraft::error::ChangeMembershipError;
+use openraft::error::ClientWriteError;
+use openraft::ChangeMembers;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A membership change with `Raft::change_membership_checked()` is rejected if the live voters of the new membership
+/// do not form a quorum.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 5 voters, isolate node 3 and 4 until they have not responded for longer than
+///   `election_timeout_max`, then write a log, which node 1 and 2 respond to.
+/// - Removing node 1 and 2 leaves only node 0 live in {0,3,4}: it is rejected with `QuorumNotLive`.
+/// - Removing node 3 and 4 leaves {0,1,2} all live: it succeeds.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn change_membership_quorum_not_live() -> anyhow::Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2,3,4}, btreeset! {}).await?;
+
+    tracing::info!("--- isolate node 3 and 4 and write a log");
+    {
+        router.isolate_node(3);
+        router.isolate_node(4);
+
+        tokio::time::sleep(Duration::from_millis(config.election_timeout_max + 100)).await;
+
+        router.client_request_many(0, "foo", 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "write a log").await?;
+    }
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- removing node 1 and 2 is rejected");
+    {
+        let res = n0.change_membership_checked(ChangeMembers::Remove(btreeset! {1,2}), false, false).await;
+
+        match res {
+            Err(ClientWriteError::ChangeMembershipError(ChangeMembershipError::QuorumNotLive(e))) => {
+                assert_eq!(btreeset! {0,3,4}, e.members);
+                assert_eq!(btreeset! {0}, e.live);
+            }
+            other => panic!("expect QuorumNotLive, got: {:?}", other),
+        }
+    }
+
+    tracing::info!("--- removing node 3 and 4 succeeds");
+    {
+        n0.change_membership_checked(ChangeMembers::Remove(btreeset! {3,4}), false, false).await?;
+        log_index += 2;
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), timeout(), "removed node 3 and 4").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Learners are part of the membership in the log, thus a new leader keeps replicating to them.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 voters and 1 learner, with node 0 as the leader.
+/// - Elect node 1 as the new leader.
+/// - Write logs on node 1 and assert the learner receives them.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn new_leader_replicates_to_learner() -> anyhow::Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {3}).await?;
+
+    tracing::info!("--- elect node 1");
+    {
+        let n1 = router.get_raft_handle(&1)?;
+        n1.elect_now().await?;
+
+        router.wait(&1, timeout()).state(ServerState::Leader, "node 1 becomes leader").await?;
+
+        // The new leader commits a blank log.
+        log_index += 1;
+        router.wait_for_log(&btreeset! {0,1,2,3}, Some(log_index), timeout(), "blank log of node 1").await?;
+    }
+
+    tracing::info!("--- write logs on node 1, the learner receives them");
+    {
+        router.client_request_many(1, "foo", 10).await?;
+        log_index += 10;
+
+        router.wait_for_log(&btreeset! {0,1,2,3}, Some(log_index), timeout(), "write 10 logs").await?;
+
+        let m3 = router.get_metrics(&3)?;
+        assert_eq!(ServerState::Learner, m3.state);
+        assert_eq!(Some(1), m3.current_leader);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
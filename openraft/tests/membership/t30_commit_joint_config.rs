@@ -60,6 +60,8 @@ async fn commit_joint_config_during_0_to_012() -> Result<()> {
     router.isolate_node(1);
     router.isolate_node(2);
 
+    assert!(!router.get_metrics(&0)?.in_joint_consensus);
+
     tracing::info!("--- changing cluster config, should timeout");
 
     tokio::spawn({
@@ -81,6 +83,16 @@ async fn commit_joint_config_during_0_to_012() -> Result<()> {
         .await;
     assert!(res.is_err(), "joint log should not commit");
 
+    let m = router.get_metrics(&0)?;
+    assert!(
+        m.in_joint_consensus,
+        "the uncommitted joint config is reported in metrics"
+    );
+    assert_eq!(
+        &vec![btreeset! {0}, btreeset! {0,1,2}],
+        m.membership_config.membership.get_joint_config()
+    );
+
     Ok(())
 }
 
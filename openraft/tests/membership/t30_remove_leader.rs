@@ -186,6 +186,83 @@ async fn remove_leader_access_new_cluster() -> Result<()> {
     Ok(())
 }
 
+/// Change membership from {0,1,2} to {1,2}.
+///
+/// - The leader steps down only after the membership log that removes it is committed.
+/// - A new leader is elected from the remaining nodes.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn remove_leader_step_down_after_commit() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            election_timeout_min: 800,
+            election_timeout_max: 1000,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    tracing::info!("--- watch the committed log id when node-0 steps down");
+    let step_down = {
+        let mut rx_metrics = n0.metrics();
+        let rx_committed = n0.committed();
+
+        tokio::spawn(async move {
+            loop {
+                if rx_metrics.borrow().state != ServerState::Leader {
+                    return *rx_committed.borrow();
+                }
+                if rx_metrics.changed().await.is_err() {
+                    return None;
+                }
+            }
+        })
+    };
+
+    tracing::info!("--- change membership 012 to 12");
+    {
+        n0.change_membership(btreeset![1, 2], true, false).await?;
+        // 2 change_membership logs
+        log_index += 2;
+    }
+
+    tracing::info!("--- node-0 steps down after the membership log is committed");
+    {
+        let committed = step_down.await?;
+        assert!(
+            committed.map(|x| x.index) >= Some(log_index),
+            "committed when stepping down: {:?}, expect >= {}",
+            committed,
+            log_index
+        );
+    }
+
+    tracing::info!("--- a new leader is elected from 12");
+    {
+        let m = router
+            .wait(&1, timeout())
+            .metrics(
+                |x| x.current_leader == Some(1) || x.current_leader == Some(2),
+                "node-1 sees a new leader in 12",
+            )
+            .await?;
+        let leader = m.current_leader.unwrap();
+
+        router.wait(&leader, timeout()).state(ServerState::Leader, "new leader").await?;
+        router
+            .wait(&0, timeout())
+            .metrics(|x| x.state != ServerState::Leader, "node-0 is not leader")
+            .await?;
+    }
+
+    Ok(())
+}
+
 fn timeout() -> Option<Duration> {
     Some(Duration::from_millis(3_000))
 }
@@ -12,3 +12,6 @@ mod t10_current_leader;
 mod t20_metrics_state_machine_consistency;
 mod t30_leader_metrics;
 mod t40_metrics_wait;
+mod t50_wait_for_leadership;
+mod t60_metrics_report_interval;
+mod t70_term_jumps;
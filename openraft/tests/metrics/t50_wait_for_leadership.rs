@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::wait_for_leadership()` and `Raft::wait_for_non_leadership()` resolve when leadership is acquired or lost.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes, with node 0 as the leader.
+/// - Assert node 0 has leadership and node 1 does not; waiting for leadership on node 1 times out.
+/// - Trigger an election on node 1.
+/// - Await leadership on node 1, the winner, and non-leadership on node 0, the loser.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn wait_for_leadership() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+    let n1 = router.get_raft_handle(&1)?;
+
+    tracing::info!("--- node 0 is the leader, node 1 is not");
+    {
+        let m = n0.wait_for_leadership(None).await?;
+        assert_eq!(ServerState::Leader, m.state);
+        assert_eq!(Some(0), m.current_leader);
+
+        let m = n1.wait_for_non_leadership(None).await?;
+        assert_ne!(ServerState::Leader, m.state);
+
+        let res = n1.wait_for_leadership(Some(Duration::from_millis(200))).await;
+        assert!(res.is_err(), "node 1 is not a leader: {:?}", res);
+    }
+
+    tracing::info!("--- elect node 1, wait for leadership on node 1 and non-leadership on node 0");
+    {
+        n1.trigger_elect().await?;
+
+        let timeout = Some(Duration::from_millis(3_000));
+
+        let m = n1.wait_for_leadership(timeout).await?;
+        assert_eq!(ServerState::Leader, m.state);
+        assert_eq!(Some(1), m.current_leader);
+
+        let m = n0.wait_for_non_leadership(timeout).await?;
+        assert_ne!(ServerState::Leader, m.state);
+    }
+
+    Ok(())
+}
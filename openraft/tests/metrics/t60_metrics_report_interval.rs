@@ -0,0 +1,80 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use tokio::time::Instant;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Metrics are reported at most once per `Config::metrics_report_min_interval`.
+///
+/// What does this test do?
+///
+/// - brings up a single node cluster with a metrics report interval of 500 ms.
+/// - writes a burst of logs while counting the metrics reports.
+/// - asserts the pending metrics are eventually reported, and the number of reports is bounded by the interval.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn metrics_report_interval() -> Result<()> {
+    let interval = 500;
+
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            metrics_report_min_interval: interval,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let n0 = router.get_raft_handle(&0)?;
+
+    let reports = Arc::new(AtomicU64::new(0));
+    {
+        let reports = reports.clone();
+        let mut rx = n0.metrics();
+        tokio::spawn(async move {
+            while rx.changed().await.is_ok() {
+                reports.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+
+    tracing::info!("--- write a burst of logs");
+    let start = Instant::now();
+    {
+        log_index += router.client_request_many(0, "foo", 100).await?;
+        router
+            .wait_for_log(
+                &btreeset! {0},
+                Some(log_index),
+                timeout(),
+                "pending metrics are reported",
+            )
+            .await?;
+    }
+    let elapsed = start.elapsed();
+
+    let got = reports.load(Ordering::Relaxed);
+    let max = elapsed.as_millis() as u64 / interval + 2;
+    assert!(
+        got <= max,
+        "got {} reports in {:?}, expect at most {}",
+        got,
+        elapsed,
+        max
+    );
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
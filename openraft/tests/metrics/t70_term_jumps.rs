@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A leader counts the replication targets that report a term more than 1 ahead of it in `RaftMetrics::term_jumps`.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 voters with node 0 as the leader.
+/// - Isolate node 2 and let it start several elections, so that its term is far ahead.
+/// - Restore node 2: the leader sees the higher term in a heartbeat response and reports it in metrics.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn term_jumps() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let leader_term = router.get_metrics(&0)?.current_term;
+    assert_eq!(0, router.get_metrics(&0)?.term_jumps);
+
+    tracing::info!("--- isolate node 2 and elect several times");
+    {
+        router.isolate_node(2);
+
+        let n2 = router.get_raft_handle(&2)?;
+        for i in 1..=3 {
+            n2.elect_now().await?;
+            router
+                .wait(&2, timeout())
+                .metrics(|m| m.current_term == leader_term + i, "node 2 term increased")
+                .await?;
+        }
+    }
+
+    tracing::info!("--- restore node 2, the leader observes the term jump");
+    {
+        router.restore_node(2);
+
+        router.wait(&0, timeout()).metrics(|m| m.term_jumps >= 1, "leader reports term jump").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
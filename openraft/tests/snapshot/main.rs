@@ -7,9 +7,13 @@ mod fixtures;
 
 mod t20_api_install_snapshot;
 mod t20_trigger_snapshot;
+mod t21_snapshot_receive_timeout;
 mod t23_snapshot_chunk_size;
 mod t24_snapshot_when_lacking_log;
 mod t25_snapshot_line_rate_to_snapshot;
+mod t26_snapshot_transfer_metrics;
+mod t27_snapshot_meta_metrics;
+mod t28_snapshot_retry_backoff;
 mod t40_after_snapshot_add_learner_and_request_a_log;
 mod t40_purge_in_snapshot_logs;
 mod t41_snapshot_overrides_membership;
@@ -79,6 +79,10 @@ async fn snapshot_arguments() -> Result<()> {
     {
         let req = req0.clone();
         n.0.install_snapshot(req).await?;
+
+        n.0.wait(timeout())
+            .metrics(|m| m.snapshot_received_bytes == Some(3), "received 3 bytes of ss1")
+            .await?;
     }
 
     tracing::info!("-- continue write with different id");
@@ -104,6 +108,10 @@ async fn snapshot_arguments() -> Result<()> {
         req.offset = 3;
         req.meta.snapshot_id = "ss2".into();
         n.0.install_snapshot(req).await?;
+
+        n.0.wait(timeout())
+            .metrics(|m| m.snapshot_received_bytes == Some(6), "received 6 bytes of ss2")
+            .await?;
     }
 
     tracing::info!("-- continue write with mismatched offset is allowed");
@@ -10,6 +10,8 @@ use crate::fixtures::init_default_ut_tracing;
 use crate::fixtures::RaftRouter;
 
 /// Manually trigger a snapshot with `Raft::trigger_snapshot()` on Leader and Follower.
+///
+/// `Raft::trigger_snapshot()` returns when the snapshot is built. Concurrent calls both get a snapshot.
 #[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
 async fn trigger_snapshot() -> anyhow::Result<()> {
     let config = Arc::new(
@@ -28,7 +30,8 @@ async fn trigger_snapshot() -> anyhow::Result<()> {
     tracing::info!("--- trigger snapshot for node-1");
     {
         let n1 = router.get_raft_handle(&1)?;
-        n1.trigger_snapshot().await?;
+        let meta = n1.trigger_snapshot().await?.unwrap();
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), log_index)), meta.last_log_id);
 
         router
             .wait(&1, timeout())
@@ -48,7 +51,8 @@ async fn trigger_snapshot() -> anyhow::Result<()> {
     tracing::info!("--- trigger snapshot for node-0");
     {
         let n0 = router.get_raft_handle(&0)?;
-        n0.trigger_snapshot().await?;
+        let meta = n0.trigger_snapshot().await?.unwrap();
+        assert_eq!(Some(LogId::new(LeaderId::new(1, 0), log_index)), meta.last_log_id);
 
         router
             .wait(&0, timeout())
@@ -56,6 +60,19 @@ async fn trigger_snapshot() -> anyhow::Result<()> {
             .await?;
     }
 
+    tracing::info!("--- trigger snapshot for node-0 twice at the same time");
+    {
+        router.client_request_many(0, "0", 10).await?;
+        log_index += 10;
+
+        let n0 = router.get_raft_handle(&0)?;
+        let (res1, res2) = futures::join!(n0.trigger_snapshot(), n0.trigger_snapshot());
+
+        let want = Some(LogId::new(LeaderId::new(1, 0), log_index));
+        assert_eq!(want, res1?.unwrap().last_log_id);
+        assert_eq!(want, res2?.unwrap().last_log_id);
+    }
+
     Ok(())
 }
 
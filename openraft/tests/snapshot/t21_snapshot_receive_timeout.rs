@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotMeta;
+use openraft::Vote;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// A snapshot that is never finalized is dropped after `Config::snapshot_receive_timeout`.
+///
+/// What does this test do?
+///
+/// - Build a single node cluster.
+/// - Send the first chunk of a snapshot and never send the rest.
+/// - Assert the partially received snapshot is dropped after the timeout: continuing the stream is rejected.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_receive_timeout() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            snapshot_receive_timeout: 500,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let _log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    let (n0, _sto0) = router.remove_node(0).unwrap();
+
+    let req0 = InstallSnapshotRequest {
+        vote: Vote::new_committed(1, 0),
+        meta: SnapshotMeta {
+            snapshot_id: "ss1".into(),
+            last_log_id: Some(LogId::new(LeaderId::new(1, 0), 0)),
+            last_membership: Default::default(),
+        },
+        offset: 0,
+        data: vec![1, 2, 3],
+        done: false,
+    };
+
+    tracing::info!("--- send the first chunk of ss1");
+    {
+        n0.install_snapshot(req0.clone()).await?;
+
+        n0.wait(timeout())
+            .metrics(|m| m.snapshot_received_bytes == Some(3), "received 3 bytes of ss1")
+            .await?;
+    }
+
+    tracing::info!("--- the stalled snapshot is dropped after the timeout");
+    {
+        n0.wait(timeout()).metrics(|m| m.snapshot_received_bytes.is_none(), "ss1 is dropped").await?;
+
+        let mut req = req0.clone();
+        req.offset = 3;
+        let res = n0.install_snapshot(req).await;
+        assert_eq!(
+            "snapshot segment id mismatch, expect: ss1+0, got: ss1+3",
+            res.unwrap_err().to_string()
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::RaftNetwork;
+use openraft::RaftNetworkFactory;
+use openraft::RaftSnapshotBuilder;
+use openraft::RaftStorage;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Metrics `snapshots_sent` and `snapshots_received` count snapshot transfers.
+///
+/// - build a stable single node cluster and send enough logs to build a snapshot and purge logs.
+/// - add a learner, which has to install the snapshot.
+/// - assert `snapshots_sent` on the leader and `snapshots_received` on the learner are both incremented.
+/// - send the learner a snapshot that is not newer than its committed log, which is cancelled.
+/// - assert `snapshots_received` on the learner is not incremented.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_transfer_metrics() -> Result<()> {
+    let snapshot_threshold: u64 = 20;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- send logs to build a snapshot");
+    {
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        router
+            .wait_for_snapshot(
+                &btreeset![0],
+                LogId::new(LeaderId::new(1, 0), log_index),
+                None,
+                "snapshot",
+            )
+            .await?;
+
+        let m = router.get_metrics(&0)?;
+        assert_eq!(0, m.snapshots_sent);
+        assert_eq!(0, m.snapshots_received);
+    }
+
+    tracing::info!("--- add learner to receive snapshot");
+    {
+        router.new_raft_node(1);
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), None, "add learner").await?;
+
+        router.wait(&0, timeout()).metrics(|x| x.snapshots_sent == 1, "leader sent 1 snapshot").await?;
+        router
+            .wait(&1, timeout())
+            .metrics(|x| x.snapshots_received == 1, "learner received 1 snapshot")
+            .await?;
+
+        assert_eq!(0, router.get_metrics(&0)?.snapshots_received);
+        assert_eq!(0, router.get_metrics(&1)?.snapshots_sent);
+    }
+
+    tracing::info!("--- send a snapshot that is cancelled by the learner");
+    {
+        let mut sto0 = router.get_storage_handle(&0)?;
+
+        let snap = {
+            let mut b = sto0.get_snapshot_builder().await;
+            b.build_snapshot().await?
+        };
+        assert_eq!(Some(log_index), snap.meta.last_log_id.map(|x| x.index));
+
+        let req = InstallSnapshotRequest {
+            vote: sto0.read_vote().await?.unwrap(),
+            meta: snap.meta.clone(),
+            offset: 0,
+            data: snap.snapshot.into_inner(),
+            done: true,
+        };
+
+        router.new_client(1, &()).await?.send_install_snapshot(req).await?;
+
+        // Wait for the metrics to be reported.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let m = router.get_metrics(&1)?;
+        assert_eq!(1, m.snapshots_received);
+        assert_ne!(Some(snap.meta.snapshot_id), m.snapshot_meta.map(|x| x.snapshot_id));
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
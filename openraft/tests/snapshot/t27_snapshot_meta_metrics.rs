@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::LeaderId;
+use openraft::LogId;
+use openraft::SnapshotPolicy;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// Metrics `snapshot_meta` reports the meta of the last snapshot built or installed.
+///
+/// - build a stable single node cluster, assert there is no snapshot meta.
+/// - send enough logs to build a snapshot, assert the snapshot meta on the leader.
+/// - add a learner, which has to install the snapshot, assert it reports the same snapshot meta.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_meta_metrics() -> Result<()> {
+    let snapshot_threshold: u64 = 20;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            max_in_snapshot_log_to_keep: 0,
+            purge_batch_size: 1,
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    assert_eq!(None, router.get_metrics(&0)?.snapshot_meta);
+
+    tracing::info!("--- send logs to build a snapshot");
+    let leader_meta = {
+        router.client_request_many(0, "0", (snapshot_threshold - 1 - log_index) as usize).await?;
+        log_index = snapshot_threshold - 1;
+
+        let want = LogId::new(LeaderId::new(1, 0), log_index);
+        router.wait_for_snapshot(&btreeset![0], want, None, "snapshot").await?;
+
+        let meta = router.get_metrics(&0)?.snapshot_meta;
+        let meta = meta.expect("leader has a snapshot");
+        assert_eq!(Some(want), meta.last_log_id);
+        meta
+    };
+
+    tracing::info!("--- add learner to receive snapshot");
+    {
+        router.new_raft_node(1);
+        router.add_learner(0, 1).await?;
+        log_index += 1;
+
+        router.wait_for_log(&btreeset![0, 1], Some(log_index), None, "add learner").await?;
+
+        let m = router
+            .wait(&1, timeout())
+            .metrics(|x| x.snapshot_meta.is_some(), "learner installed snapshot")
+            .await?;
+        assert_eq!(Some(leader_meta), m.snapshot_meta);
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::FaultPoint;
+use memstore::FaultPolicy;
+use memstore::FaultyStore;
+use memstore::MemStore;
+use openraft::Config;
+use openraft::ServerState;
+use openraft::SnapshotPolicy;
+use tokio::time::sleep;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::TypedRaftRouter;
+
+type FaultyRouter = TypedRaftRouter<memstore::Config, FaultyStore<memstore::Config, Arc<MemStore>>>;
+
+/// A leader that fails to build a snapshot for a replication target does not retry in a busy loop.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 1 voter and 1 learner, then isolate the learner.
+/// - Make building snapshot on the leader always fail with a transient error, and write enough logs so that the learner
+///   needs a snapshot.
+/// - Assert building snapshot is retried with a backoff, and the leader keeps running.
+/// - Remove the fault and restore the learner: it receives a snapshot.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn snapshot_retry_backoff() -> Result<()> {
+    let snapshot_threshold: u64 = 10;
+
+    let config = Arc::new(
+        Config {
+            snapshot_policy: SnapshotPolicy::LogsSinceLast(snapshot_threshold),
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+    let mut router = FaultyRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {1}).await?;
+
+    let sto0 = router.get_storage_handle(&0)?;
+
+    tracing::info!("--- isolate node 1, fail building snapshot on node 0");
+    {
+        router.isolate_node(1);
+        sto0.set_transient_fault(FaultPoint::BuildSnapshot, FaultPolicy::Probability { p: 1.0, seed: 0 });
+
+        let n = snapshot_threshold * 2;
+        router.client_request_many(0, "0", n as usize).await?;
+        log_index += n;
+
+        router.wait(&0, timeout()).log(Some(log_index), "write logs to node 0").await?;
+    }
+
+    tracing::info!("--- building snapshot is retried with backoff");
+    {
+        sleep(Duration::from_millis(500)).await;
+        let calls = sto0.calls(FaultPoint::BuildSnapshot);
+        assert!(calls > 0);
+
+        sleep(Duration::from_millis(1_000)).await;
+        let more = sto0.calls(FaultPoint::BuildSnapshot) - calls;
+        assert!(more <= 5, "expect at most 5 retries in 1 second, got: {}", more);
+
+        let m0 = router.get_metrics(&0)?;
+        assert_eq!(ServerState::Leader, m0.state);
+        assert!(m0.snapshot.is_none());
+    }
+
+    tracing::info!("--- remove the fault, node 1 receives a snapshot");
+    {
+        sto0.clear_fault(FaultPoint::BuildSnapshot);
+        router.restore_node(1);
+
+        router.client_request_many(0, "0", 1).await?;
+        log_index += 1;
+
+        router
+            .wait(&1, Some(Duration::from_millis(5_000)))
+            .log(Some(log_index), "node 1 catches up")
+            .await?;
+        router.wait(&1, timeout()).metrics(|m| m.snapshot.is_some(), "node 1 installs a snapshot").await?;
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(2_000))
+}
@@ -10,3 +10,6 @@ mod fixtures;
 
 mod t10_total_order_apply;
 mod t20_state_machine_apply_membership;
+mod t30_committed_notification;
+mod t40_apply_fault_on_membership;
+mod t50_apply_hook;
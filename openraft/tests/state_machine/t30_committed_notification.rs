@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::ClientRequest;
+use openraft::Config;
+use openraft::LogIdOptionExt;
+use openraft::RaftStorage;
+use tokio::time::timeout;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// `Raft::committed()` notifies every advance of the committed log id before the logs are applied.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 nodes.
+/// - Subscribe to the committed log id channel of every node.
+/// - Write several logs and collect the committed log ids every node observed.
+/// - Assert the observed committed log ids are monotonically increasing and are never behind the applied log id.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn committed_notification() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    tracing::info!("--- subscribe to committed log id on every node");
+    let mut handles = vec![];
+    for id in 0..3 {
+        let mut rx = router.get_raft_handle(&id)?.committed();
+        let mut sto = router.get_storage_handle(&id)?;
+
+        assert_eq!(Some(log_index), rx.borrow().index(), "n{} initial committed", id);
+
+        let h = tokio::spawn(async move {
+            let mut observed = vec![];
+            while rx.changed().await.is_ok() {
+                // Read the applied log id first: the committed log id is sent before applying.
+                let (applied, _) = sto.last_applied_state().await.unwrap();
+                let committed = *rx.borrow();
+
+                assert!(
+                    applied <= committed,
+                    "n{}: applied {:?} should not be greater than committed {:?}",
+                    id,
+                    applied,
+                    committed
+                );
+                observed.push(committed);
+            }
+            observed
+        });
+        handles.push(h);
+    }
+
+    tracing::info!("--- write logs and check committed is not behind applied");
+    {
+        let n0 = router.get_raft_handle(&0)?;
+        let rx0 = n0.committed();
+
+        for i in 0..10 {
+            let resp = n0
+                .client_write(ClientRequest {
+                    client: "foo".to_string(),
+                    serial: i,
+                    status: format!("request-{}", i),
+                })
+                .await?;
+            log_index += 1;
+
+            assert_eq!(log_index, resp.log_id.index);
+            assert!(
+                rx0.borrow().index() >= Some(log_index),
+                "committed must be notified no later than applied"
+            );
+            assert!(n0.is_committed(log_index));
+            assert!(!n0.is_committed(log_index + 1));
+        }
+
+        router.wait_for_log(&btreeset! {0,1,2}, Some(log_index), None, "replicate logs").await?;
+
+        for id in 1..3 {
+            let n = router.get_raft_handle(&id)?;
+            let committed = timeout(Duration::from_millis(1_000), n.wait_committed(log_index)).await??;
+            assert_eq!(log_index, committed.index);
+            assert!(
+                n.is_committed(log_index),
+                "n{} knows log {} is committed",
+                id,
+                log_index
+            );
+        }
+    }
+
+    tracing::info!("--- shutdown and check observed committed log ids");
+    {
+        for id in 0..3 {
+            let (node, _) = router.remove_node(id).unwrap();
+            node.shutdown().await?;
+        }
+
+        for (id, h) in handles.into_iter().enumerate() {
+            let observed = h.await?;
+            tracing::info!("n{} observed committed: {:?}", id, observed);
+
+            assert!(!observed.is_empty(), "n{} should observe committed log ids", id);
+            for w in observed.windows(2) {
+                assert!(
+                    w[0] < w[1],
+                    "n{}: committed log ids must be increasing: {:?}",
+                    id,
+                    observed
+                );
+            }
+            assert_eq!(Some(log_index), observed.last().unwrap().index());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use memstore::FaultPoint;
+use memstore::FaultPolicy;
+use memstore::FaultyStore;
+use memstore::MemStore;
+use openraft::Config;
+use openraft::EntryPayload;
+use openraft::LogIdOptionExt;
+use openraft::RaftStorageDebug;
+use openraft::ServerState;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::TypedRaftRouter;
+
+type FaultyRouter = TypedRaftRouter<memstore::Config, FaultyStore<memstore::Config, Arc<MemStore>>>;
+
+/// A failure applying a membership entry shuts down the node, after the normal entries before it are applied.
+///
+/// The fault fails the whole `apply_to_state_machine()` call, thus no entry in the failed batch is applied.
+///
+/// What does this test do?
+///
+/// - bring up a single node cluster and write several normal entries.
+/// - make the store fail to apply membership entries only.
+/// - add a learner, which proposes a membership entry.
+/// - assert the node shuts down and nothing after the last normal entry is applied.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn apply_fault_on_membership() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_heartbeat: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = FaultyRouter::new(config.clone());
+    let mut log_index = router.new_nodes_from_single(btreeset! {0}, btreeset! {}).await?;
+
+    tracing::info!("--- write normal entries, they are applied without error");
+    {
+        let mut sto = router.get_storage_handle(&0)?;
+        sto.set_fault(
+            FaultPoint::ApplyToStateMachine,
+            FaultPolicy::Payload(Arc::new(|p| matches!(p, EntryPayload::Membership(_)))),
+        );
+
+        router.client_request_many(0, "foo", 10).await?;
+        log_index += 10;
+
+        router.wait(&0, timeout()).log(Some(log_index), "normal entries applied").await?;
+
+        let sm = sto.get_state_machine().await;
+        assert_eq!(Some(log_index), sm.last_applied_log.index());
+    }
+
+    tracing::info!("--- add a learner, the membership entry fails to apply");
+    {
+        router.new_raft_node(1);
+        let _ = router.add_learner(0, 1).await;
+
+        router.wait(&0, timeout()).state(ServerState::Shutdown, "node-0 shut down").await?;
+
+        let metrics = router.get_metrics(&0)?;
+        assert!(metrics.running_state.is_err(), "node-0 quit on apply error");
+
+        let mut sto = router.get_storage_handle(&0)?;
+        let sm = sto.get_state_machine().await;
+        assert_eq!(
+            Some(log_index),
+            sm.last_applied_log.index(),
+            "the membership entry is not applied"
+        );
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(1_000))
+}
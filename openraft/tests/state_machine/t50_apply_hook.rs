@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use maplit::btreeset;
+use openraft::Config;
+use openraft::EntryPayload;
+
+use crate::fixtures::init_default_ut_tracing;
+use crate::fixtures::RaftRouter;
+
+/// The apply hook is called with every log entry applied to the state machine, in log order.
+///
+/// What does this test do?
+///
+/// - Bring up a cluster of 3 voters, then restart follower node 1 with an apply hook.
+/// - Write several logs on the leader.
+/// - Assert the hook on node 1 observed every newly applied log, in order.
+#[async_entry::test(worker_threads = 8, init = "init_default_ut_tracing()", tracing_span = "debug")]
+async fn apply_hook() -> Result<()> {
+    let config = Arc::new(
+        Config {
+            enable_elect: false,
+            ..Default::default()
+        }
+        .validate()?,
+    );
+
+    let mut router = RaftRouter::new(config.clone());
+
+    let mut log_index = router.new_nodes_from_single(btreeset! {0,1,2}, btreeset! {}).await?;
+
+    let applied = Arc::new(Mutex::new(vec![]));
+
+    tracing::info!("--- restart node 1 with an apply hook");
+    {
+        let (n1, sto1) = router.remove_node(1).unwrap();
+        n1.shutdown().await?;
+
+        let applied = applied.clone();
+        router.new_raft_node_with_apply_hook(1, sto1, move |entry, _resp| {
+            let is_normal = matches!(entry.payload, EntryPayload::Normal(_));
+            applied.lock().unwrap().push((entry.log_id.index, is_normal));
+        });
+
+        router.wait(&1, timeout()).log(Some(log_index), "node 1 restarted").await?;
+    }
+
+    tracing::info!("--- write logs on the leader");
+    {
+        let n = 10;
+        router.client_request_many(0, "foo", n).await?;
+        let want = (log_index + 1..=log_index + n as u64).map(|i| (i, true)).collect::<Vec<_>>();
+        log_index += n as u64;
+
+        router.wait(&1, timeout()).log(Some(log_index), "node 1 applied all logs").await?;
+
+        let got = applied.lock().unwrap().clone();
+        assert_eq!(want, got, "the hook observed every applied log in order");
+    }
+
+    Ok(())
+}
+
+fn timeout() -> Option<Duration> {
+    Some(Duration::from_millis(3_000))
+}
@@ -272,6 +272,12 @@ pub struct SledStore {
 
 type StorageResult<T> = Result<T, StorageError<ExampleNodeId>>;
 
+/// The key of the vote in the `store` tree.
+const VOTE_KEY: &[u8] = b"vote";
+
+/// The key of the second copy of the vote, to recover from a torn write of [`VOTE_KEY`].
+const VOTE_BACKUP_KEY: &[u8] = b"vote_backup";
+
 /// converts an id to a byte vector for storing in the database.
 /// Note that we're using big endian encoding to ensure correct sorting of keys
 /// with notes form: https://github.com/spacejam/sled#a-note-on-lexicographic-ordering-and-endianness
@@ -323,19 +329,51 @@ impl SledStore {
         store_tree.flush_async().await.map_err(s_w_err).map(|_| ())
     }
 
+    /// Save the vote in two slots: `vote` first and then `vote_backup`, flushing after each.
+    ///
+    /// A torn write can only damage one of the slots and the other one still holds a complete vote:
+    /// - If writing `vote` is torn, `vote_backup` holds the previous vote.
+    /// - If writing `vote_backup` is torn, `vote` holds the new vote.
     async fn set_vote_(&self, vote: &Vote<ExampleNodeId>) -> StorageResult<()> {
         let store_tree = store(&self.db);
-        let val = serde_json::to_vec(vote).unwrap();
-        store_tree.insert(b"vote", val).map_err(v_w_err).map(|_| ())?;
+        let val = serde_json::to_vec(vote).map_err(v_w_err)?;
+
+        for key in [VOTE_KEY, VOTE_BACKUP_KEY] {
+            store_tree.insert(key, val.as_slice()).map_err(v_w_err)?;
+            store_tree.flush_async().await.map_err(v_w_err)?;
+        }
 
-        store_tree.flush_async().await.map_err(v_w_err).map(|_| ())
+        Ok(())
     }
 
+    /// Read the vote from the `vote` slot, or from `vote_backup` if `vote` is damaged.
+    ///
+    /// It never falls back to `None` when a vote has been saved: if both slots are damaged, it returns an
+    /// error, because a reset vote would let this node vote twice in a term.
     fn get_vote_(&self) -> StorageResult<Option<Vote<ExampleNodeId>>> {
         let store_tree = store(&self.db);
-        let val = store_tree.get(b"vote").map_err(v_r_err)?.and_then(|v| serde_json::from_slice(&v).ok());
 
-        Ok(val)
+        let primary = store_tree.get(VOTE_KEY).map_err(v_r_err)?;
+        let backup = store_tree.get(VOTE_BACKUP_KEY).map_err(v_r_err)?;
+
+        let primary = match primary {
+            None => None,
+            Some(v) => match serde_json::from_slice(&v) {
+                Ok(vote) => return Ok(Some(vote)),
+                Err(e) => {
+                    tracing::warn!(error = display(&e), "torn vote, recover from backup");
+                    Some(e)
+                }
+            },
+        };
+
+        match backup {
+            None => match primary {
+                None => Ok(None),
+                Some(e) => Err(v_r_err(e)),
+            },
+            Some(v) => serde_json::from_slice(&v).map(Some).map_err(v_r_err),
+        }
     }
 
     fn get_current_snapshot_(&self) -> StorageResult<Option<ExampleSnapshot>> {
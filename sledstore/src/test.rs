@@ -6,11 +6,15 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use openraft::testing::StoreBuilder;
 use openraft::testing::Suite;
+use openraft::RaftStorage;
 use openraft::StorageError;
+use openraft::Vote;
 
 use crate::ExampleNodeId;
 use crate::ExampleTypeConfig;
 use crate::SledStore;
+use crate::VOTE_BACKUP_KEY;
+use crate::VOTE_KEY;
 
 static GLOBAL_TEST_COUNT: AtomicUsize = AtomicUsize::new(0);
 
@@ -21,6 +25,29 @@ pub fn test_raft_store() -> Result<(), StorageError<ExampleNodeId>> {
     Suite::test_all(SledBuilder {})
 }
 
+/// A torn write of the vote must recover the previous vote, or fail, but never reset the vote.
+#[async_std::test]
+pub async fn test_recover_torn_vote() -> Result<(), StorageError<ExampleNodeId>> {
+    let td = tempdir::TempDir::new("SledTornVote").expect("couldn't create temp dir");
+    let db: sled::Db = sled::open(td.path()).expect("could not open db");
+    let mut sto = SledStore::new(Arc::new(db)).await;
+
+    sto.save_vote(&Vote::new(2, 1)).await?;
+
+    // Writing the next vote is torn: only a part of it reaches the primary slot.
+    let next = serde_json::to_vec(&Vote::new(3, 2)).unwrap();
+    crate::store(&sto.db).insert(VOTE_KEY, &next[..next.len() / 2]).unwrap();
+
+    assert_eq!(Some(Vote::new(2, 1)), sto.read_vote().await?);
+
+    // Both slots are damaged: fail loudly.
+    crate::store(&sto.db).insert(VOTE_BACKUP_KEY, &next[..1]).unwrap();
+
+    assert!(sto.read_vote().await.is_err());
+
+    Ok(())
+}
+
 #[async_trait]
 impl StoreBuilder<ExampleTypeConfig, Arc<SledStore>> for SledBuilder {
     async fn run_test<Fun, Ret, Res>(&self, t: Fun) -> Result<Ret, StorageError<ExampleNodeId>>